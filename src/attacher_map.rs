@@ -0,0 +1,168 @@
+use crate::native::{format, Map, Range, String, Vec};
+use crate::node::Node;
+use crate::diagnostic::Diagnostic;
+use crate::scalar::ScalarValue;
+use crate::tree::{RangeNode, RangeNodeQueue, resolve};
+
+/// Contains the resolved attachers of a concept keyed by their labels.
+///
+/// Attachers are kept in the order they have been declared. Lookup by label uses a hash map, or a
+/// B-tree map if the `no_std` feature has been activated. Comments among the attachers are skipped.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, AttacherMap};
+/// let source = b"server\n\thost: a\n\thost: b\n\tport: 80\n";
+/// let nodes = parse_source(&source[..]);
+///
+/// let attachers = AttacherMap::of(&source[..], &nodes[0]);
+///
+/// assert_eq!(attachers.get(b"port").unwrap().content, b"80");
+/// assert_eq!(attachers.get_all(b"host").count(), 2);
+/// assert_eq!(attachers.duplicates().len(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AttacherMap<'s> {
+	entries: Vec<AttacherEntry<'s>>,
+	indices: Map<&'s [u8], Vec<usize>>
+}
+
+/// Represents a resolved attacher.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttacherEntry<'s> {
+	pub label: &'s [u8],
+	pub content: &'s [u8],
+	pub label_span: Range<usize>,
+	pub content_span: Range<usize>
+}
+
+impl<'s> AttacherEntry<'s> {
+	/// Refers to the content so that it can be interpreted as a typed value.
+	pub fn value(&self) -> ScalarValue<'s> {
+		ScalarValue::new(self.content, self.content_span.clone())
+	}
+}
+
+impl<'s> AttacherMap<'s> {
+	/// Resolves the attachers from the source.
+	pub fn new(source: &'s [u8], attachers: &RangeNodeQueue) -> Self {
+		let mut map = Self::default();
+
+		for attacher in attachers {
+			if let Node::Attacher(label, content, _) = attacher {
+				let entry = AttacherEntry {
+					label: resolve(source, label),
+					content: resolve(source, content),
+					label_span: label.clone(),
+					content_span: content.clone()
+				};
+				map.indices.entry(entry.label).or_default().push(map.entries.len());
+				map.entries.push(entry);
+			}
+		}
+
+		map
+	}
+
+	/// Resolves the attachers of a concept. Other kinds of nodes have no attachers.
+	pub fn of(source: &'s [u8], node: &RangeNode) -> Self {
+		match node {
+			Node::Complex(_, attachers, _) | Node::Simplex(_, attachers) => Self::new(source, attachers),
+			_ => Self::default()
+		}
+	}
+
+	/// Returns the first attacher with the label.
+	pub fn get(&self, label: &[u8]) -> Option<&AttacherEntry<'s>> {
+		self.get_all(label).next()
+	}
+
+	/// Returns all attachers with the label in declaration order.
+	pub fn get_all<'m>(&'m self, label: &[u8]) -> impl Iterator<Item = &'m AttacherEntry<'s>> {
+		self.indices.get(label)
+			.into_iter()
+			.flatten()
+			.map(move |&index| &self.entries[index])
+	}
+
+	/// Checks if there is an attacher with the label.
+	pub fn contains(&self, label: &[u8]) -> bool {
+		self.indices.contains_key(label)
+	}
+
+	/// Returns all attachers in declaration order.
+	pub fn iter(&self) -> impl Iterator<Item = &AttacherEntry<'s>> {
+		self.entries.iter()
+	}
+
+	/// Returns the number of attachers, including the ones with duplicate labels.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Checks if there are no attachers.
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Returns a warning for every attacher whose label has been declared earlier.
+	pub fn duplicates(&self) -> Vec<Diagnostic> {
+		let mut diagnostics = Vec::new();
+
+		for (index, entry) in self.entries.iter().enumerate() {
+			let first_index = self.indices[entry.label][0];
+			if first_index != index {
+				let first_span = &self.entries[first_index].label_span;
+				let message = format!(
+					"attacher `{}` is repeated; it is first declared at bytes {}..{}",
+					String::from_utf8_lossy(entry.label),
+					first_span.start,
+					first_span.end
+				);
+				let span = entry.label_span.clone();
+				diagnostics.push(Diagnostic::warning(span, "duplicate_attacher", message));
+			}
+		}
+
+		diagnostics
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use super::AttacherMap;
+
+	#[test]
+	fn can_look_up_attachers() {
+		let source = b"a: 1b: 2a: 3";
+		let mut attachers = VecDeque::new();
+		attachers.push_back(Node::Attacher(0..1, 3..4, Vec::new()));
+		attachers.push_back(Node::LineComment(0..4));
+		attachers.push_back(Node::Attacher(4..5, 7..8, Vec::new()));
+		attachers.push_back(Node::Attacher(8..9, 11..12, Vec::new()));
+
+		let map = AttacherMap::new(&source[..], &attachers);
+
+		assert_eq!(map.len(), 3);
+		assert_eq!(map.get(b"a").unwrap().content, b"1");
+		assert_eq!(
+			map.get_all(b"a").map(|entry| entry.content).collect::<Vec<_>>(),
+			[&b"1"[..], &b"3"[..]]
+		);
+		assert_eq!(
+			map.iter().map(|entry| entry.label).collect::<Vec<_>>(),
+			[&b"a"[..], &b"b"[..], &b"a"[..]]
+		);
+		assert!(!map.contains(b"c"));
+
+		let duplicates = map.duplicates();
+		assert_eq!(duplicates.len(), 1);
+		assert_eq!(duplicates[0].span, 8..9);
+		assert_eq!(
+			duplicates[0].message,
+			"attacher `a` is repeated; it is first declared at bytes 0..1"
+		);
+	}
+}
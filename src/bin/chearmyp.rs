@@ -0,0 +1,483 @@
+//! Command-line tool to inspect, check, format, and convert Chearmyp files.
+
+use std::io::{Read, Write};
+use std::process::ExitCode;
+use chearmyp_parser::{
+	Diagnostic,
+	Document,
+	JsonOptions,
+	RangeNodeQueue,
+	Schema,
+	Severity,
+	Symbol,
+	dump,
+	from_json,
+	from_toml,
+	from_xml,
+	from_yaml,
+	lint,
+	outline,
+	parse_source,
+	to_json,
+	to_source,
+	to_toml,
+	to_xml,
+	to_yaml
+};
+
+const USAGE: &str = "\
+usage: chearmyp <command> [options] [files...]
+
+Files are read from the standard input if there are none or if a file is `-`. The outputs of
+several files are each preceded by a `==> file <==` header.
+
+commands:
+	parse [--json] [--spans]          prints the tree as a dump or as JSON
+	tree                              prints the outline of the concepts and their attachers
+	check [--schema <file>]           reports diagnostics; exits with 1 if there are errors
+	fmt [--check | --write]           prints, checks, or rewrites the formatted sources
+	convert --to <format> [--from <format>]
+	                                  converts between chearmyp, json, yaml, toml, and xml; the
+	                                  input format is inferred from the file extension by default";
+
+fn main() -> ExitCode {
+	let arguments = std::env::args().skip(1).collect::<Vec<_>>();
+
+	match run(&arguments) {
+		Ok(code) => code,
+		Err(message) => {
+			eprintln!("error: {}\n\n{}", message, USAGE);
+			ExitCode::from(2)
+		}
+	}
+}
+
+fn run(arguments: &[String]) -> Result<ExitCode, String> {
+	let (command, rest) = arguments.split_first().ok_or("expected a command")?;
+
+	match command.as_str() {
+		"parse" => parse(&Arguments::new(rest, &["--json", "--spans"], &[])?),
+		"tree" => tree(&Arguments::new(rest, &[], &[])?),
+		"check" => check(&Arguments::new(rest, &[], &["--schema"])?),
+		"fmt" => format(&Arguments::new(rest, &["--check", "--write"], &[])?),
+		"convert" => convert(&Arguments::new(rest, &[], &["--to", "--from"])?),
+		"help" | "--help" | "-h" => {
+			println!("{}", USAGE);
+			Ok(ExitCode::SUCCESS)
+		},
+		_ => Err(format!("unknown command `{}`", command))
+	}
+}
+
+/// Contains the options and files of a command.
+struct Arguments {
+	flags: Vec<String>,
+	values: Vec<(String, String)>,
+	files: Vec<String>
+}
+
+impl Arguments {
+	fn new(arguments: &[String], flags: &[&str], valued_options: &[&str]) -> Result<Self, String> {
+		let mut parsed = Self { flags: Vec::new(), values: Vec::new(), files: Vec::new() };
+		let mut arguments = arguments.iter();
+
+		while let Some(argument) = arguments.next() {
+			if flags.contains(&argument.as_str()) {
+				parsed.flags.push(argument.clone());
+			} else if valued_options.contains(&argument.as_str()) {
+				let value = arguments.next().ok_or(format!("`{}` needs a value", argument))?;
+				parsed.values.push((argument.clone(), value.clone()));
+			} else if argument.starts_with("--") {
+				return Err(format!("unknown option `{}`", argument));
+			} else {
+				parsed.files.push(argument.clone());
+			}
+		}
+
+		Ok(parsed)
+	}
+
+	fn has(&self, flag: &str) -> bool {
+		self.flags.iter().any(|parsed_flag| parsed_flag == flag)
+	}
+
+	fn value(&self, option: &str) -> Option<&str> {
+		self.values.iter()
+			.rev()
+			.find(|(parsed_option, _)| parsed_option == option)
+			.map(|(_, value)| value.as_str())
+	}
+
+	fn reads_standard_input(&self) -> bool {
+		self.files.is_empty() || self.files.iter().any(|file| file == "-")
+	}
+
+	fn inputs(&self) -> Result<Vec<Input>, String> {
+		if self.files.is_empty() {
+			return Ok(vec![Input::read("-")?]);
+		}
+
+		self.files.iter().map(|file| Input::read(file)).collect()
+	}
+}
+
+/// Represents the contents of a file or the standard input.
+struct Input {
+	name: String,
+	contents: Vec<u8>
+}
+
+impl Input {
+	fn read(file: &str) -> Result<Self, String> {
+		if file == "-" {
+			let mut contents = Vec::new();
+			std::io::stdin().read_to_end(&mut contents)
+				.map_err(|error| format!("cannot read the standard input: {}", error))?;
+			Ok(Self { name: "<stdin>".into(), contents })
+		} else {
+			let contents = std::fs::read(file)
+				.map_err(|error| format!("cannot read `{}`: {}", file, error))?;
+			Ok(Self { name: file.into(), contents })
+		}
+	}
+}
+
+fn parse(arguments: &Arguments) -> Result<ExitCode, String> {
+	if arguments.has("--spans") && !arguments.has("--json") {
+		return Err("`--spans` can only be used with `--json`".into());
+	}
+
+	let inputs = arguments.inputs()?;
+	let has_headers = inputs.len() > 1;
+
+	for input in &inputs {
+		let nodes = parse_source(&input.contents);
+
+		if has_headers {
+			println!("==> {} <==", input.name);
+		}
+
+		if arguments.has("--json") {
+			let options = JsonOptions { spans: arguments.has("--spans") };
+			let value = to_json(&input.contents, &nodes, options).map_err(|error| {
+				format!("cannot serialize `{}`: {}", input.name, error)
+			})?;
+			println!("{}", serde_json::to_string_pretty(&value).map_err(|error| error.to_string())?);
+		} else {
+			println!("{}", dump(&input.contents, &nodes));
+		}
+	}
+
+	Ok(ExitCode::SUCCESS)
+}
+
+fn tree(arguments: &Arguments) -> Result<ExitCode, String> {
+	let inputs = arguments.inputs()?;
+	let has_headers = inputs.len() > 1;
+
+	for input in &inputs {
+		if has_headers {
+			println!("==> {} <==", input.name);
+		}
+
+		print_symbols(&outline(&input.contents, &parse_source(&input.contents)), 0);
+	}
+
+	Ok(ExitCode::SUCCESS)
+}
+
+fn print_symbols(symbols: &[Symbol], depth: usize) {
+	for symbol in symbols {
+		println!("{}{} ({})", "\t".repeat(depth), symbol.name, symbol.kind.as_str());
+		print_symbols(&symbol.children, depth + 1);
+	}
+}
+
+fn check(arguments: &Arguments) -> Result<ExitCode, String> {
+	let schema = match arguments.value("--schema") {
+		Some(file) => {
+			let input = Input::read(file)?;
+			let schema = Schema::from_source(&input.contents).map_err(|diagnostics| {
+				let mut message = format!("`{}` is not a valid schema", file);
+				for diagnostic in diagnostics {
+					message.push_str(&format!("\n{}", locate(&input, &diagnostic)));
+				}
+				message
+			})?;
+			Some(schema)
+		},
+		None => None
+	};
+	let mut error_count = 0;
+
+	for input in arguments.inputs()? {
+		let mut diagnostics = match Document::parse(&input.contents) {
+			Ok(document) => {
+				let mut diagnostics = lint(&input.contents, document.nodes());
+				if let Some(schema) = &schema {
+					diagnostics.extend(schema.validate(&input.contents, document.nodes()));
+				}
+				diagnostics
+			},
+			Err(diagnostic) => vec![diagnostic]
+		};
+		diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+
+		for diagnostic in &diagnostics {
+			if diagnostic.severity == Severity::Error {
+				error_count += 1;
+			}
+			println!("{}", locate(&input, diagnostic));
+		}
+	}
+
+	if error_count > 0 {
+		eprintln!("found {} error(s)", error_count);
+		Ok(ExitCode::FAILURE)
+	} else {
+		Ok(ExitCode::SUCCESS)
+	}
+}
+
+fn format(arguments: &Arguments) -> Result<ExitCode, String> {
+	let is_checking = arguments.has("--check");
+	let is_writing = arguments.has("--write");
+	let mut unformatted_count = 0;
+
+	if is_checking && is_writing {
+		return Err("`--check` and `--write` cannot be used together".into());
+	}
+
+	if is_writing && arguments.reads_standard_input() {
+		return Err("the standard input cannot be rewritten".into());
+	}
+
+	for input in arguments.inputs()? {
+		let formatted_source = to_source(&input.contents, &parse_source(&input.contents));
+
+		if is_checking {
+			if formatted_source != input.contents {
+				unformatted_count += 1;
+				println!("{} is not formatted", input.name);
+			}
+		} else if is_writing {
+			if formatted_source != input.contents {
+				std::fs::write(&input.name, &formatted_source)
+					.map_err(|error| format!("cannot write `{}`: {}", input.name, error))?;
+			}
+		} else {
+			std::io::stdout().write_all(&formatted_source).map_err(|error| error.to_string())?;
+		}
+	}
+
+	if unformatted_count > 0 {
+		Ok(ExitCode::FAILURE)
+	} else {
+		Ok(ExitCode::SUCCESS)
+	}
+}
+
+fn convert(arguments: &Arguments) -> Result<ExitCode, String> {
+	let output_format = arguments.value("--to").ok_or("`convert` needs `--to <format>`")?;
+	let output_format = Format::from_name(output_format)?;
+	let input_format = arguments.value("--from").map(Format::from_name).transpose()?;
+
+	let inputs = arguments.inputs()?;
+	let has_headers = inputs.len() > 1;
+
+	for input in &inputs {
+		let input_format = input_format.unwrap_or_else(|| Format::from_extension(&input.name));
+		let (source, nodes) = read_nodes(input, input_format)?;
+		let output = write_nodes(input, &source, &nodes, output_format)?;
+
+		if has_headers {
+			println!("==> {} <==", input.name);
+		}
+
+		std::io::stdout().write_all(&output).map_err(|error| error.to_string())?;
+	}
+
+	Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+	Chearmyp,
+	Json,
+	Yaml,
+	Toml,
+	Xml
+}
+
+impl Format {
+	fn from_name(name: &str) -> Result<Self, String> {
+		match name {
+			"chearmyp" => Ok(Self::Chearmyp),
+			"json" => Ok(Self::Json),
+			"yaml" => Ok(Self::Yaml),
+			"toml" => Ok(Self::Toml),
+			"xml" => Ok(Self::Xml),
+			_ => Err(format!("unknown format `{}`", name))
+		}
+	}
+
+	fn from_extension(file: &str) -> Self {
+		match file.rsplit_once('.').map(|(_, extension)| extension) {
+			Some("json") => Self::Json,
+			Some("yaml" | "yml") => Self::Yaml,
+			Some("toml") => Self::Toml,
+			Some("xml") => Self::Xml,
+			_ => Self::Chearmyp
+		}
+	}
+}
+
+fn read_nodes(input: &Input, format: Format) -> Result<(Vec<u8>, RangeNodeQueue), String> {
+	let describe = |error: &dyn std::fmt::Display| {
+		format!("cannot read `{}`: {}", input.name, error)
+	};
+
+	match format {
+		Format::Chearmyp => Ok((input.contents.clone(), parse_source(&input.contents))),
+		Format::Json => {
+			let value = serde_json::from_slice(&input.contents).map_err(|error| describe(&error))?;
+			from_json(&value).map_err(|error| describe(&error))
+		},
+		Format::Yaml => {
+			let value = serde_yaml::from_slice(&input.contents).map_err(|error| describe(&error))?;
+			let conversion = from_yaml(&value);
+			report_losses(input, &conversion.losses);
+			Ok(conversion.value)
+		},
+		Format::Toml => {
+			let text = std::str::from_utf8(&input.contents).map_err(|error| describe(&error))?;
+			let table = toml::from_str(text).map_err(|error| describe(&error))?;
+			let conversion = from_toml(&table);
+			report_losses(input, &conversion.losses);
+			Ok(conversion.value)
+		},
+		Format::Xml => {
+			let text = std::str::from_utf8(&input.contents).map_err(|error| describe(&error))?;
+			from_xml(text).map_err(|error| describe(&error))
+		}
+	}
+}
+
+fn write_nodes(input: &Input, source: &[u8], nodes: &RangeNodeQueue, format: Format)
+-> Result<Vec<u8>, String> {
+	let mut output = match format {
+		Format::Chearmyp => return Ok(to_source(source, nodes)),
+		Format::Json => {
+			let value = to_json(source, nodes, JsonOptions::default()).map_err(|error| {
+				format!("cannot serialize `{}`: {}", input.name, error)
+			})?;
+			serde_json::to_string_pretty(&value).map_err(|error| error.to_string())?
+		},
+		Format::Yaml => {
+			let conversion = to_yaml(source, nodes);
+			report_losses(input, &conversion.losses);
+			serde_yaml::to_string(&conversion.value).map_err(|error| error.to_string())?
+		},
+		Format::Toml => {
+			let conversion = to_toml(source, nodes);
+			report_losses(input, &conversion.losses);
+			toml::to_string(&conversion.value).map_err(|error| error.to_string())?
+		},
+		Format::Xml => to_xml(source, nodes)
+	};
+
+	if !output.ends_with('\n') {
+		output.push('\n');
+	}
+
+	Ok(output.into_bytes())
+}
+
+fn report_losses(input: &Input, losses: &[chearmyp_parser::Loss]) {
+	for loss in losses {
+		eprintln!("warning: {}: {} at `{}`", input.name, loss.message, loss.path);
+	}
+}
+
+/// Returns the diagnostic prefixed by the name of the input and its line and column.
+fn locate(input: &Input, diagnostic: &Diagnostic) -> String {
+	let (line, column) = line_and_column(&input.contents, diagnostic.span.start);
+	format!(
+		"{}:{}:{}: {}[{}]: {}",
+		input.name,
+		line,
+		column,
+		diagnostic.severity,
+		diagnostic.code,
+		diagnostic.message
+	)
+}
+
+/// Returns the line and column, both counted from 1, of the byte offset.
+fn line_and_column(source: &[u8], offset: usize) -> (usize, usize) {
+	let preceding_bytes = &source[..offset.min(source.len())];
+	let line = preceding_bytes.iter().filter(|&&byte| byte == b'\n').count() + 1;
+	let line_start = preceding_bytes.iter()
+		.rposition(|&byte| byte == b'\n')
+		.map(|index| index + 1)
+		.unwrap_or(0);
+	(line, preceding_bytes.len() - line_start + 1)
+}
+
+#[cfg(test)]
+mod t {
+	use std::process::ExitCode;
+	use super::{Arguments, check, format, line_and_column};
+
+	#[test]
+	fn can_locate_offsets() {
+		let source = b"a\n\tb|\n";
+
+		assert_eq!(line_and_column(&source[..], 0), (1, 1));
+		assert_eq!(line_and_column(&source[..], 3), (2, 2));
+		assert_eq!(line_and_column(&source[..], 99), (3, 1));
+	}
+
+	#[test]
+	fn can_parse_arguments() {
+		let arguments = ["--to", "json", "a.chr", "-", "--check"].map(String::from);
+
+		let parsed = Arguments::new(&arguments, &["--check"], &["--to"]).unwrap();
+
+		assert!(parsed.has("--check"));
+		assert_eq!(parsed.value("--to"), Some("json"));
+		assert_eq!(parsed.files, ["a.chr", "-"]);
+		assert!(parsed.reads_standard_input());
+		assert!(Arguments::new(&arguments, &[], &["--to"]).is_err());
+	}
+
+	#[test]
+	fn can_exit_with_failure_for_errors_and_unformatted_sources() {
+		let directory = std::env::temp_dir().join(format!("chearmyp-{}", std::process::id()));
+		std::fs::create_dir_all(&directory).unwrap();
+		let file = |name: &str, contents: &str| {
+			let path = directory.join(name);
+			std::fs::write(&path, contents).unwrap();
+			path.to_string_lossy().into_owned()
+		};
+		let schema = file("schema.chr", "concept\n\tname: server\n\troot|\n");
+		let valid = file("valid.chr", "server|\n");
+		let invalid = file("invalid.chr", "client|\n");
+		let unformatted = file("unformatted.chr", "server|");
+		let run_check = |file: &str| {
+			let arguments = [String::from("--schema"), schema.clone(), String::from(file)];
+			check(&Arguments::new(&arguments, &[], &["--schema"]).unwrap()).unwrap()
+		};
+		let run_format_check = |file: &str| {
+			let arguments = [String::from("--check"), String::from(file)];
+			format(&Arguments::new(&arguments, &["--check"], &[]).unwrap()).unwrap()
+		};
+
+		assert_eq!(run_check(&valid), ExitCode::SUCCESS);
+		assert_eq!(run_check(&invalid), ExitCode::FAILURE);
+		assert_eq!(run_format_check(&valid), ExitCode::SUCCESS);
+		assert_eq!(run_format_check(&unformatted), ExitCode::FAILURE);
+
+		std::fs::remove_dir_all(&directory).unwrap();
+	}
+}
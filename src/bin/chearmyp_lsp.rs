@@ -0,0 +1,15 @@
+//! Language server for Chearmyp that communicates over the standard input and output.
+
+use std::process::ExitCode;
+use chearmyp_parser::serve;
+
+fn main() -> ExitCode {
+	match serve(std::io::stdin().lock(), std::io::stdout().lock()) {
+		Ok(true) => ExitCode::SUCCESS,
+		Ok(false) => ExitCode::FAILURE,
+		Err(error) => {
+			eprintln!("error: {}", error);
+			ExitCode::from(2)
+		}
+	}
+}
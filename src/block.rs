@@ -0,0 +1,147 @@
+//! Lines of block comments and block othertongues keep the indentation that they have in the
+//! source. The indentation that all lines share is their common indentation. Blank lines, which
+//! only have spaces and tabs, do not affect it since editors usually leave out their indentation.
+//! Spaces and tabs are not converted so a line indented by a tab does not share indentation with a
+//! line indented by spaces.
+
+use crate::native::{Range, String, Vec};
+use crate::tree::resolve;
+
+/// Represents the line ending used when joining lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Newline {
+	#[default]
+	Lf,
+	CrLf
+}
+
+impl Newline {
+	/// Returns the characters of the line ending.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Lf => "\n",
+			Self::CrLf => "\r\n"
+		}
+	}
+}
+
+/// Contains the options for dedenting lines of blocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockOptions {
+	/// Line ending used by [`join_block()`].
+	pub newline: Newline,
+	/// Removes the blank lines before the first and after the last non-blank lines.
+	pub trim_blank_lines: bool
+}
+
+/// Returns the number of bytes of indentation that the non-blank lines share.
+pub fn common_indentation(source: &[u8], lines: &[Range<usize>]) -> usize {
+	let mut common_indentation: Option<&[u8]> = None;
+
+	for line in lines {
+		let text = resolve(source, line);
+		let indentation_length = text.iter()
+			.position(|byte| !matches!(byte, b' ' | b'\t'))
+			.unwrap_or(text.len());
+
+		if indentation_length == text.len() {
+			continue;
+		}
+
+		let indentation = &text[..indentation_length];
+		common_indentation = Some(match common_indentation {
+			Some(common_indentation) => {
+				let shared_length = common_indentation.iter()
+					.zip(indentation)
+					.take_while(|(common_byte, byte)| common_byte == byte)
+					.count();
+				&common_indentation[..shared_length]
+			},
+			None => indentation
+		});
+	}
+
+	common_indentation.map(|indentation| indentation.len()).unwrap_or(0)
+}
+
+/// Returns the boundaries of the lines without their common indentation.
+///
+/// Blank lines lose up to the same number of bytes so their boundaries may become empty.
+pub fn dedent(source: &[u8], lines: &[Range<usize>], options: &BlockOptions) -> Vec<Range<usize>> {
+	let is_blank = |line: &Range<usize>| {
+		resolve(source, line).iter().all(|byte| matches!(byte, b' ' | b'\t'))
+	};
+	let lines = if options.trim_blank_lines {
+		let start = lines.iter().position(|line| !is_blank(line)).unwrap_or(lines.len());
+		let end = lines.iter().rposition(|line| !is_blank(line)).map(|end| end + 1).unwrap_or(start);
+		&lines[start..end]
+	} else {
+		lines
+	};
+	let indentation = common_indentation(source, lines);
+
+	lines.iter()
+		.map(|line| (line.start + indentation).min(line.end)..line.end)
+		.collect()
+}
+
+/// Returns the texts of the lines without their common indentation.
+pub fn dedented_lines<'s>(source: &'s [u8], lines: &[Range<usize>], options: &BlockOptions)
+-> Vec<&'s [u8]> {
+	dedent(source, lines, options).iter().map(|line| resolve(source, line)).collect()
+}
+
+/// Joins the dedented lines with the configured line ending.
+///
+/// Invalid UTF-8 is replaced with the replacement character.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{join_block, BlockOptions};
+/// let source = b"\t\tfn main() {\n\t\t\tstart();\n\t\t}\n\t";
+/// let lines = [0..13, 14..25, 26..29, 30..31];
+/// let options = BlockOptions { trim_blank_lines: true, ..BlockOptions::default() };
+///
+/// assert_eq!(join_block(&source[..], &lines, &options), "fn main() {\n\tstart();\n}");
+/// ```
+pub fn join_block(source: &[u8], lines: &[Range<usize>], options: &BlockOptions) -> String {
+	let mut text = String::new();
+
+	for (index, line) in dedented_lines(source, lines, options).into_iter().enumerate() {
+		if index > 0 {
+			text.push_str(options.newline.as_str());
+		}
+		text.push_str(&String::from_utf8_lossy(line));
+	}
+
+	text
+}
+
+#[cfg(test)]
+mod t {
+	use super::{BlockOptions, Newline, common_indentation, dedent, join_block};
+
+	#[test]
+	fn can_compute_common_indentation() {
+		let source = b"\t  a\t \t b\t\t  \t c";
+
+		assert_eq!(common_indentation(&source[..], &[0..4]), 3);
+		assert_eq!(common_indentation(&source[..], &[0..4, 4..9]), 2);
+		assert_eq!(common_indentation(&source[..], &[0..4, 9..13, 13..16]), 2);
+		assert_eq!(common_indentation(&source[..], &[9..13]), 0);
+	}
+
+	#[test]
+	fn can_dedent_and_join_lines() {
+		let source = b"  \n  a\n    b\n\n";
+		let lines = [0..2, 3..6, 7..12, 13..13];
+		let trimmed = BlockOptions { newline: Newline::CrLf, trim_blank_lines: true };
+
+		assert_eq!(
+			dedent(&source[..], &lines, &BlockOptions::default()),
+			[2..2, 5..6, 9..12, 13..13]
+		);
+		assert_eq!(dedent(&source[..], &lines, &trimmed), [5..6, 9..12]);
+		assert_eq!(join_block(&source[..], &lines, &trimmed), "a\r\n  b");
+	}
+}
@@ -0,0 +1,178 @@
+use crate::native::{Range, Vec, VecDeque};
+use crate::node::Node;
+use crate::tree::{RangeNode, RangeNodeQueue, resolve};
+
+/// Builds nodes together with the source that their boundaries refer to.
+///
+/// It is used when the texts of the nodes do not come from a single source such as when they have
+/// been imported or transformed.
+#[derive(Debug, Default)]
+pub struct TreeBuilder {
+	source: Vec<u8>
+}
+
+impl TreeBuilder {
+	/// Creates a builder with an empty source.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Appends the text to the source and returns its boundary.
+	pub fn push_text(&mut self, text: &[u8]) -> Range<usize> {
+		let start = self.source.len();
+		self.source.extend_from_slice(text);
+		start..self.source.len()
+	}
+
+	/// Creates a complex node.
+	pub fn complex(&mut self, concept: &[u8], attachers: RangeNodeQueue, content: RangeNodeQueue)
+	-> RangeNode {
+		let concept = self.push_text(concept);
+		Node::Complex(concept, attachers, content)
+	}
+
+	/// Creates a simplex node.
+	pub fn simplex(&mut self, concept: &[u8], attachers: RangeNodeQueue) -> RangeNode {
+		let concept = self.push_text(concept);
+		Node::Simplex(concept, attachers)
+	}
+
+	/// Creates an attacher node the same way the parser does.
+	pub fn attacher(&mut self, label: &[u8], content: &[u8]) -> RangeNode {
+		let label = self.push_text(label);
+		let content = self.push_text(content);
+		let mut comments = Vec::new();
+		comments.push(0..0);
+		Node::Attacher(label, content, comments)
+	}
+
+	/// Creates a line comment node.
+	pub fn line_comment(&mut self, line: &[u8]) -> RangeNode {
+		Node::LineComment(self.push_text(line))
+	}
+
+	/// Creates a block comment node.
+	pub fn block_comment<'a, T>(&mut self, lines: T) -> RangeNode
+	where
+		T: IntoIterator<Item = &'a [u8]> {
+		Node::BlockComment(self.push_lines(lines))
+	}
+
+	/// Creates a line othertongue node.
+	pub fn line_othertongue(&mut self, line: &[u8]) -> RangeNode {
+		Node::LineOthertongue(self.push_text(line))
+	}
+
+	/// Creates a block othertongue node.
+	pub fn block_othertongue<'a, T>(&mut self, lines: T) -> RangeNode
+	where
+		T: IntoIterator<Item = &'a [u8]> {
+		Node::BlockOthertongue(self.push_lines(lines))
+	}
+
+	/// Copies the node, including its descendants, whose boundaries refer to another source.
+	pub fn copy(&mut self, source: &[u8], node: &RangeNode) -> RangeNode {
+		match node {
+			Node::Complex(concept, attachers, content) => {
+				let concept = self.push_text(resolve(source, concept));
+				let attachers = self.copy_all(source, attachers);
+				let content = self.copy_all(source, content);
+				Node::Complex(concept, attachers, content)
+			},
+			Node::Simplex(concept, attachers) => {
+				let concept = self.push_text(resolve(source, concept));
+				let attachers = self.copy_all(source, attachers);
+				Node::Simplex(concept, attachers)
+			},
+			Node::Attacher(label, content, _) => {
+				self.attacher(resolve(source, label), resolve(source, content))
+			},
+			Node::LineComment(line) => self.line_comment(resolve(source, line)),
+			Node::BlockComment(lines) => {
+				self.block_comment(lines.iter().map(|line| resolve(source, line)))
+			},
+			Node::LineOthertongue(line) => self.line_othertongue(resolve(source, line)),
+			Node::BlockOthertongue(lines) => {
+				self.block_othertongue(lines.iter().map(|line| resolve(source, line)))
+			}
+		}
+	}
+
+	/// Copies the nodes whose boundaries refer to another source.
+	pub fn copy_all(&mut self, source: &[u8], nodes: &RangeNodeQueue) -> RangeNodeQueue {
+		let mut copied_nodes = VecDeque::with_capacity(nodes.len());
+
+		for node in nodes {
+			copied_nodes.push_back(self.copy(source, node));
+		}
+
+		copied_nodes
+	}
+
+	/// Returns the source that the built nodes refer to.
+	pub fn finish(self) -> Vec<u8> {
+		self.source
+	}
+
+	fn push_lines<'a, T>(&mut self, lines: T) -> Vec<Range<usize>>
+	where
+		T: IntoIterator<Item = &'a [u8]> {
+		lines.into_iter().map(|line| self.push_text(line)).collect()
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::VecDeque;
+	use crate::node::Node;
+	use crate::dump::assert_dump;
+	use super::TreeBuilder;
+
+	#[test]
+	fn can_build_nodes() {
+		let mut builder = TreeBuilder::new();
+
+		let attacher = builder.attacher(b"to", b"everyone");
+		let simplex = builder.simplex(b"world", {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(attacher);
+			attachers
+		});
+		let complex = builder.complex(b"hello", VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(simplex);
+			content
+		});
+		let nodes = {
+			let mut nodes = VecDeque::new();
+			nodes.push_back(complex);
+			nodes
+		};
+		let source = builder.finish();
+
+		assert_dump(&source, &nodes, "
+			(complex \"hello\"
+				(content
+					(simplex \"world\"
+						(attachers (attacher \"to\" \"everyone\")))))
+		");
+	}
+
+	#[test]
+	fn can_copy_nodes() {
+		let source = b"hello\n\tworld|";
+		let mut builder = TreeBuilder::new();
+		builder.push_text(b"preceding text");
+		let nodes = {
+			let mut nodes = VecDeque::new();
+			nodes.push_back(Node::Complex(0..5, VecDeque::new(), VecDeque::new()));
+			nodes.push_back(Node::Simplex(7..12, VecDeque::new()));
+			nodes
+		};
+
+		let copied_nodes = builder.copy_all(&source[..], &nodes);
+		let copied_source = builder.finish();
+
+		assert_dump(&copied_source, &copied_nodes, "(complex \"hello\") (simplex \"world\")");
+	}
+}
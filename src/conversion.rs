@@ -0,0 +1,439 @@
+use crate::native::{format, Range, String, Vec};
+use crate::node::Node;
+use crate::builder::TreeBuilder;
+use crate::tree::{ELEMENT_NAME, RangeNode, RangeNodeQueue, resolve};
+use crate::write::{check_name, check_text};
+
+/// Contains the converted value and what has been lost in the conversion.
+///
+/// Trees are converted to other formats through the following rules:
+///
+/// | Node                                        | Value                                        |
+/// |---------------------------------------------|----------------------------------------------|
+/// | document or complex                         | mapping of its attachers then its content    |
+/// | attacher                                    | boolean, number, or string                   |
+/// | bare simplex                                | null                                         |
+/// | simplex with attachers                      | mapping of its attachers                     |
+/// | bare complex of bare simplexes and `item`s  | sequence of scalars, mappings, or sequences  |
+///
+/// Bare concepts have no attachers and `item`s are complexes named `item`. Contents of attachers
+/// are read as booleans or numbers only if they would be written back the same. So `007` and `1e3`
+/// stay strings.
+///
+/// Values are converted back to trees by reversing the rules. Scalars in sequences become simplexes
+/// while mappings and sequences in sequences become complexes named `item`.
+///
+/// The following are lossy and are reported:
+/// - Comments and othertongues which have no representation in the other formats.
+/// - Entries whose keys have been used by earlier entries in the same mapping. The earlier entries
+///   are kept.
+/// - Multi-line strings whose line endings are replaced by spaces since attachers occupy one line.
+/// - Null elements of sequences, keys that are not scalars, and tags of YAML values.
+/// - Keys, strings, and elements that would be read back differently, such as keys with `:` or
+///   strings ending with `|`. Their entries or elements are dropped.
+/// - Strings which would be read back as booleans or numbers, such as `"80"`.
+/// - Empty sequences which would be read back as empty mappings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversion<T> {
+	/// Value in the output format.
+	pub value: T,
+	/// Parts of the input that could not be represented in the value.
+	pub losses: Vec<Loss>
+}
+
+/// Represents a part of the input that could not be represented in the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loss {
+	/// Keys, separated by slashes, that lead to the lost part.
+	pub path: String,
+	/// Boundary of the lost part if the input is a tree.
+	pub span: Option<Range<usize>>,
+	/// Description of what has been lost.
+	pub message: String
+}
+
+/// Represents the value that is common to the supported formats.
+pub(crate) enum Neutral {
+	Null,
+	Scalar(String),
+	Sequence(Vec<Neutral>),
+	Map(Vec<(String, Neutral)>)
+}
+
+/// Represents the value of a scalar after inferring its type.
+pub(crate) enum Scalar<'a> {
+	Boolean(bool),
+	Integer(i64),
+	Float(f64),
+	Text(&'a str)
+}
+
+impl Neutral {
+	/// Creates a scalar from a string which may span multiple lines.
+	pub(crate) fn from_text(text: String, path: &str, losses: &mut Vec<Loss>) -> Self {
+		let text = if text.contains(&['\n', '\r'][..]) {
+			report(losses, path, None, "line endings of the string have been replaced by spaces");
+			text.lines().collect::<Vec<&str>>().join(" ")
+		} else {
+			text
+		};
+
+		if !matches!(infer_scalar(&text), Scalar::Text(_)) {
+			report(losses, path, None, "string will be read back as a boolean or number");
+		}
+
+		Neutral::Scalar(text)
+	}
+}
+
+/// Returns the type of scalar that the text represents.
+///
+/// Numbers are only inferred if they are written back as the same text. Otherwise, converting the
+/// value back would change the content of the attacher.
+pub(crate) fn infer_scalar(text: &str) -> Scalar {
+	let is_numeric = !text.is_empty() && text.bytes().all(|byte| {
+		byte.is_ascii_digit() || matches!(byte, b'.' | b'e' | b'E' | b'+' | b'-')
+	});
+
+	match text {
+		"true" => Scalar::Boolean(true),
+		"false" => Scalar::Boolean(false),
+		_ if is_numeric => match (text.parse::<i64>(), text.parse::<f64>()) {
+			(Ok(integer), _) if format!("{}", integer) == text => Scalar::Integer(integer),
+			(_, Ok(float)) if float.is_finite() && format!("{}", float) == text => {
+				Scalar::Float(float)
+			},
+			_ => Scalar::Text(text)
+		},
+		_ => Scalar::Text(text)
+	}
+}
+
+/// Returns the mapping that represents the document.
+pub(crate) fn document_to_neutral(source: &[u8], nodes: &RangeNodeQueue, losses: &mut Vec<Loss>)
+-> Neutral {
+	entries_to_neutral(source, "", &[nodes], losses)
+}
+
+/// Returns the nodes that represent the value and their source.
+pub(crate) fn neutral_to_document(value: Neutral, losses: &mut Vec<Loss>)
+-> (Vec<u8>, RangeNodeQueue) {
+	let entries = match value {
+		Neutral::Map(entries) => entries,
+		value => {
+			report(losses, "", None, "the top-level value is not a mapping so it is put in `value`");
+			let mut entries = Vec::with_capacity(1);
+			entries.push(("value".into(), value));
+			entries
+		}
+	};
+
+	let mut builder = TreeBuilder::new();
+	let (mut nodes, content) = entries_to_nodes(&mut builder, "", entries, losses);
+	nodes.extend(content);
+	(builder.finish(), nodes)
+}
+
+/// Returns the path of an entry.
+pub(crate) fn join(path: &str, key: &str) -> String {
+	if path.is_empty() {
+		key.into()
+	} else {
+		format!("{}/{}", path, key)
+	}
+}
+
+pub(crate) fn report(
+	losses: &mut Vec<Loss>,
+	path: &str,
+	span: Option<Range<usize>>,
+	message: &str
+) {
+	losses.push(Loss {
+		path: path.into(),
+		span,
+		message: message.into()
+	});
+}
+
+fn text(source: &[u8], boundary: &Range<usize>) -> String {
+	String::from_utf8_lossy(resolve(source, boundary)).into_owned()
+}
+
+fn entries_to_neutral(
+	source: &[u8],
+	path: &str,
+	groups: &[&RangeNodeQueue],
+	losses: &mut Vec<Loss>
+) -> Neutral {
+	let mut entries: Vec<(String, Neutral)> = Vec::new();
+
+	for node in groups.iter().flat_map(|nodes| nodes.iter()) {
+		let (key, span, value) = match node {
+			Node::Attacher(label, content, _) => {
+				(text(source, label), label.clone(), Neutral::Scalar(text(source, content)))
+			},
+			Node::Complex(concept, _, _) | Node::Simplex(concept, _) => {
+				let key = text(source, concept);
+				let value = concept_to_neutral(source, &join(path, &key), node, losses);
+				(key, concept.clone(), value)
+			},
+			node => {
+				report_dropped_node(losses, path, node);
+				continue;
+			}
+		};
+
+		if entries.iter().any(|(existing_key, _)| *existing_key == key) {
+			let message = format!("entry with a duplicate key `{}` has been dropped", key);
+			report(losses, &join(path, &key), Some(span), &message);
+		} else {
+			entries.push((key, value));
+		}
+	}
+
+	Neutral::Map(entries)
+}
+
+fn concept_to_neutral(source: &[u8], path: &str, node: &RangeNode, losses: &mut Vec<Loss>)
+-> Neutral {
+	match node {
+		Node::Simplex(_, attachers) if attachers.is_empty() => Neutral::Null,
+		Node::Simplex(_, attachers) => entries_to_neutral(source, path, &[attachers], losses),
+		Node::Complex(_, attachers, content) if is_sequence(source, attachers, content) => {
+			let mut elements = Vec::with_capacity(content.len());
+
+			for node in attachers.iter().chain(content.iter()) {
+				match node {
+					Node::Simplex(concept, _) => elements.push(Neutral::Scalar(text(source, concept))),
+					Node::Complex(..) => {
+						let path = join(path, &format!("{}", elements.len()));
+						elements.push(concept_to_neutral(source, &path, node, losses));
+					},
+					node => report_dropped_node(losses, path, node)
+				}
+			}
+
+			Neutral::Sequence(elements)
+		},
+		Node::Complex(_, attachers, content) => {
+			entries_to_neutral(source, path, &[attachers, content], losses)
+		},
+		_ => Neutral::Null
+	}
+}
+
+fn is_sequence(source: &[u8], attachers: &RangeNodeQueue, content: &RangeNodeQueue) -> bool {
+	let has_attachers = attachers.iter().any(|node| matches!(node, Node::Attacher(..)));
+	let mut concepts = content.iter().filter(|node| {
+		matches!(node, Node::Complex(..) | Node::Simplex(..) | Node::Attacher(..))
+	}).peekable();
+
+	!has_attachers && concepts.peek().is_some() && concepts.all(|node| match node {
+		Node::Simplex(_, attachers) => attachers.is_empty(),
+		Node::Complex(concept, _, _) => resolve(source, concept) == ELEMENT_NAME.as_bytes(),
+		_ => false
+	})
+}
+
+fn report_dropped_node(losses: &mut Vec<Loss>, path: &str, node: &RangeNode) {
+	let (span, message) = match node {
+		Node::LineComment(line) => (Some(line.clone()), "comment has been dropped"),
+		Node::BlockComment(lines) => (span_of_lines(lines), "comment has been dropped"),
+		Node::LineOthertongue(line) => (Some(line.clone()), "othertongue has been dropped"),
+		Node::BlockOthertongue(lines) => (span_of_lines(lines), "othertongue has been dropped"),
+		Node::Attacher(label, _, _) => (Some(label.clone()), "attacher has been dropped"),
+		Node::Complex(concept, _, _) | Node::Simplex(concept, _) => {
+			(Some(concept.clone()), "concept has been dropped")
+		}
+	};
+
+	report(losses, path, span, message);
+}
+
+fn span_of_lines(lines: &[Range<usize>]) -> Option<Range<usize>> {
+	match (lines.first(), lines.last()) {
+		(Some(first), Some(last)) => Some(first.start..last.end),
+		_ => None
+	}
+}
+
+fn entries_to_nodes(
+	builder: &mut TreeBuilder,
+	path: &str,
+	entries: Vec<(String, Neutral)>,
+	losses: &mut Vec<Loss>
+) -> (RangeNodeQueue, RangeNodeQueue) {
+	let mut attachers = RangeNodeQueue::new();
+	let mut content = RangeNodeQueue::new();
+
+	for (key, value) in entries {
+		let path = join(path, &key);
+		let key = match check_name(&key) {
+			Ok(key) => key,
+			Err(message) => {
+				report_dropped_text(losses, &path, "entry", message);
+				continue;
+			}
+		};
+
+		match value {
+			Neutral::Null => content.push_back(builder.simplex(key, RangeNodeQueue::new())),
+			Neutral::Scalar(text) => match check_text(&text) {
+				Ok(text) => attachers.push_back(builder.attacher(key, text)),
+				Err(message) => report_dropped_text(losses, &path, "entry", message)
+			},
+			Neutral::Sequence(elements) => {
+				let elements = elements_to_nodes(builder, &path, elements, losses);
+				content.push_back(builder.complex(key, RangeNodeQueue::new(), elements));
+			},
+			Neutral::Map(entries) => {
+				let (attachers_of_entry, content_of_entry) =
+					entries_to_nodes(builder, &path, entries, losses);
+				content.push_back(builder.complex(key, attachers_of_entry, content_of_entry));
+			}
+		}
+	}
+
+	(attachers, content)
+}
+
+fn elements_to_nodes(
+	builder: &mut TreeBuilder,
+	path: &str,
+	elements: Vec<Neutral>,
+	losses: &mut Vec<Loss>
+) -> RangeNodeQueue {
+	let mut nodes = RangeNodeQueue::with_capacity(elements.len());
+	let element_name = ELEMENT_NAME.as_bytes();
+
+	for (index, element) in elements.into_iter().enumerate() {
+		let path = join(path, &format!("{}", index));
+
+		match element {
+			Neutral::Null => report(losses, &path, None, "null element has been dropped"),
+			Neutral::Scalar(text) => match check_name(&text) {
+				Ok(text) => nodes.push_back(builder.simplex(text, RangeNodeQueue::new())),
+				Err(message) => report_dropped_text(losses, &path, "element", message)
+			},
+			Neutral::Sequence(elements) => {
+				let elements = elements_to_nodes(builder, &path, elements, losses);
+				nodes.push_back(builder.complex(element_name, RangeNodeQueue::new(), elements));
+			},
+			Neutral::Map(entries) => {
+				let (attachers, content) = entries_to_nodes(builder, &path, entries, losses);
+				nodes.push_back(builder.complex(element_name, attachers, content));
+			}
+		}
+	}
+
+	if nodes.is_empty() {
+		report(losses, path, None, "empty sequence will be read back as an empty mapping");
+	}
+
+	nodes
+}
+
+fn report_dropped_text(losses: &mut Vec<Loss>, path: &str, kind: &str, message: &str) {
+	let message = format!("{} has been dropped since {}", kind, message);
+	report(losses, path, None, &message);
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use crate::dump::assert_dump;
+	use super::{document_to_neutral, infer_scalar, neutral_to_document, Loss, Neutral, Scalar};
+
+	#[test]
+	fn can_report_dropped_comments_and_duplicates() {
+		let source = b"name: a\n# note\nname: b";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Attacher(0..4, 6..7, Vec::new()));
+		nodes.push_back(Node::LineComment(9..14));
+		nodes.push_back(Node::Attacher(15..19, 21..22, Vec::new()));
+		let mut losses = Vec::new();
+
+		let value = document_to_neutral(&source[..], &nodes, &mut losses);
+
+		assert!(matches!(value, Neutral::Map(entries) if entries.len() == 1));
+		assert_eq!(losses, {
+			let mut losses = Vec::new();
+			losses.push(Loss {
+				path: "".into(),
+				span: Some(9..14),
+				message: "comment has been dropped".into()
+			});
+			losses.push(Loss {
+				path: "name".into(),
+				span: Some(15..19),
+				message: "entry with a duplicate key `name` has been dropped".into()
+			});
+			losses
+		});
+	}
+
+	#[test]
+	fn can_convert_sequences_back_to_nodes() {
+		let mut elements = Vec::new();
+		elements.push(Neutral::Scalar("a".into()));
+		elements.push(Neutral::Null);
+		elements.push(Neutral::Map({
+			let mut entries = Vec::new();
+			entries.push(("b".into(), Neutral::Scalar("c".into())));
+			entries
+		}));
+		let mut entries = Vec::new();
+		entries.push(("list".into(), Neutral::Sequence(elements)));
+		let mut losses = Vec::new();
+
+		let (source, nodes) = neutral_to_document(Neutral::Map(entries), &mut losses);
+
+		assert_dump(&source, &nodes, "
+			(complex \"list\"
+				(content
+					(simplex \"a\")
+					(complex \"item\" (attachers (attacher \"b\" \"c\")))))
+		");
+		assert_eq!(losses.len(), 1);
+		assert_eq!(losses[0].path, "list/1");
+	}
+
+	#[test]
+	fn can_report_values_that_would_be_read_back_differently() {
+		let mut elements = Vec::new();
+		elements.push(Neutral::Scalar("a|".into()));
+		let mut entries = Vec::new();
+		entries.push(("host:port".into(), Neutral::Scalar("a".into())));
+		entries.push(("list".into(), Neutral::Sequence(elements)));
+		entries.push(("empty".into(), Neutral::Sequence(Vec::new())));
+		let mut losses = Vec::new();
+
+		let (source, nodes) = neutral_to_document(Neutral::Map(entries), &mut losses);
+
+		assert_dump(&source, &nodes, "(complex \"list\") (complex \"empty\")");
+		let messages = losses.iter().map(|loss| loss.message.as_str()).collect::<Vec<_>>();
+		assert_eq!(messages, [
+			"entry has been dropped since names cannot contain `:`",
+			"element has been dropped since texts cannot end with `|`",
+			"empty sequence will be read back as an empty mapping",
+			"empty sequence will be read back as an empty mapping"
+		]);
+		assert_eq!(losses[1].path, "list/0");
+		assert_eq!(losses[3].path, "empty");
+
+		Neutral::from_text("80".into(), "port", &mut losses);
+		assert_eq!(losses[4].message, "string will be read back as a boolean or number");
+	}
+
+	#[test]
+	fn can_infer_only_scalars_that_are_written_back_the_same() {
+		assert!(matches!(infer_scalar("true"), Scalar::Boolean(true)));
+		assert!(matches!(infer_scalar("-12"), Scalar::Integer(-12)));
+		assert!(matches!(infer_scalar("1.5"), Scalar::Float(float) if float == 1.5));
+		assert!(matches!(infer_scalar("007"), Scalar::Text("007")));
+		assert!(matches!(infer_scalar("1e3"), Scalar::Text("1e3")));
+		assert!(matches!(infer_scalar("+1"), Scalar::Text("+1")));
+	}
+}
@@ -0,0 +1,751 @@
+//! Nodes are mapped to the data model of serde as follows:
+//!
+//! - The document and complexes are maps or structs. Their entries are their attachers followed by
+//!   the concepts in their content. Complexes may also be sequences of the concepts in their
+//!   content, in which case the names of the concepts are ignored.
+//! - Attachers are scalars. Their contents are parsed on demand to booleans, numbers, or strings.
+//! - Simplexes are units if they are entries. They are also `true` if a boolean was expected which
+//!   allows simplexes to be used as flags. If they are elements of a sequence, their names are
+//!   scalars.
+//! - Unit variants of enumerations are scalars. Other variants are complexes whose only concept in
+//!   their content is named after the variant.
+//!
+//! Comments and othertongues are skipped.
+
+use core::str::from_utf8;
+use serde::de::{
+	Deserialize,
+	DeserializeSeed,
+	Deserializer as SerdeDeserializer,
+	EnumAccess,
+	IntoDeserializer,
+	MapAccess,
+	SeqAccess,
+	VariantAccess,
+	Visitor
+};
+use serde::de::value::BorrowedStrDeserializer;
+use crate::native::{format, Range, String, VecDeque};
+use crate::node::Node;
+use crate::parse::parse_source;
+use crate::serde_error::SerdeError;
+use crate::tree::{RangeNode, RangeNodeQueue, resolve};
+
+/// Returns a Rust value from the source after parsing it.
+///
+/// ## Examples
+/// ```
+/// use serde::Deserialize;
+/// use chearmyp_parser::from_slice;
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Server {
+/// 	host: String,
+/// 	port: u16,
+/// 	debug: bool
+/// }
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Config {
+/// 	server: Server
+/// }
+///
+/// let source = b"server\n\thost: example.com\n\tport: 8080\n\tdebug: false\n";
+///
+/// let config: Config = from_slice(&source[..]).unwrap();
+///
+/// assert_eq!(config, Config {
+/// 	server: Server {
+/// 		host: "example.com".to_string(),
+/// 		port: 8080,
+/// 		debug: false
+/// 	}
+/// });
+/// ```
+pub fn from_slice<'de, T>(source: &'de [u8]) -> Result<T, SerdeError>
+where
+	T: Deserialize<'de> {
+	let nodes = parse_source(source);
+	from_nodes(source, &nodes)
+}
+
+/// Returns a Rust value from nodes that have been parsed already.
+pub fn from_nodes<'de, T>(source: &'de [u8], nodes: &RangeNodeQueue) -> Result<T, SerdeError>
+where
+	T: Deserialize<'de> {
+	T::deserialize(Deserializer::from_nodes(source, nodes))
+}
+
+/// Represents the deserializer of nodes.
+pub struct Deserializer<'de, 'n> {
+	source: &'de [u8],
+	value: Value<'n>,
+	path: String
+}
+
+#[derive(Clone)]
+enum Value<'n> {
+	Document(&'n RangeNodeQueue),
+	Scalar(Range<usize>),
+	Concept(&'n RangeNode, Position)
+}
+
+/// Indicates where a concept was found in its parent.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Position {
+	Entry,
+	Element
+}
+
+type Entries<'de, 'n> = VecDeque<(&'de str, Value<'n>)>;
+
+impl<'de, 'n> Deserializer<'de, 'n> {
+	/// Creates a deserializer that treats the nodes as the entries of a document.
+	pub fn from_nodes(source: &'de [u8], nodes: &'n RangeNodeQueue) -> Self {
+		Self {
+			source,
+			value: Value::Document(nodes),
+			path: String::new()
+		}
+	}
+
+	fn child(&self, key: &str, value: Value<'n>) -> Self {
+		let path = if self.path.is_empty() {
+			key.into()
+		} else {
+			format!("{}/{}", self.path, key)
+		};
+
+		Self {
+			source: self.source,
+			value,
+			path
+		}
+	}
+
+	fn deserialize_seed<T>(self, seed: T) -> Result<T::Value, SerdeError>
+	where
+		T: DeserializeSeed<'de> {
+		let path = self.path.clone();
+		let span = self.span();
+		seed.deserialize(self).map_err(|error| error.locate(&path, span))
+	}
+
+	fn concept(&self) -> Option<(&'n RangeNode, Position)> {
+		match &self.value {
+			Value::Concept(node, position) => Some((*node, *position)),
+			_ => None
+		}
+	}
+
+	fn span(&self) -> Option<Range<usize>> {
+		match &self.value {
+			Value::Document(_) => None,
+			Value::Scalar(content) => Some(content.clone()),
+			Value::Concept(node, _) => match node {
+				Node::Complex(concept, _, _) | Node::Simplex(concept, _) => Some(concept.clone()),
+				_ => None
+			}
+		}
+	}
+
+	fn error(&self, span: Option<Range<usize>>, message: String) -> SerdeError {
+		SerdeError {
+			path: self.path.clone(),
+			span,
+			message
+		}
+	}
+
+	fn unexpected(&self, expected: &str) -> SerdeError {
+		let found = match self.concept() {
+			None => match self.value {
+				Value::Scalar(_) => "the content of an attacher",
+				_ => "the document"
+			},
+			Some((Node::Complex(..), _)) => "a complex",
+			Some(_) => "a simplex"
+		};
+
+		self.error(self.span(), format!("expected {} but found {}", expected, found))
+	}
+
+	fn text(&self, boundary: &Range<usize>) -> Result<&'de str, SerdeError> {
+		from_utf8(resolve(self.source, boundary)).map_err(|_| {
+			self.error(Some(boundary.clone()), "expected a text in UTF-8".into())
+		})
+	}
+
+	fn scalar(&self) -> Result<(&'de str, Range<usize>), SerdeError> {
+		match (&self.value, self.concept()) {
+			(Value::Scalar(content), _) => Ok((self.text(content)?, content.clone())),
+			(_, Some((Node::Simplex(concept, _), Position::Element))) => {
+				Ok((self.text(concept)?, concept.clone()))
+			},
+			_ => Err(self.unexpected("a scalar"))
+		}
+	}
+
+	fn is_flag(&self) -> bool {
+		match self.concept() {
+			Some((Node::Simplex(_, attachers), Position::Entry)) => !has_entries(attachers),
+			_ => false
+		}
+	}
+
+	fn entries(&self) -> Result<Entries<'de, 'n>, SerdeError> {
+		let mut entries = VecDeque::new();
+
+		match (&self.value, self.concept()) {
+			(Value::Document(nodes), _) => self.collect_entries(nodes, &mut entries)?,
+			(_, Some((Node::Complex(_, attachers, content), _))) => {
+				self.collect_entries(attachers, &mut entries)?;
+				self.collect_entries(content, &mut entries)?;
+			},
+			(_, Some((Node::Simplex(_, attachers), _))) => {
+				self.collect_entries(attachers, &mut entries)?;
+			},
+			_ => return Err(self.unexpected("a concept"))
+		}
+
+		Ok(entries)
+	}
+
+	fn collect_entries(&self, nodes: &'n RangeNodeQueue, entries: &mut Entries<'de, 'n>)
+	-> Result<(), SerdeError> {
+		for node in nodes {
+			match node {
+				Node::Attacher(label, content, _) => {
+					entries.push_back((self.text(label)?, Value::Scalar(content.clone())));
+				},
+				Node::Complex(concept, _, _) | Node::Simplex(concept, _) => {
+					entries.push_back((self.text(concept)?, Value::Concept(node, Position::Entry)));
+				},
+				_ => {}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn elements(&self) -> Result<&'n RangeNodeQueue, SerdeError> {
+		match (&self.value, self.concept()) {
+			(Value::Document(nodes), _) => Ok(*nodes),
+			(_, Some((Node::Complex(_, _, content), _))) => Ok(content),
+			// Attachers of simplexes never contain concepts so they are treated as empty sequences.
+			(_, Some((Node::Simplex(_, attachers), _))) => Ok(attachers),
+			_ => Err(self.unexpected("a complex"))
+		}
+	}
+
+	fn variant(&self) -> Result<(&'de str, Value<'n>), SerdeError> {
+		if let Some((Node::Complex(_, attachers, content), _)) = self.concept() {
+			let mut concepts = content.iter().filter(|node| is_concept(node));
+
+			if let (false, Some(node), None) = (has_entries(attachers), concepts.next(), concepts.next()) {
+				if let Node::Complex(concept, _, _) | Node::Simplex(concept, _) = node {
+					return Ok((self.text(concept)?, Value::Concept(node, Position::Entry)));
+				}
+			}
+		}
+
+		Err(self.unexpected("a complex with a single concept"))
+	}
+}
+
+macro_rules! deserialize_parsed {
+	($($method:ident => $visit:ident as $expected:literal),*) => {
+		$(
+			fn $method<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+			where
+				V: Visitor<'de> {
+				let (text, span) = self.scalar()?;
+
+				match text.trim().parse() {
+					Ok(value) => visitor.$visit(value),
+					Err(_) => Err(self.error(
+						Some(span),
+						format!("expected {} but found `{}`", $expected, text)
+					))
+				}
+			}
+		)*
+	};
+}
+
+impl<'de, 'n> SerdeDeserializer<'de> for Deserializer<'de, 'n> {
+	type Error = SerdeError;
+
+	fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		match (&self.value, self.concept()) {
+			(Value::Scalar(_), _) => self.deserialize_str(visitor),
+			(_, Some((Node::Simplex(_, attachers), position))) if !has_entries(attachers) => {
+				match position {
+					Position::Entry => visitor.visit_unit(),
+					Position::Element => self.deserialize_str(visitor)
+				}
+			},
+			_ => self.deserialize_map(visitor)
+		}
+	}
+
+	fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		if self.is_flag() {
+			return visitor.visit_bool(true);
+		}
+
+		let (text, span) = self.scalar()?;
+
+		match text.trim() {
+			"true" => visitor.visit_bool(true),
+			"false" => visitor.visit_bool(false),
+			_ => Err(self.error(Some(span), format!("expected a boolean but found `{}`", text)))
+		}
+	}
+
+	deserialize_parsed! {
+		deserialize_i8 => visit_i8 as "an integer",
+		deserialize_i16 => visit_i16 as "an integer",
+		deserialize_i32 => visit_i32 as "an integer",
+		deserialize_i64 => visit_i64 as "an integer",
+		deserialize_u8 => visit_u8 as "an unsigned integer",
+		deserialize_u16 => visit_u16 as "an unsigned integer",
+		deserialize_u32 => visit_u32 as "an unsigned integer",
+		deserialize_u64 => visit_u64 as "an unsigned integer",
+		deserialize_f32 => visit_f32 as "a number",
+		deserialize_f64 => visit_f64 as "a number"
+	}
+
+	fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		let (text, span) = self.scalar()?;
+		let mut characters = text.chars();
+
+		match (characters.next(), characters.next()) {
+			(Some(character), None) => visitor.visit_char(character),
+			_ => Err(self.error(Some(span), format!("expected a character but found `{}`", text)))
+		}
+	}
+
+	fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		let (text, _) = self.scalar()?;
+		visitor.visit_borrowed_str(text)
+	}
+
+	fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		self.deserialize_str(visitor)
+	}
+
+	fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		let (text, _) = self.scalar()?;
+		visitor.visit_borrowed_bytes(text.as_bytes())
+	}
+
+	fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		self.deserialize_bytes(visitor)
+	}
+
+	fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		if self.is_flag() {
+			visitor.visit_unit()
+		} else {
+			Err(self.unexpected("a simplex"))
+		}
+	}
+
+	fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V)
+	-> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		self.deserialize_unit(visitor)
+	}
+
+	fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V)
+	-> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		let nodes = self.elements()?;
+		visitor.visit_seq(ElementAccess {
+			deserializer: &self,
+			nodes,
+			position: 0,
+			index: 0
+		})
+	}
+
+	fn deserialize_tuple<V>(self, _length: usize, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_tuple_struct<V>(self, _name: &'static str, _length: usize, visitor: V)
+	-> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		let entries = self.entries()?;
+		visitor.visit_map(EntryAccess {
+			deserializer: &self,
+			entries,
+			value: None
+		})
+	}
+
+	fn deserialize_struct<V>(
+		self,
+		_name: &'static str,
+		_fields: &'static [&'static str],
+		visitor: V
+	) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		self.deserialize_map(visitor)
+	}
+
+	fn deserialize_enum<V>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V
+	) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		if let Ok((text, _)) = self.scalar() {
+			return visitor.visit_enum(text.into_deserializer());
+		}
+
+		let (name, value) = self.variant()?;
+		let value = self.child(name, value);
+		visitor.visit_enum(VariantDeserializer { name, value })
+	}
+
+	fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		self.deserialize_str(visitor)
+	}
+
+	fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		visitor.visit_unit()
+	}
+}
+
+struct EntryAccess<'a, 'de, 'n> {
+	deserializer: &'a Deserializer<'de, 'n>,
+	entries: Entries<'de, 'n>,
+	value: Option<(&'de str, Value<'n>)>
+}
+
+impl<'a, 'de, 'n> MapAccess<'de> for EntryAccess<'a, 'de, 'n> {
+	type Error = SerdeError;
+
+	fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, SerdeError>
+	where
+		K: DeserializeSeed<'de> {
+		match self.entries.pop_front() {
+			Some((key, value)) => {
+				self.value = Some((key, value));
+				seed.deserialize(BorrowedStrDeserializer::<SerdeError>::new(key)).map(Some)
+			},
+			None => Ok(None)
+		}
+	}
+
+	fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, SerdeError>
+	where
+		V: DeserializeSeed<'de> {
+		let (key, value) = self.value.take().expect("a key should be deserialized first");
+		self.deserializer.child(key, value).deserialize_seed(seed)
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.entries.len())
+	}
+}
+
+struct ElementAccess<'a, 'de, 'n> {
+	deserializer: &'a Deserializer<'de, 'n>,
+	nodes: &'n RangeNodeQueue,
+	position: usize,
+	index: usize
+}
+
+impl<'a, 'de, 'n> SeqAccess<'de> for ElementAccess<'a, 'de, 'n> {
+	type Error = SerdeError;
+
+	fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, SerdeError>
+	where
+		T: DeserializeSeed<'de> {
+		while let Some(node) = self.nodes.get(self.position) {
+			self.position += 1;
+
+			if is_concept(node) {
+				let key = format!("{}", self.index);
+				let element = self.deserializer.child(&key, Value::Concept(node, Position::Element));
+				self.index += 1;
+				return element.deserialize_seed(seed).map(Some);
+			}
+		}
+
+		Ok(None)
+	}
+}
+
+struct VariantDeserializer<'de, 'n> {
+	name: &'de str,
+	value: Deserializer<'de, 'n>
+}
+
+impl<'de, 'n> EnumAccess<'de> for VariantDeserializer<'de, 'n> {
+	type Error = SerdeError;
+	type Variant = Deserializer<'de, 'n>;
+
+	fn variant_seed<T>(self, seed: T) -> Result<(T::Value, Self::Variant), SerdeError>
+	where
+		T: DeserializeSeed<'de> {
+		let variant = seed.deserialize(BorrowedStrDeserializer::<SerdeError>::new(self.name))?;
+		Ok((variant, self.value))
+	}
+}
+
+impl<'de, 'n> VariantAccess<'de> for Deserializer<'de, 'n> {
+	type Error = SerdeError;
+
+	fn unit_variant(self) -> Result<(), SerdeError> {
+		Ok(())
+	}
+
+	fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, SerdeError>
+	where
+		T: DeserializeSeed<'de> {
+		self.deserialize_seed(seed)
+	}
+
+	fn tuple_variant<V>(self, _length: usize, visitor: V) -> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		SerdeDeserializer::deserialize_seq(self, visitor)
+	}
+
+	fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V)
+	-> Result<V::Value, SerdeError>
+	where
+		V: Visitor<'de> {
+		SerdeDeserializer::deserialize_map(self, visitor)
+	}
+}
+
+fn is_concept(node: &RangeNode) -> bool {
+	matches!(node, Node::Complex(..) | Node::Simplex(..))
+}
+
+fn has_entries(nodes: &RangeNodeQueue) -> bool {
+	nodes.iter().any(|node| is_concept(node) || matches!(node, Node::Attacher(..)))
+}
+
+#[cfg(test)]
+mod t {
+	use serde::Deserialize;
+	use crate::native::{String, Vec, VecDeque};
+	use crate::node::Node;
+	use super::{from_nodes, from_slice};
+
+	#[derive(Deserialize, Debug, PartialEq)]
+	struct Server {
+		host: String,
+		port: u16,
+		#[serde(default)]
+		debug: bool
+	}
+
+	#[test]
+	fn can_deserialize_struct_from_attachers() {
+		let source = b"server\n\thost: localhost\n\tport: 80\n\tdebug|";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..6, {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(Node::Attacher(8..12, 14..23, Vec::new()));
+			attachers.push_back(Node::Attacher(25..29, 31..33, Vec::new()));
+			attachers
+		}, {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(35..40, VecDeque::new()));
+			content
+		}));
+
+		#[derive(Deserialize, Debug, PartialEq)]
+		struct Config {
+			server: Server
+		}
+
+		let config: Config = from_nodes(&source[..], &nodes).unwrap();
+
+		assert_eq!(config, Config {
+			server: Server {
+				host: "localhost".into(),
+				port: 80,
+				debug: true
+			}
+		});
+	}
+
+	#[test]
+	fn can_deserialize_sequence_from_content() {
+		let source = b"names\n\talpha|\n\t# skipped\n\tbeta|";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..5, VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(7..12, VecDeque::new()));
+			content.push_back(Node::LineComment(16..24));
+			content.push_back(Node::Simplex(26..30, VecDeque::new()));
+			content
+		}));
+
+		#[derive(Deserialize, Debug, PartialEq)]
+		struct Names {
+			names: Vec<String>
+		}
+
+		let names: Names = from_nodes(&source[..], &nodes).unwrap();
+
+		assert_eq!(names.names, ["alpha", "beta"]);
+	}
+
+	#[test]
+	fn can_deserialize_enumerations() {
+		let source = b"mode: fast\nshape\n\tcircle\n\t\tradius: 2";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Attacher(0..4, 6..10, Vec::new()));
+		nodes.push_back(Node::Complex(11..16, VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Complex(18..24, {
+				let mut attachers = VecDeque::new();
+				attachers.push_back(Node::Attacher(27..33, 35..36, Vec::new()));
+				attachers
+			}, VecDeque::new()));
+			content
+		}));
+
+		#[derive(Deserialize, Debug, PartialEq)]
+		#[serde(rename_all = "lowercase")]
+		enum Mode {
+			Fast
+		}
+
+		#[derive(Deserialize, Debug, PartialEq)]
+		#[serde(rename_all = "lowercase")]
+		enum Shape {
+			Circle { radius: u8 }
+		}
+
+		#[derive(Deserialize, Debug, PartialEq)]
+		struct Drawing {
+			mode: Mode,
+			shape: Shape
+		}
+
+		let drawing: Drawing = from_nodes(&source[..], &nodes).unwrap();
+
+		assert_eq!(drawing, Drawing {
+			mode: Mode::Fast,
+			shape: Shape::Circle { radius: 2 }
+		});
+	}
+
+	#[test]
+	fn cannot_deserialize_invalid_number() {
+		let source = b"server\n\thost: localhost\n\tport: eighty";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..6, {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(Node::Attacher(8..12, 14..23, Vec::new()));
+			attachers.push_back(Node::Attacher(25..29, 31..37, Vec::new()));
+			attachers
+		}, VecDeque::new()));
+
+		#[derive(Deserialize, Debug)]
+		struct Config {
+			#[allow(dead_code)]
+			server: Server
+		}
+
+		let error = from_nodes::<Config>(&source[..], &nodes).unwrap_err();
+
+		assert_eq!(error.path, "server/port");
+		assert_eq!(error.span, Some(31..37));
+		assert_eq!(error.message, "expected an unsigned integer but found `eighty`");
+	}
+
+	#[test]
+	fn cannot_deserialize_missing_field() {
+		let source = b"server\n\thost: localhost";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..6, {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(Node::Attacher(8..12, 14..23, Vec::new()));
+			attachers
+		}, VecDeque::new()));
+
+		#[derive(Deserialize, Debug)]
+		struct Config {
+			#[allow(dead_code)]
+			server: Server
+		}
+
+		let error = from_nodes::<Config>(&source[..], &nodes).unwrap_err();
+
+		assert_eq!(error.path, "server");
+		assert_eq!(error.span, Some(0..6));
+		assert_eq!(error.message, "missing field `port`");
+	}
+
+	#[test]
+	fn can_deserialize_from_slice() {
+		#[derive(Deserialize, Debug, PartialEq)]
+		struct Config {
+			server: Server
+		}
+
+		let source = b"server\n\thost: example.com\n\tport: 8080\n";
+
+		let config: Config = from_slice(&source[..]).unwrap();
+
+		assert_eq!(config.server.host, "example.com");
+		assert_eq!(config.server.port, 8080);
+	}
+}
@@ -0,0 +1,79 @@
+use core::fmt::{Display, Formatter, Result as FormatResult};
+use crate::native::{Range, String};
+
+/// Represents how serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+	Error,
+	Warning,
+	Information,
+	Hint
+}
+
+impl Display for Severity {
+	fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+		let name = match self {
+			Self::Error => "error",
+			Self::Warning => "warning",
+			Self::Information => "information",
+			Self::Hint => "hint"
+		};
+		formatter.write_str(name)
+	}
+}
+
+/// Represents a problem found in a source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+	/// How serious the problem is.
+	pub severity: Severity,
+	/// Boundary in the source where the problem has been found.
+	pub span: Range<usize>,
+	/// Stable identifier of the kind of problem.
+	pub code: &'static str,
+	/// Description of the problem meant for people.
+	pub message: String
+}
+
+impl Diagnostic {
+	/// Creates a diagnostic with error severity.
+	pub fn error(span: Range<usize>, code: &'static str, message: String) -> Self {
+		Self { severity: Severity::Error, span, code, message }
+	}
+
+	/// Creates a diagnostic with warning severity.
+	pub fn warning(span: Range<usize>, code: &'static str, message: String) -> Self {
+		Self { severity: Severity::Warning, span, code, message }
+	}
+}
+
+impl Display for Diagnostic {
+	fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+		write!(
+			formatter,
+			"{}[{}]: {} at bytes {}..{}",
+			self.severity,
+			self.code,
+			self.message,
+			self.span.start,
+			self.span.end
+		)
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::format;
+	use super::Diagnostic;
+
+	#[test]
+	fn can_display_diagnostic() {
+		let message = "unknown concept `hello`".into();
+		let diagnostic = Diagnostic::warning(3..8, "unknown_concept", message);
+
+		assert_eq!(
+			format!("{}", diagnostic),
+			"warning[unknown_concept]: unknown concept `hello` at bytes 3..8"
+		);
+	}
+}
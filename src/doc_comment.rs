@@ -0,0 +1,233 @@
+//! Documentation comments are the run of line and block comments that directly precede a complex
+//! or simplex in the same list. A blank line between two comments, or between the last comment and
+//! the concept, ends the run so only the comments after the blank line document the concept.
+//! Suppression comments of the linter are never documentation.
+
+use crate::native::{Range, Vec, VecDeque};
+use crate::node::Node;
+use crate::lint::parse_directive;
+use crate::tree::{RangeNode, RangeNodeQueue, resolve, span_of};
+
+/// Contains the documentation comments of concepts keyed by their paths.
+///
+/// A path has the indices that lead to the concept in the nodes returned by
+/// [`attach_doc_comments()`]. Each index counts the attachers of the parent before its content.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocComments {
+	docs: Vec<(Vec<usize>, Vec<Range<usize>>)>
+}
+
+impl DocComments {
+	/// Returns the lines of the documentation comments of the concept at the path in source order.
+	pub fn docs(&self, path: &[usize]) -> &[Range<usize>] {
+		self.docs.iter()
+			.find(|(concept_path, _)| concept_path == path)
+			.map(|(_, lines)| lines.as_slice())
+			.unwrap_or(&[])
+	}
+
+	/// Returns the paths of the documented concepts and their lines.
+	pub fn iter(&self) -> impl Iterator<Item = (&[usize], &[Range<usize>])> {
+		self.docs.iter().map(|(path, lines)| (path.as_slice(), lines.as_slice()))
+	}
+
+	pub fn len(&self) -> usize {
+		self.docs.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.docs.is_empty()
+	}
+
+	fn add(&mut self, path: &[usize], comments: &[&RangeNode]) {
+		if comments.is_empty() {
+			return;
+		}
+
+		let lines = comments.iter()
+			.flat_map(|comment| match comment {
+				Node::LineComment(line) => core::slice::from_ref(line),
+				Node::BlockComment(lines) => lines.as_slice(),
+				_ => &[]
+			})
+			.cloned()
+			.collect();
+		self.docs.push((path.into(), lines));
+	}
+}
+
+/// Moves the documentation comments out of the nodes and returns them separately.
+///
+/// The boundaries of the returned nodes still refer to the same source.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, attach_doc_comments};
+/// let source = b"# Not documentation\n\n# Serves files.\nserver|\n";
+/// let nodes = parse_source(&source[..]);
+///
+/// let (nodes, doc_comments) = attach_doc_comments(&source[..], &nodes);
+///
+/// assert_eq!(nodes.len(), 2);
+/// let lines = doc_comments.docs(&[1]);
+/// assert_eq!(&source[lines[0].clone()], b" Serves files.");
+/// ```
+pub fn attach_doc_comments(source: &[u8], nodes: &RangeNodeQueue) -> (RangeNodeQueue, DocComments) {
+	let mut doc_comments = DocComments::default();
+	let nodes = attach_in_list(source, nodes, &mut doc_comments, &mut Vec::new(), 0);
+	(nodes, doc_comments)
+}
+
+/// Attaches the comments in the list whose remaining nodes are at indices from the given index.
+fn attach_in_list(
+	source: &[u8],
+	nodes: &RangeNodeQueue,
+	doc_comments: &mut DocComments,
+	path: &mut Vec<usize>,
+	start: usize
+) -> RangeNodeQueue {
+	let mut remaining_nodes = VecDeque::with_capacity(nodes.len());
+	let mut pending_comments: Vec<&RangeNode> = Vec::new();
+
+	for node in nodes {
+		if is_candidate(source, node) {
+			pending_comments.push(node);
+			continue;
+		}
+
+		match node {
+			Node::Complex(concept, attachers, content) => {
+				let attached_comments = take_attached(source, &mut pending_comments, concept.start);
+				remaining_nodes.extend(pending_comments.drain(..).cloned());
+				path.push(start + remaining_nodes.len());
+				doc_comments.add(path, &attached_comments);
+
+				let (attachers, content) =
+					attach_in_complex(source, attachers, content, doc_comments, path);
+				path.pop();
+				remaining_nodes.push_back(Node::Complex(concept.clone(), attachers, content));
+			},
+			Node::Simplex(concept, _) => {
+				let attached_comments = take_attached(source, &mut pending_comments, concept.start);
+				remaining_nodes.extend(pending_comments.drain(..).cloned());
+				path.push(start + remaining_nodes.len());
+				doc_comments.add(path, &attached_comments);
+				path.pop();
+				remaining_nodes.push_back(node.clone());
+			},
+			_ => {
+				remaining_nodes.extend(pending_comments.drain(..).cloned());
+				remaining_nodes.push_back(node.clone());
+			}
+		}
+	}
+
+	remaining_nodes.extend(pending_comments.drain(..).cloned());
+	remaining_nodes
+}
+
+/// Comments at the end of the attachers of a complex precede its first content so they may
+/// document it.
+fn attach_in_complex(
+	source: &[u8],
+	attachers: &RangeNodeQueue,
+	content: &RangeNodeQueue,
+	doc_comments: &mut DocComments,
+	path: &mut Vec<usize>
+) -> (RangeNodeQueue, RangeNodeQueue) {
+	let mut attachers = attachers.clone();
+	let first_concept = match content.front() {
+		Some(Node::Complex(concept, _, _) | Node::Simplex(concept, _)) => Some(concept),
+		_ => None
+	};
+
+	if let Some(first_concept) = first_concept {
+		let candidate_count = attachers.iter()
+			.rev()
+			.take_while(|attacher| is_candidate(source, attacher))
+			.count();
+		let mut trailing_comments = attachers.iter()
+			.skip(attachers.len() - candidate_count)
+			.collect::<Vec<_>>();
+		let attached_comments = take_attached(source, &mut trailing_comments, first_concept.start);
+		let attached_count = attached_comments.len();
+		path.push(attachers.len() - attached_count);
+		doc_comments.add(path, &attached_comments);
+		path.pop();
+		attachers.truncate(attachers.len() - attached_count);
+	}
+
+	let content = attach_in_list(source, content, doc_comments, path, attachers.len());
+	(attachers, content)
+}
+
+fn is_candidate(source: &[u8], node: &RangeNode) -> bool {
+	matches!(node, Node::LineComment(_) | Node::BlockComment(_))
+		&& parse_directive(source, node).is_none()
+}
+
+/// Removes the comments that are not separated from the next comments and the concept by blank
+/// lines and returns them in source order.
+fn take_attached<'n>(source: &[u8], comments: &mut Vec<&'n RangeNode>, concept_start: usize)
+-> Vec<&'n RangeNode> {
+	let mut next_start = concept_start;
+	let mut attached_start = comments.len();
+
+	while attached_start > 0
+	&& !is_separated(source, span_of(comments[attached_start - 1]).end, next_start) {
+		attached_start -= 1;
+		next_start = span_of(comments[attached_start]).start;
+	}
+
+	comments.split_off(attached_start)
+}
+
+/// Checks if there is a blank line between the boundaries.
+fn is_separated(source: &[u8], end: usize, start: usize) -> bool {
+	let gap = resolve(source, &(end..start.max(end)));
+	let mut segments = gap.split(|&byte| byte == b'\n');
+	segments.next();
+	segments.next_back();
+	segments.any(|segment| segment.iter().all(|byte| byte.is_ascii_whitespace()))
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::Vec;
+	use crate::dump::assert_dump;
+	use crate::parse::parse_source;
+	use crate::tree::resolve;
+	use super::attach_doc_comments;
+
+	#[test]
+	fn can_attach_doc_comments() {
+		let source = b"a\n\tk: v\n\t# first\n\t# second\n\tb|\n\t# lint: allow(x)\n\tc|\n";
+		let nodes = parse_source(&source[..]);
+
+		let (nodes, doc_comments) = attach_doc_comments(&source[..], &nodes);
+
+		assert_dump(&source[..], &nodes, "
+			(complex \"a\" (attachers (attacher \"k\" \"v\")) (content
+				(simplex \"b\")
+				(line_comment \" lint: allow(x)\")
+				(simplex \"c\")))
+		");
+		assert_eq!(doc_comments.len(), 1);
+		let lines = doc_comments.docs(&[0, 1]);
+		assert_eq!(
+			lines.iter().map(|line| resolve(&source[..], line)).collect::<Vec<_>>(),
+			[&b" first"[..], &b" second"[..]]
+		);
+	}
+
+	#[test]
+	fn cannot_attach_comments_after_blank_line() {
+		let source = b"# kept\n\n# also kept\n\nx|\n# trailing\n";
+		let nodes = parse_source(&source[..]);
+
+		let (nodes, doc_comments) = attach_doc_comments(&source[..], &nodes);
+
+		assert!(doc_comments.is_empty());
+		assert_eq!(nodes.len(), 4);
+	}
+}
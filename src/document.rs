@@ -0,0 +1,257 @@
+use crate::native::{format, Range};
+use crate::node::Node;
+use crate::diagnostic::Diagnostic;
+use crate::parse::parse_source;
+use crate::tree::{RangeNode, RangeNodeQueue, span_of};
+
+/// Pairs parsed nodes with their source so that their boundaries resolve to strings.
+///
+/// The source is validated as UTF-8 once when the document is created.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::Document;
+/// let document = Document::parse(b"server\n\tport: 80\n\tdebug|\n").unwrap();
+///
+/// let server = document.roots().next().unwrap();
+///
+/// assert_eq!(server.name(), "server");
+/// assert_eq!(server.attachers().next().unwrap().content(), "80");
+/// assert_eq!(server.children().next().unwrap().name(), "debug");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Document<'src> {
+	source: &'src str,
+	nodes: RangeNodeQueue
+}
+
+/// Refers to a node of a document.
+#[derive(Debug, Clone, Copy)]
+pub struct DocumentNode<'d, 'src: 'd> {
+	source: &'src str,
+	node: &'d RangeNode
+}
+
+impl<'src> Document<'src> {
+	/// Pairs the nodes with their source.
+	///
+	/// Returns an error that spans the first invalid bytes if the source is not valid UTF-8. Returns
+	/// an error that spans the first boundary that is outside the source or splits a character.
+	pub fn new(source: &'src [u8], nodes: RangeNodeQueue) -> Result<Self, Diagnostic> {
+		let source = validate(source)?;
+		validate_nodes(source, &nodes)?;
+		Ok(Self { source, nodes })
+	}
+
+	/// Validates the source and then parses it.
+	pub fn parse(source: &'src [u8]) -> Result<Self, Diagnostic> {
+		let source = validate(source)?;
+		let nodes = parse_source(source.as_bytes());
+		Ok(Self { source, nodes })
+	}
+
+	/// Returns the validated source.
+	pub fn source(&self) -> &'src str {
+		self.source
+	}
+
+	/// Returns all parsed nodes, including the comments and othertongues.
+	pub fn nodes(&self) -> &RangeNodeQueue {
+		&self.nodes
+	}
+
+	/// Returns the top-level nodes.
+	pub fn roots(&self) -> impl Iterator<Item = DocumentNode<'_, 'src>> {
+		let source = self.source;
+		self.nodes.iter().map(move |node| DocumentNode { source, node })
+	}
+
+	/// Returns the text that the boundary covers.
+	pub fn resolve(&self, boundary: &Range<usize>) -> &'src str {
+		&self.source[boundary.clone()]
+	}
+}
+
+impl<'d, 'src: 'd> DocumentNode<'d, 'src> {
+	/// Returns the node that is referred to.
+	pub fn node(&self) -> &'d RangeNode {
+		self.node
+	}
+
+	/// Returns the name of a concept or the label of an attacher. Other nodes have empty names.
+	pub fn name(&self) -> &'src str {
+		match self.node {
+			Node::Complex(concept, _, _) | Node::Simplex(concept, _) => self.resolve(concept),
+			Node::Attacher(label, _, _) => self.resolve(label),
+			_ => ""
+		}
+	}
+
+	/// Returns the content of an attacher or the line of a line comment or othertongue. Other nodes
+	/// have empty contents.
+	pub fn content(&self) -> &'src str {
+		match self.node {
+			Node::Attacher(_, content, _) => self.resolve(content),
+			Node::LineComment(line) | Node::LineOthertongue(line) => self.resolve(line),
+			_ => ""
+		}
+	}
+
+	/// Returns the lines of a comment or othertongue.
+	pub fn lines(&self) -> impl Iterator<Item = &'src str> + 'd {
+		let source = self.source;
+		let lines = match self.node {
+			Node::LineComment(line) | Node::LineOthertongue(line) => core::slice::from_ref(line),
+			Node::BlockComment(lines) | Node::BlockOthertongue(lines) => lines.as_slice(),
+			_ => &[]
+		};
+		lines.iter().map(move |line| &source[line.clone()])
+	}
+
+	/// Returns the boundary that covers the node without its descendants.
+	pub fn span(&self) -> Range<usize> {
+		span_of(self.node)
+	}
+
+	/// Returns the attachers of a concept.
+	pub fn attachers(&self) -> impl Iterator<Item = DocumentNode<'d, 'src>> {
+		self.related(true, false).filter(|node| matches!(node.node, Node::Attacher(..)))
+	}
+
+	/// Returns the content of a complex except comments.
+	pub fn children(&self) -> impl Iterator<Item = DocumentNode<'d, 'src>> {
+		self.related(false, true).filter(|node| !node.is_comment())
+	}
+
+	/// Returns the comments among the attachers and content of a concept.
+	pub fn comments(&self) -> impl Iterator<Item = DocumentNode<'d, 'src>> {
+		self.related(true, true).filter(|node| node.is_comment())
+	}
+
+	fn related(&self, has_attachers: bool, has_content: bool)
+	-> impl Iterator<Item = DocumentNode<'d, 'src>> {
+		let source = self.source;
+		let (attachers, content) = match self.node {
+			Node::Complex(_, attachers, content) => (Some(attachers), Some(content)),
+			Node::Simplex(_, attachers) => (Some(attachers), None),
+			_ => (None, None)
+		};
+		let attachers = attachers.filter(|_| has_attachers).into_iter().flatten();
+		let content = content.filter(|_| has_content).into_iter().flatten();
+		attachers.chain(content).map(move |node| DocumentNode { source, node })
+	}
+
+	fn is_comment(&self) -> bool {
+		matches!(self.node, Node::LineComment(_) | Node::BlockComment(_))
+	}
+
+	fn resolve(&self, boundary: &Range<usize>) -> &'src str {
+		&self.source[boundary.clone()]
+	}
+}
+
+fn validate(source: &[u8]) -> Result<&str, Diagnostic> {
+	core::str::from_utf8(source).map_err(|error| {
+		let start = error.valid_up_to();
+		let end = error.error_len().map(|length| start + length).unwrap_or(source.len());
+		let message = format!("invalid UTF-8 at bytes {}..{}", start, end);
+		Diagnostic::error(start..end, "invalid_utf8", message)
+	})
+}
+
+fn validate_nodes(source: &str, nodes: &RangeNodeQueue) -> Result<(), Diagnostic> {
+	nodes.iter().try_for_each(|node| validate_node(source, node))
+}
+
+fn validate_node(source: &str, node: &RangeNode) -> Result<(), Diagnostic> {
+	match node {
+		Node::Complex(concept, attachers, content) => {
+			validate_boundary(source, concept)?;
+			validate_nodes(source, attachers)?;
+			validate_nodes(source, content)
+		},
+		Node::Simplex(concept, attachers) => {
+			validate_boundary(source, concept)?;
+			validate_nodes(source, attachers)
+		},
+		Node::Attacher(label, content, comments) => {
+			validate_boundary(source, label)?;
+			validate_boundary(source, content)?;
+			comments.iter().try_for_each(|comment| validate_boundary(source, comment))
+		},
+		Node::LineComment(line) | Node::LineOthertongue(line) => validate_boundary(source, line),
+		Node::BlockComment(lines) | Node::BlockOthertongue(lines) => {
+			lines.iter().try_for_each(|line| validate_boundary(source, line))
+		}
+	}
+}
+
+fn validate_boundary(source: &str, boundary: &Range<usize>) -> Result<(), Diagnostic> {
+	match source.get(boundary.clone()) {
+		Some(_) => Ok(()),
+		None => {
+			let message = format!(
+				"bytes {}..{} are outside the source or split a character",
+				boundary.start,
+				boundary.end
+			);
+			Err(Diagnostic::error(boundary.clone(), "invalid_boundary", message))
+		}
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use super::Document;
+
+	#[test]
+	fn can_resolve_node_texts() {
+		let source = "réseauport80enfant note";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..7, {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(Node::Attacher(7..11, 11..13, Vec::new()));
+			attachers.push_back(Node::LineComment(19..24));
+			attachers
+		}, {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(13..19, VecDeque::new()));
+			content
+		}));
+
+		let document = Document::new(source.as_bytes(), nodes).unwrap();
+		let root = document.roots().next().unwrap();
+
+		assert_eq!(root.name(), "réseau");
+		assert_eq!(root.span(), 0..7);
+		assert_eq!(root.attachers().map(|node| node.name()).collect::<Vec<_>>(), ["port"]);
+		assert_eq!(root.attachers().map(|node| node.content()).collect::<Vec<_>>(), ["80"]);
+		assert_eq!(root.children().map(|node| node.name()).collect::<Vec<_>>(), ["enfant"]);
+		assert_eq!(root.comments().flat_map(|node| node.lines()).collect::<Vec<_>>(), [" note"]);
+	}
+
+	#[test]
+	fn cannot_accept_invalid_utf8() {
+		let error = Document::parse(b"ok\xffbad|").unwrap_err();
+
+		assert_eq!(error.span, 2..3);
+		assert_eq!(error.code, "invalid_utf8");
+	}
+
+	#[test]
+	fn cannot_accept_invalid_boundaries() {
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..2, VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(3..9, VecDeque::new()));
+			content
+		}));
+
+		let error = Document::new("réseau".as_bytes(), nodes).unwrap_err();
+
+		assert_eq!(error.span, 0..2);
+		assert_eq!(error.code, "invalid_boundary");
+	}
+}
@@ -0,0 +1,277 @@
+use core::fmt::Write;
+use crate::native::String;
+use crate::node::Node;
+use crate::tree::{RangeNode, RangeNodeQueue, resolve};
+
+/// Returns an indented S-expression of the nodes whose boundaries have been resolved from the
+/// source.
+///
+/// Empty groups of attachers and content are omitted to keep the dump compact.
+///
+/// ## Examples
+/// ```
+/// use std::collections::VecDeque;
+/// use chearmyp_node::Node;
+/// use chearmyp_parser::dump;
+/// let source = b"hello\n\tworld|";
+/// let nodes = VecDeque::from(vec![
+/// 	Node::Complex(0..5, VecDeque::new(), VecDeque::from(vec![
+/// 		Node::Simplex(7..12, VecDeque::new())
+/// 	]))
+/// ]);
+///
+/// assert_eq!(dump(&source[..], &nodes), "(complex \"hello\"\n\t(content\n\t\t(simplex \"world\")))");
+/// ```
+pub fn dump(source: &[u8], nodes: &RangeNodeQueue) -> String {
+	let mut output = String::new();
+
+	for (index, node) in nodes.iter().enumerate() {
+		if index > 0 {
+			output.push('\n');
+		}
+
+		write_node(&mut output, source, node, 0);
+	}
+
+	output
+}
+
+/// Panics if the dump of the nodes differs from the expected dump.
+///
+/// Whitespace outside of quoted texts are not significant so the expected dump may be indented
+/// freely.
+pub fn assert_dump(source: &[u8], nodes: &RangeNodeQueue, expected_dump: &str) {
+	let dump = dump(source, nodes);
+
+	if normalize(&dump) != normalize(expected_dump) {
+		panic!("the dumps are not equal\n\nexpected:\n{}\n\nfound:\n{}\n", expected_dump.trim(), dump);
+	}
+}
+
+fn write_node(output: &mut String, source: &[u8], node: &RangeNode, depth: usize) {
+	indent(output, depth);
+
+	match node {
+		Node::Complex(concept, attachers, content) => {
+			output.push_str("(complex ");
+			write_text(output, resolve(source, concept));
+			write_group(output, source, "attachers", attachers, depth + 1);
+			write_group(output, source, "content", content, depth + 1);
+		},
+		Node::Simplex(concept, attachers) => {
+			output.push_str("(simplex ");
+			write_text(output, resolve(source, concept));
+			write_group(output, source, "attachers", attachers, depth + 1);
+		},
+		Node::Attacher(label, content, _) => {
+			output.push_str("(attacher ");
+			write_text(output, resolve(source, label));
+			output.push(' ');
+			write_text(output, resolve(source, content));
+		},
+		Node::LineComment(line) => {
+			output.push_str("(line_comment ");
+			write_text(output, resolve(source, line));
+		},
+		Node::BlockComment(lines) => {
+			output.push_str("(block_comment");
+			for line in lines {
+				output.push(' ');
+				write_text(output, resolve(source, line));
+			}
+		},
+		Node::LineOthertongue(line) => {
+			output.push_str("(line_othertongue ");
+			write_text(output, resolve(source, line));
+		},
+		Node::BlockOthertongue(lines) => {
+			output.push_str("(block_othertongue");
+			for line in lines {
+				output.push(' ');
+				write_text(output, resolve(source, line));
+			}
+		}
+	}
+
+	output.push(')');
+}
+
+fn write_group(
+	output: &mut String,
+	source: &[u8],
+	name: &str,
+	nodes: &RangeNodeQueue,
+	depth: usize
+) {
+	if nodes.is_empty() {
+		return;
+	}
+
+	output.push('\n');
+	indent(output, depth);
+	output.push('(');
+	output.push_str(name);
+
+	for node in nodes {
+		output.push('\n');
+		write_node(output, source, node, depth + 1);
+	}
+
+	output.push(')');
+}
+
+fn write_text(output: &mut String, text: &[u8]) {
+	output.push('"');
+
+	match core::str::from_utf8(text) {
+		Ok(text) => for character in text.chars() {
+			write_character(output, character);
+		},
+		Err(_) => for &byte in text {
+			if byte.is_ascii() && !byte.is_ascii_control() {
+				write_character(output, byte as char);
+			} else {
+				write!(output, "\\x{:02x}", byte).unwrap();
+			}
+		}
+	}
+
+	output.push('"');
+}
+
+fn write_character(output: &mut String, character: char) {
+	match character {
+		'"' => output.push_str("\\\""),
+		'\\' => output.push_str("\\\\"),
+		'\n' => output.push_str("\\n"),
+		'\r' => output.push_str("\\r"),
+		'\t' => output.push_str("\\t"),
+		character if character.is_control() => {
+			write!(output, "\\u{{{:x}}}", character as u32).unwrap();
+		},
+		character => output.push(character)
+	}
+}
+
+fn indent(output: &mut String, depth: usize) {
+	for _ in 0..depth {
+		output.push('\t');
+	}
+}
+
+/// Collapses the whitespace outside of quoted texts so that dumps can be compared.
+fn normalize(dump: &str) -> String {
+	let mut normalized = String::with_capacity(dump.len());
+	let mut is_quoted = false;
+	let mut is_escaped = false;
+	let mut has_pending_space = false;
+
+	for character in dump.chars() {
+		if is_quoted {
+			normalized.push(character);
+
+			if is_escaped {
+				is_escaped = false;
+			} else if character == '\\' {
+				is_escaped = true;
+			} else if character == '"' {
+				is_quoted = false;
+			}
+		} else if character.is_whitespace() {
+			has_pending_space = true;
+		} else {
+			let is_separated = !normalized.is_empty() && !normalized.ends_with('(');
+			if has_pending_space && is_separated && character != ')' {
+				normalized.push(' ');
+			}
+
+			has_pending_space = false;
+			is_quoted = character == '"';
+			normalized.push(character);
+		}
+	}
+
+	normalized
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use super::{dump, assert_dump};
+
+	#[test]
+	fn can_dump_nested_nodes() {
+		let source = b"# A sample source\nhello\n\tworld\n\t\tto: everyone\nhi universe|\n";
+		let nodes = {
+			let mut nodes = VecDeque::new();
+			nodes.push_back(Node::LineComment(1..17));
+			nodes.push_back(Node::Complex(18..23, VecDeque::new(), {
+				let mut content = VecDeque::new();
+				content.push_back(Node::Complex(25..30, {
+					let mut attachers = VecDeque::new();
+					attachers.push_back(Node::Attacher(33..35, 37..45, Vec::new()));
+					attachers
+				}, VecDeque::new()));
+				content
+			}));
+			nodes.push_back(Node::Simplex(46..57, VecDeque::new()));
+			nodes
+		};
+
+		let dump = dump(&source[..], &nodes);
+
+		assert_eq!(dump, concat!(
+			"(line_comment \" A sample source\")\n",
+			"(complex \"hello\"\n",
+			"\t(content\n",
+			"\t\t(complex \"world\"\n",
+			"\t\t\t(attachers\n",
+			"\t\t\t\t(attacher \"to\" \"everyone\")))))\n",
+			"(simplex \"hi universe\")"
+		));
+	}
+
+	#[test]
+	fn can_escape_texts() {
+		let source = b"say \"hi\"\xff\x1b[0m\x7f";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::LineOthertongue(0..8));
+		nodes.push_back(Node::LineOthertongue(4..9));
+		nodes.push_back(Node::LineOthertongue(9..14));
+
+		let dump = dump(&source[..], &nodes);
+
+		assert_eq!(dump, concat!(
+			"(line_othertongue \"say \\\"hi\\\"\")\n",
+			"(line_othertongue \"\\\"hi\\\"\\xff\")\n",
+			"(line_othertongue \"\\u{1b}[0m\\u{7f}\")"
+		));
+	}
+
+	#[test]
+	fn can_assert_freely_indented_dump() {
+		let source = b"hello\n\tworld|";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..5, VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(7..12, VecDeque::new()));
+			content
+		}));
+
+		assert_dump(&source[..], &nodes, "
+			(complex \"hello\"
+				(content (simplex \"world\")))
+		");
+	}
+
+	#[test]
+	#[should_panic]
+	fn cannot_assert_different_dump() {
+		let source = b"hello";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Simplex(0..5, VecDeque::new()));
+
+		assert_dump(&source[..], &nodes, "(simplex \"hell o\")");
+	}
+}
@@ -0,0 +1,314 @@
+//! An include directive is a concept named `include` with a `path` attacher:
+//!
+//! ```text
+//! server
+//! 	include|
+//! 		path: listeners.chr
+//! ```
+//!
+//! The directive is replaced by the top-level nodes of the referenced file, whose own directives
+//! are resolved as well. Paths are located relative to the file that includes them. Directives
+//! among attachers are left as they are since only concepts can be included.
+
+use crate::native::{format, Map, Range, String, Vec, VecDeque};
+use crate::node::Node;
+use crate::builder::TreeBuilder;
+use crate::diagnostic::Diagnostic;
+use crate::parse::parse_source;
+use crate::tree::{RangeNode, RangeNodeQueue, resolve};
+
+/// Represents a reader of the files that are referenced by include directives.
+pub trait SourceLoader {
+	/// Returns the identifier of the file that the path refers to. The including path is the
+	/// identifier of the file that contains the directive.
+	///
+	/// Identifiers are compared to detect cycles so the same file should have one identifier.
+	fn locate(&self, path: &str, including_path: &str) -> String;
+
+	/// Reads the file with the identifier or returns the reason why it cannot be read.
+	fn load(&mut self, path: &str) -> Result<Vec<u8>, String>;
+}
+
+/// Loads files in memory by their paths as is. It is useful for tests or where there is no file
+/// system.
+impl SourceLoader for Map<String, Vec<u8>> {
+	fn locate(&self, path: &str, _including_path: &str) -> String {
+		path.into()
+	}
+
+	fn load(&mut self, path: &str) -> Result<Vec<u8>, String> {
+		self.get(path).cloned().ok_or_else(|| "file does not exist".into())
+	}
+}
+
+/// Loads files from the file system. Relative paths are located from the directory of the
+/// including file and are normalized without accessing the file system.
+#[cfg(not(feature = "no_std"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileLoader;
+
+#[cfg(not(feature = "no_std"))]
+impl SourceLoader for FileLoader {
+	fn locate(&self, path: &str, including_path: &str) -> String {
+		use std::path::{Component, Path, PathBuf};
+
+		let directory = Path::new(including_path).parent().unwrap_or(Path::new(""));
+		let mut located_path = PathBuf::new();
+
+		for component in directory.join(path).components() {
+			match component {
+				Component::CurDir => {},
+				Component::ParentDir if matches!(
+					located_path.components().next_back(),
+					Some(Component::Normal(_))
+				) => {
+					located_path.pop();
+				},
+				_ => located_path.push(component)
+			}
+		}
+
+		located_path.to_string_lossy().into_owned()
+	}
+
+	fn load(&mut self, path: &str) -> Result<Vec<u8>, String> {
+		std::fs::read(path).map_err(|error| error.to_string())
+	}
+}
+
+/// Contains the nodes of the files that have been composed together.
+#[derive(Debug, Clone, Default)]
+pub struct Composition {
+	/// Source that the boundaries of the nodes refer to.
+	pub source: Vec<u8>,
+	pub nodes: RangeNodeQueue,
+	/// Problems found while resolving the directives. Their spans refer to the files which are
+	/// named in their messages, not to the composed source.
+	pub diagnostics: Vec<Diagnostic>
+}
+
+/// Loads the file and replaces its include directives with the nodes of the referenced files.
+///
+/// Directives that cannot be resolved are removed and reported.
+///
+/// ## Examples
+/// ```
+/// use std::collections::HashMap;
+/// use chearmyp_parser::{resolve_includes, assert_dump};
+/// let mut files = HashMap::new();
+/// files.insert("main".to_string(), b"server\n\tinclude|\n\t\tpath: port\n".to_vec());
+/// files.insert("port".to_string(), b"port|\n".to_vec());
+///
+/// let composition = resolve_includes(&mut files, "main");
+///
+/// assert!(composition.diagnostics.is_empty());
+/// assert_dump(&composition.source, &composition.nodes, "
+/// 	(complex \"server\" (content (simplex \"port\")))
+/// ");
+/// ```
+pub fn resolve_includes<L>(loader: &mut L, path: &str) -> Composition
+where L: SourceLoader + ?Sized {
+	let mut resolver = Resolver {
+		loader,
+		builder: TreeBuilder::new(),
+		files: Vec::new(),
+		directive_spans: Vec::new(),
+		diagnostics: Vec::new()
+	};
+
+	let nodes = match resolver.loader.load(path) {
+		Ok(source) => resolver.resolve_file(path.into(), &source),
+		Err(reason) => {
+			let message = format!("cannot load `{}`: {}", path, reason);
+			resolver.diagnostics.push(Diagnostic::error(0..0, "unloadable_include", message));
+			VecDeque::new()
+		}
+	};
+
+	Composition {
+		source: resolver.builder.finish(),
+		nodes,
+		diagnostics: resolver.diagnostics
+	}
+}
+
+struct Resolver<'l, L: SourceLoader + ?Sized> {
+	loader: &'l mut L,
+	builder: TreeBuilder,
+	/// Identifiers of the files being resolved, from the first file to the current one.
+	files: Vec<String>,
+	/// Spans of the directives that led to the next files in the chain.
+	directive_spans: Vec<Range<usize>>,
+	diagnostics: Vec<Diagnostic>
+}
+
+impl<'l, L: SourceLoader + ?Sized> Resolver<'l, L> {
+	fn resolve_file(&mut self, path: String, source: &[u8]) -> RangeNodeQueue {
+		let nodes = parse_source(source);
+		self.files.push(path);
+		let resolved_nodes = self.resolve_nodes(source, &nodes);
+		self.files.pop();
+		resolved_nodes
+	}
+
+	fn resolve_nodes(&mut self, source: &[u8], nodes: &RangeNodeQueue) -> RangeNodeQueue {
+		let mut resolved_nodes = VecDeque::with_capacity(nodes.len());
+
+		for node in nodes {
+			match node {
+				Node::Complex(concept, _, _) | Node::Simplex(concept, _)
+				if resolve(source, concept) == b"include" => {
+					resolved_nodes.extend(self.include(source, node, concept.clone()));
+				},
+				Node::Complex(concept, attachers, content) => {
+					let concept = self.builder.push_text(resolve(source, concept));
+					let attachers = self.builder.copy_all(source, attachers);
+					let content = self.resolve_nodes(source, content);
+					resolved_nodes.push_back(Node::Complex(concept, attachers, content));
+				},
+				_ => resolved_nodes.push_back(self.builder.copy(source, node))
+			}
+		}
+
+		resolved_nodes
+	}
+
+	fn include(&mut self, source: &[u8], directive: &RangeNode, span: Range<usize>)
+	-> RangeNodeQueue {
+		let path = match self.directive_path(source, directive, &span) {
+			Some(path) => path,
+			None => return VecDeque::new()
+		};
+		let including_path = self.files.last().map(String::as_str).unwrap_or("");
+		let located_path = self.loader.locate(&path, including_path);
+
+		if let Some(index) = self.files.iter().position(|file| *file == located_path) {
+			let cycle = self.files[index..].iter()
+				.chain(core::iter::once(&located_path))
+				.map(|file| format!("`{}`", file))
+				.collect::<Vec<_>>()
+				.join(" -> ");
+			let message = format!("cyclic include {}", cycle);
+			self.report(span, "cyclic_include", message);
+			return VecDeque::new();
+		}
+
+		match self.loader.load(&located_path) {
+			Ok(included_source) => {
+				self.directive_spans.push(span);
+				let nodes = self.resolve_file(located_path, &included_source);
+				self.directive_spans.pop();
+				nodes
+			},
+			Err(reason) => {
+				let message = format!("cannot load `{}`: {}", located_path, reason);
+				self.report(span, "unloadable_include", message);
+				VecDeque::new()
+			}
+		}
+	}
+
+	fn directive_path(&mut self, source: &[u8], directive: &RangeNode, span: &Range<usize>)
+	-> Option<String> {
+		let (attachers, has_content) = match directive {
+			Node::Complex(_, attachers, content) => (attachers, !content.is_empty()),
+			Node::Simplex(_, attachers) => (attachers, false),
+			_ => return None
+		};
+
+		if has_content {
+			let message = "include directive cannot have content".into();
+			self.report(span.clone(), "invalid_include", message);
+			return None;
+		}
+
+		let path = attachers.iter().find_map(|attacher| match attacher {
+			Node::Attacher(label, content, _) if resolve(source, label) == b"path" => {
+				Some((content, core::str::from_utf8(resolve(source, content))))
+			},
+			_ => None
+		});
+
+		match path {
+			Some((_, Ok(path))) => Some(path.into()),
+			Some((content, Err(_))) => {
+				let message = "path of include directive is not valid UTF-8".into();
+				self.report(content.clone(), "invalid_include", message);
+				None
+			},
+			None => {
+				let message = "include directive has no `path` attacher".into();
+				self.report(span.clone(), "invalid_include", message);
+				None
+			}
+		}
+	}
+
+	/// Reports a problem in the current file together with the chain of files that included it.
+	fn report(&mut self, span: Range<usize>, code: &'static str, message: String) {
+		let mut message = format!("{} in `{}`", message, self.files[self.files.len() - 1]);
+
+		for (file, directive_span) in self.files.iter().zip(&self.directive_spans).rev() {
+			message.push_str(&format!(
+				", included from `{}` at bytes {}..{}",
+				file,
+				directive_span.start,
+				directive_span.end
+			));
+		}
+
+		self.diagnostics.push(Diagnostic::error(span, code, message));
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Map, String, Vec};
+	use crate::dump::assert_dump;
+	use super::resolve_includes;
+
+	fn files(entries: &[(&str, &str)]) -> Map<String, Vec<u8>> {
+		entries.iter()
+			.map(|(path, source)| (String::from(*path), source.as_bytes().to_vec()))
+			.collect()
+	}
+
+	#[test]
+	fn can_compose_nested_includes() {
+		let mut loader = files(&[
+			("main", "a\n\tinclude|\n\t\tpath: b\n\tz|\n"),
+			("b", "b|\ninclude|\n\tpath: c\n"),
+			("c", "c\n\tname: value\n")
+		]);
+
+		let composition = resolve_includes(&mut loader, "main");
+
+		assert!(composition.diagnostics.is_empty());
+		assert_dump(&composition.source, &composition.nodes, "
+			(complex \"a\" (content
+				(simplex \"b\")
+				(complex \"c\" (attachers (attacher \"name\" \"value\")))
+				(simplex \"z\")))
+		");
+	}
+
+	#[test]
+	fn cannot_include_cyclically() {
+		let mut loader = files(&[
+			("main", "include|\n\tpath: b\n"),
+			("b", "include|\n\tpath: main\ninclude|\n\tpath: missing\n")
+		]);
+
+		let composition = resolve_includes(&mut loader, "main");
+
+		let messages = composition.diagnostics.iter()
+			.map(|diagnostic| diagnostic.message.as_str())
+			.collect::<Vec<_>>();
+		assert_eq!(messages, [
+			"cyclic include `main` -> `b` -> `main` in `b`, included from `main` at bytes 0..7",
+			"cannot load `missing`: file does not exist in `b`, included from `main` at bytes 0..7"
+		]);
+		assert_eq!(composition.diagnostics[1].span, 21..28);
+		assert!(composition.nodes.is_empty());
+	}
+}
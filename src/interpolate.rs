@@ -0,0 +1,315 @@
+//! References are written as `${name}` in the contents of attachers. Write `$${name}` to keep a
+//! reference as literal text. The value of a name is looked up in this order:
+//!
+//! 1. the variables given by the caller,
+//! 2. the attachers of the first top-level `vars` concept,
+//! 3. the process environment, if it has been enabled.
+//!
+//! Values of the `vars` concept may refer to other names. Values from the caller and environment
+//! are used as they are. The `vars` concept is removed from the expanded nodes.
+
+use crate::native::{format, Map, Range, String, Vec, VecDeque};
+use crate::node::Node;
+use crate::attacher_map::AttacherMap;
+use crate::builder::TreeBuilder;
+use crate::diagnostic::Diagnostic;
+use crate::tree::{RangeNode, RangeNodeQueue, path_of, resolve, span_of};
+
+/// Contains the options for expanding references.
+#[derive(Debug, Clone, Default)]
+pub struct InterpolationOptions {
+	/// Values which take precedence over the ones declared in the source.
+	pub variables: Map<String, String>,
+	/// Looks up the names that have not been declared in the process environment.
+	#[cfg(not(feature = "no_std"))]
+	pub environment: bool
+}
+
+/// Contains the nodes whose references have been expanded.
+#[derive(Debug, Clone, Default)]
+pub struct Expansion {
+	/// Source that the boundaries of the nodes refer to.
+	pub source: Vec<u8>,
+	pub nodes: RangeNodeQueue,
+	/// Boundaries of the nodes in the original source keyed by the indices that lead to the nodes.
+	pub original_spans: Vec<(Vec<usize>, Range<usize>)>,
+	/// Problems found while expanding. Their spans refer to the original source.
+	pub diagnostics: Vec<Diagnostic>
+}
+
+impl Expansion {
+	/// Returns the boundary in the original source of the node that the expanded node copies.
+	///
+	/// The node is found by its address so it must be borrowed from the expanded nodes.
+	pub fn original_span_of(&self, node: &RangeNode) -> Option<Range<usize>> {
+		let path = path_of(&self.nodes, node)?;
+		self.original_spans.iter()
+			.find(|(expanded_path, _)| *expanded_path == path)
+			.map(|(_, span)| span.clone())
+	}
+}
+
+/// Replaces the references in the contents of attachers with their values.
+///
+/// References that cannot be expanded are kept as they are and reported.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, interpolate, assert_dump, InterpolationOptions};
+/// let source = b"vars\n\thost: example.com\nserver\n\turl: https://${host}/\n";
+/// let nodes = parse_source(&source[..]);
+///
+/// let expansion = interpolate(&source[..], &nodes, &InterpolationOptions::default());
+///
+/// assert!(expansion.diagnostics.is_empty());
+/// assert_dump(&expansion.source, &expansion.nodes, "
+/// 	(complex \"server\" (attachers (attacher \"url\" \"https://example.com/\")))
+/// ");
+/// ```
+pub fn interpolate(source: &[u8], nodes: &RangeNodeQueue, options: &InterpolationOptions)
+-> Expansion {
+	let declarations = nodes.iter()
+		.find(|node| is_vars(source, node))
+		.map(|node| AttacherMap::of(source, node))
+		.unwrap_or_default();
+	let mut interpolator = Interpolator {
+		source,
+		options,
+		declarations,
+		values: Map::new(),
+		resolving_names: Vec::new(),
+		cyclic_names: Vec::new(),
+		builder: TreeBuilder::new(),
+		path: Vec::new(),
+		original_spans: Vec::new(),
+		diagnostics: Vec::new()
+	};
+
+	let top_level_nodes = nodes.iter().filter(|node| !is_vars(source, node));
+	let nodes = interpolator.expand_nodes(top_level_nodes, 0);
+
+	Expansion {
+		source: interpolator.builder.finish(),
+		nodes,
+		original_spans: interpolator.original_spans,
+		diagnostics: interpolator.diagnostics
+	}
+}
+
+fn is_vars(source: &[u8], node: &RangeNode) -> bool {
+	match node {
+		Node::Complex(concept, _, _) | Node::Simplex(concept, _) => {
+			resolve(source, concept) == b"vars"
+		},
+		_ => false
+	}
+}
+
+struct Interpolator<'s, 'o> {
+	source: &'s [u8],
+	options: &'o InterpolationOptions,
+	declarations: AttacherMap<'s>,
+	/// Expanded values of the declared names. Names whose values refer to themselves have none.
+	values: Map<&'s [u8], Option<Vec<u8>>>,
+	/// Declared names whose values are being expanded.
+	resolving_names: Vec<&'s [u8]>,
+	/// Declared names whose values have been found to refer to themselves.
+	cyclic_names: Vec<&'s [u8]>,
+	builder: TreeBuilder,
+	/// Indices that lead to the node being expanded.
+	path: Vec<usize>,
+	original_spans: Vec<(Vec<usize>, Range<usize>)>,
+	diagnostics: Vec<Diagnostic>
+}
+
+impl<'s, 'o> Interpolator<'s, 'o> {
+	fn expand_node(&mut self, node: &RangeNode) -> RangeNode {
+		self.original_spans.push((self.path.clone(), span_of(node)));
+
+		match node {
+			Node::Complex(concept, attachers, content) => {
+				let concept = self.builder.push_text(resolve(self.source, concept));
+				let attachers = self.expand_nodes(attachers, 0);
+				let content = self.expand_nodes(content, attachers.len());
+				Node::Complex(concept, attachers, content)
+			},
+			Node::Simplex(concept, attachers) => {
+				let concept = self.builder.push_text(resolve(self.source, concept));
+				let attachers = self.expand_nodes(attachers, 0);
+				Node::Simplex(concept, attachers)
+			},
+			Node::Attacher(label, content, _) => {
+				let content = self.expand_text(content.clone());
+				self.builder.attacher(resolve(self.source, label), &content)
+			},
+			_ => self.builder.copy(self.source, node)
+		}
+	}
+
+	/// Expands the nodes whose indices among their siblings start from the given index.
+	fn expand_nodes<'n, T>(&mut self, nodes: T, start: usize) -> RangeNodeQueue
+	where
+		T: IntoIterator<Item = &'n RangeNode> {
+		let mut expanded_nodes = VecDeque::new();
+
+		for node in nodes {
+			self.path.push(start + expanded_nodes.len());
+			let expanded_node = self.expand_node(node);
+			self.path.pop();
+			expanded_nodes.push_back(expanded_node);
+		}
+
+		expanded_nodes
+	}
+
+	fn expand_text(&mut self, boundary: Range<usize>) -> Vec<u8> {
+		let source = self.source;
+		let text = resolve(source, &boundary);
+		let mut expanded_text = Vec::with_capacity(text.len());
+		let mut index = 0;
+
+		while index < text.len() {
+			let rest = &text[index..];
+
+			if rest.starts_with(b"$${") {
+				expanded_text.extend_from_slice(b"${");
+				index += 3;
+			} else if rest.starts_with(b"${") {
+				let start = boundary.start + index;
+				let name_length = match rest[2..].iter().position(|&byte| byte == b'}') {
+					Some(name_length) => name_length,
+					None => {
+						let message = "reference is not closed with `}`".into();
+						let span = start..boundary.end;
+						self.diagnostics.push(Diagnostic::error(span, "invalid_reference", message));
+						expanded_text.extend_from_slice(rest);
+						break;
+					}
+				};
+				let reference = &rest[..name_length + 3];
+				let span = start..start + reference.len();
+
+				match self.value(&reference[2..name_length + 2], span) {
+					Some(value) => expanded_text.extend_from_slice(&value),
+					None => expanded_text.extend_from_slice(reference)
+				}
+
+				index += reference.len();
+			} else {
+				expanded_text.push(text[index]);
+				index += 1;
+			}
+		}
+
+		expanded_text
+	}
+
+	fn value(&mut self, name: &'s [u8], span: Range<usize>) -> Option<Vec<u8>> {
+		let printable_name = String::from_utf8_lossy(name);
+
+		if name.is_empty() {
+			let message = "reference has no name".into();
+			self.diagnostics.push(Diagnostic::error(span, "invalid_reference", message));
+			return None;
+		}
+
+		let given_value = core::str::from_utf8(name).ok()
+			.and_then(|name| self.options.variables.get(name));
+		if let Some(value) = given_value {
+			return Some(value.as_bytes().to_vec());
+		}
+
+		if let Some(index) = self.resolving_names.iter().position(|&resolving| resolving == name) {
+			let cycle = self.resolving_names[index..].iter()
+				.chain(core::iter::once(&name))
+				.map(|name| format!("`{}`", String::from_utf8_lossy(name)))
+				.collect::<Vec<_>>()
+				.join(" -> ");
+			let message = format!("variable `{}` refers to itself through {}", printable_name, cycle);
+			self.diagnostics.push(Diagnostic::error(span, "cyclic_variable", message));
+			self.cyclic_names.extend_from_slice(&self.resolving_names[index..]);
+			return None;
+		}
+
+		if let Some(value) = self.values.get(name) {
+			return value.clone();
+		}
+
+		if let Some(declaration) = self.declarations.get(name) {
+			let content_span = declaration.content_span.clone();
+			self.resolving_names.push(name);
+			let value = self.expand_text(content_span);
+			self.resolving_names.pop();
+			let value = Some(value).filter(|_| !self.cyclic_names.contains(&name));
+			self.values.insert(name, value.clone());
+			return value;
+		}
+
+		#[cfg(not(feature = "no_std"))]
+		if self.options.environment {
+			let environment_value = core::str::from_utf8(name).ok()
+				.and_then(|name| std::env::var(name).ok());
+			if let Some(value) = environment_value {
+				return Some(value.into_bytes());
+			}
+		}
+
+		let message = format!("variable `{}` is not defined", printable_name);
+		self.diagnostics.push(Diagnostic::error(span, "undefined_variable", message));
+		None
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::Vec;
+	use crate::node::Node;
+	use crate::dump::assert_dump;
+	use crate::parse::parse_source;
+	use super::{InterpolationOptions, interpolate};
+
+	#[test]
+	fn can_expand_references() {
+		let source = b"vars\n\tname: app\n\timage: ${name}:${version}\nx|\n\ta: ${image} $${name}\n";
+		let nodes = parse_source(&source[..]);
+		let mut options = InterpolationOptions::default();
+		options.variables.insert("version".into(), "1.2".into());
+
+		let expansion = interpolate(&source[..], &nodes, &options);
+
+		assert!(expansion.diagnostics.is_empty());
+		assert_dump(&expansion.source, &expansion.nodes, "
+			(simplex \"x\" (attachers (attacher \"a\" \"app:1.2 ${name}\")))
+		");
+		let attacher = match &expansion.nodes[0] {
+			Node::Simplex(_, attachers) => &attachers[0],
+			_ => unreachable!()
+		};
+		assert_eq!(expansion.original_span_of(&expansion.nodes[0]), Some(43..44));
+		assert_eq!(expansion.original_span_of(attacher), Some(47..67));
+	}
+
+	#[test]
+	fn cannot_expand_undefined_or_cyclic_references() {
+		let source = b"vars\n\ta: ${b}\n\tb: ${a}\nx|\n\tc: ${a} ${b} ${d} ${e\n";
+		let nodes = parse_source(&source[..]);
+
+		let expansion = interpolate(&source[..], &nodes, &InterpolationOptions::default());
+
+		let codes = expansion.diagnostics.iter()
+			.map(|diagnostic| (diagnostic.code, diagnostic.span.clone()))
+			.collect::<Vec<_>>();
+		assert_eq!(codes, [
+			("cyclic_variable", 18..22),
+			("undefined_variable", 40..44),
+			("invalid_reference", 45..48)
+		]);
+		assert_eq!(
+			expansion.diagnostics[0].message,
+			"variable `a` refers to itself through `a` -> `b` -> `a`"
+		);
+		assert_dump(&expansion.source, &expansion.nodes, "
+			(simplex \"x\" (attachers (attacher \"c\" \"${a} ${b} ${d} ${e\")))
+		");
+	}
+}
@@ -0,0 +1,458 @@
+//! Every node is represented as an object with a `kind` field. The rest of the fields depend on the
+//! kind of node:
+//!
+//! | Kind                | Fields                              |
+//! |---------------------|-------------------------------------|
+//! | `complex`           | `name`, `attachers`, and `content`  |
+//! | `simplex`           | `name` and `attachers`              |
+//! | `attacher`          | `label` and `content`               |
+//! | `line_comment`      | `line`                              |
+//! | `block_comment`     | `lines`                             |
+//! | `line_othertongue`  | `line`                              |
+//! | `block_othertongue` | `lines`                             |
+//!
+//! If spans have been requested, each text field is accompanied by a field with a `_span` suffix
+//! which contains the start and end of its boundary in bytes. Lines have their spans in
+//! `line_spans`. Spans are ignored when deserializing since the boundaries would be reassigned.
+//!
+//! Texts that are not valid UTF-8 cannot be serialized. Nodes are only deserialized where the
+//! parser would place them. Attachers cannot be in the content of complexes, while concepts and
+//! othertongues cannot be among attachers. The top level may have any kind of node.
+
+use core::fmt::{Display, Formatter, Result as FormatResult};
+use serde_json::{Map, Value};
+use crate::native::{format, Range, String, Vec, VecDeque};
+use crate::node::Node;
+use crate::builder::TreeBuilder;
+use crate::tree::{RangeNode, RangeNodeQueue, resolve};
+
+/// Contains the options for serializing nodes to JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+	/// Includes the byte spans of the texts.
+	pub spans: bool
+}
+
+/// Represents the error found when serializing nodes to JSON or deserializing them from JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+	/// JSON pointer of the value that caused the error or that could not be created.
+	pub path: String,
+	/// Description of the problem.
+	pub message: String
+}
+
+impl JsonError {
+	fn new(path: &str, message: String) -> Self {
+		Self { path: path.into(), message }
+	}
+}
+
+impl Display for JsonError {
+	fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+		if self.path.is_empty() {
+			write!(formatter, "{}", self.message)
+		} else {
+			write!(formatter, "{} at `{}`", self.message, self.path)
+		}
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for JsonError {}
+
+/// Returns a JSON array of nodes whose texts have been resolved from the source.
+///
+/// ## Examples
+/// ```
+/// use std::collections::VecDeque;
+/// use serde_json::json;
+/// use chearmyp_node::Node;
+/// use chearmyp_parser::{to_json, JsonOptions};
+/// let source = b"hello|";
+/// let nodes = VecDeque::from(vec![Node::Simplex(0..5, VecDeque::new())]);
+///
+/// let value = to_json(&source[..], &nodes, JsonOptions { spans: true }).unwrap();
+///
+/// assert_eq!(value, json!([
+/// 	{ "kind": "simplex", "name": "hello", "name_span": [0, 5], "attachers": [] }
+/// ]));
+/// ```
+pub fn to_json(source: &[u8], nodes: &RangeNodeQueue, options: JsonOptions)
+-> Result<Value, JsonError> {
+	nodes_to_json(source, nodes, options, "")
+}
+
+/// Returns the nodes and their source from a JSON array that follows the schema of [`to_json()`].
+pub fn from_json(value: &Value) -> Result<(Vec<u8>, RangeNodeQueue), JsonError> {
+	let mut builder = TreeBuilder::new();
+	let nodes = nodes_from_json(&mut builder, value, "", Group::TopLevel)?;
+	Ok((builder.finish(), nodes))
+}
+
+/// Represents where nodes are listed, which limits the kinds of nodes that may be there.
+#[derive(Clone, Copy)]
+enum Group {
+	TopLevel,
+	Attachers,
+	Content
+}
+
+fn nodes_to_json(source: &[u8], nodes: &RangeNodeQueue, options: JsonOptions, path: &str)
+-> Result<Value, JsonError> {
+	let values = nodes.iter().enumerate().map(|(index, node)| {
+		node_to_json(source, node, options, &format!("{}/{}", path, index))
+	}).collect::<Result<_, _>>()?;
+	Ok(Value::Array(values))
+}
+
+fn node_to_json(source: &[u8], node: &RangeNode, options: JsonOptions, path: &str)
+-> Result<Value, JsonError> {
+	let mut object = Map::new();
+	let nodes_path = |key: &str| format!("{}/{}", path, key);
+
+	match node {
+		Node::Complex(concept, attachers, content) => {
+			object.insert("kind".into(), "complex".into());
+			insert_text(&mut object, "name", source, concept, options, path)?;
+			let attachers = nodes_to_json(source, attachers, options, &nodes_path("attachers"))?;
+			object.insert("attachers".into(), attachers);
+			let content = nodes_to_json(source, content, options, &nodes_path("content"))?;
+			object.insert("content".into(), content);
+		},
+		Node::Simplex(concept, attachers) => {
+			object.insert("kind".into(), "simplex".into());
+			insert_text(&mut object, "name", source, concept, options, path)?;
+			let attachers = nodes_to_json(source, attachers, options, &nodes_path("attachers"))?;
+			object.insert("attachers".into(), attachers);
+		},
+		Node::Attacher(label, content, _) => {
+			object.insert("kind".into(), "attacher".into());
+			insert_text(&mut object, "label", source, label, options, path)?;
+			insert_text(&mut object, "content", source, content, options, path)?;
+		},
+		Node::LineComment(line) => {
+			object.insert("kind".into(), "line_comment".into());
+			insert_text(&mut object, "line", source, line, options, path)?;
+		},
+		Node::BlockComment(lines) => {
+			object.insert("kind".into(), "block_comment".into());
+			insert_lines(&mut object, source, lines, options, path)?;
+		},
+		Node::LineOthertongue(line) => {
+			object.insert("kind".into(), "line_othertongue".into());
+			insert_text(&mut object, "line", source, line, options, path)?;
+		},
+		Node::BlockOthertongue(lines) => {
+			object.insert("kind".into(), "block_othertongue".into());
+			insert_lines(&mut object, source, lines, options, path)?;
+		}
+	}
+
+	Ok(Value::Object(object))
+}
+
+fn insert_text(
+	object: &mut Map<String, Value>,
+	key: &str,
+	source: &[u8],
+	boundary: &Range<usize>,
+	options: JsonOptions,
+	path: &str
+) -> Result<(), JsonError> {
+	let text = text_to_json(source, boundary, &format!("{}/{}", path, key))?;
+	object.insert(key.into(), text);
+
+	if options.spans {
+		object.insert(format!("{}_span", key), span_to_json(boundary));
+	}
+
+	Ok(())
+}
+
+fn insert_lines(
+	object: &mut Map<String, Value>,
+	source: &[u8],
+	lines: &[Range<usize>],
+	options: JsonOptions,
+	path: &str
+) -> Result<(), JsonError> {
+	let texts = lines.iter().enumerate().map(|(index, line)| {
+		text_to_json(source, line, &format!("{}/lines/{}", path, index))
+	}).collect::<Result<_, _>>()?;
+	object.insert("lines".into(), Value::Array(texts));
+
+	if options.spans {
+		let spans = lines.iter().map(span_to_json).collect();
+		object.insert("line_spans".into(), Value::Array(spans));
+	}
+
+	Ok(())
+}
+
+fn text_to_json(source: &[u8], boundary: &Range<usize>, path: &str) -> Result<Value, JsonError> {
+	match core::str::from_utf8(resolve(source, boundary)) {
+		Ok(text) => Ok(Value::String(text.into())),
+		Err(_) => Err(JsonError::new(path, "expected the text to be valid UTF-8".into()))
+	}
+}
+
+fn span_to_json(boundary: &Range<usize>) -> Value {
+	let mut span = Vec::with_capacity(2);
+	span.push(boundary.start.into());
+	span.push(boundary.end.into());
+	Value::Array(span)
+}
+
+fn nodes_from_json(builder: &mut TreeBuilder, value: &Value, path: &str, group: Group)
+-> Result<RangeNodeQueue, JsonError> {
+	let values = value.as_array().ok_or_else(|| {
+		JsonError::new(path, "expected an array of nodes".into())
+	})?;
+	let mut nodes = VecDeque::with_capacity(values.len());
+
+	for (index, value) in values.iter().enumerate() {
+		let path = format!("{}/{}", path, index);
+		nodes.push_back(node_from_json(builder, value, &path, group)?);
+	}
+
+	Ok(nodes)
+}
+
+fn node_from_json(builder: &mut TreeBuilder, value: &Value, path: &str, group: Group)
+-> Result<RangeNode, JsonError> {
+	let kind = text_from_json(value, "kind", path)?;
+	let is_misplaced = match (group, kind) {
+		(Group::Attachers, "complex" | "simplex" | "line_othertongue" | "block_othertongue") => true,
+		(Group::Content, "attacher") => true,
+		_ => false
+	};
+
+	if is_misplaced {
+		let place = if let Group::Attachers = group { "attachers" } else { "content" };
+		return Err(JsonError::new(path, format!("`{}` cannot be in {}", kind, place)));
+	}
+
+	match kind {
+		"complex" => {
+			let name = text_from_json(value, "name", path)?;
+			let attachers = optional_nodes_from_json(builder, value, "attachers", path)?;
+			let content = optional_nodes_from_json(builder, value, "content", path)?;
+			Ok(builder.complex(name.as_bytes(), attachers, content))
+		},
+		"simplex" => {
+			let name = text_from_json(value, "name", path)?;
+			let attachers = optional_nodes_from_json(builder, value, "attachers", path)?;
+			Ok(builder.simplex(name.as_bytes(), attachers))
+		},
+		"attacher" => {
+			let label = text_from_json(value, "label", path)?;
+			let content = text_from_json(value, "content", path)?;
+			Ok(builder.attacher(label.as_bytes(), content.as_bytes()))
+		},
+		"line_comment" => {
+			let line = text_from_json(value, "line", path)?;
+			Ok(builder.line_comment(line.as_bytes()))
+		},
+		"block_comment" => {
+			let lines = lines_from_json(value, path)?;
+			Ok(builder.block_comment(lines.into_iter().map(str::as_bytes)))
+		},
+		"line_othertongue" => {
+			let line = text_from_json(value, "line", path)?;
+			Ok(builder.line_othertongue(line.as_bytes()))
+		},
+		"block_othertongue" => {
+			let lines = lines_from_json(value, path)?;
+			Ok(builder.block_othertongue(lines.into_iter().map(str::as_bytes)))
+		},
+		kind => Err(JsonError::new(path, format!("unknown kind of node `{}`", kind)))
+	}
+}
+
+fn text_from_json<'a>(value: &'a Value, key: &str, path: &str) -> Result<&'a str, JsonError> {
+	value.get(key).and_then(Value::as_str).ok_or_else(|| {
+		JsonError::new(path, format!("expected `{}` to be a string", key))
+	})
+}
+
+fn optional_nodes_from_json(builder: &mut TreeBuilder, value: &Value, key: &str, path: &str)
+-> Result<RangeNodeQueue, JsonError> {
+	let group = if key == "attachers" { Group::Attachers } else { Group::Content };
+
+	match value.get(key) {
+		Some(nodes) => nodes_from_json(builder, nodes, &format!("{}/{}", path, key), group),
+		None => Ok(VecDeque::new())
+	}
+}
+
+fn lines_from_json<'a>(value: &'a Value, path: &str) -> Result<Vec<&'a str>, JsonError> {
+	let lines = value.get("lines").and_then(Value::as_array).ok_or_else(|| {
+		JsonError::new(path, "expected `lines` to be an array".into())
+	})?;
+
+	lines.iter().enumerate().map(|(index, line)| {
+		line.as_str().ok_or_else(|| {
+			JsonError::new(&format!("{}/lines/{}", path, index), "expected a string".into())
+		})
+	}).collect()
+}
+
+#[cfg(test)]
+mod t {
+	use serde_json::json;
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use crate::dump::{dump, assert_dump};
+	use super::{to_json, from_json, JsonOptions, JsonError};
+
+	#[test]
+	fn can_serialize_without_spans() {
+		let source = b"hello\n\tto: everyone\n\t# greeting\n\t= raw";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..5, {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(Node::Attacher(7..9, 11..19, Vec::new()));
+			attachers
+		}, {
+			let mut content = VecDeque::new();
+			content.push_back(Node::LineComment(22..31));
+			content.push_back(Node::LineOthertongue(35..38));
+			content
+		}));
+
+		let value = to_json(&source[..], &nodes, JsonOptions::default()).unwrap();
+
+		assert_eq!(value, json!([
+			{
+				"kind": "complex",
+				"name": "hello",
+				"attachers": [
+					{ "kind": "attacher", "label": "to", "content": "everyone" }
+				],
+				"content": [
+					{ "kind": "line_comment", "line": " greeting" },
+					{ "kind": "line_othertongue", "line": "raw" }
+				]
+			}
+		]));
+	}
+
+	#[test]
+	fn can_serialize_with_spans() {
+		let source = b"###\nfirst\nsecond\n###";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::BlockComment({
+			let mut lines = Vec::new();
+			lines.push(4..9);
+			lines.push(10..16);
+			lines
+		}));
+
+		let value = to_json(&source[..], &nodes, JsonOptions { spans: true }).unwrap();
+
+		assert_eq!(value, json!([
+			{
+				"kind": "block_comment",
+				"lines": ["first", "second"],
+				"line_spans": [[4, 9], [10, 16]]
+			}
+		]));
+	}
+
+	#[test]
+	fn can_deserialize() {
+		let value = json!([
+			{
+				"kind": "complex",
+				"name": "hello",
+				"content": [
+					{
+						"kind": "simplex",
+						"name": "world",
+						"name_span": [100, 105],
+						"attachers": [{ "kind": "attacher", "label": "to", "content": "all" }]
+					},
+					{ "kind": "block_othertongue", "lines": ["a", "b"] }
+				]
+			}
+		]);
+
+		let (source, nodes) = from_json(&value).unwrap();
+
+		assert_dump(&source, &nodes, "
+			(complex \"hello\"
+				(content
+					(simplex \"world\" (attachers (attacher \"to\" \"all\")))
+					(block_othertongue \"a\" \"b\")))
+		");
+	}
+
+	#[test]
+	fn can_deserialize_serialized_nodes() {
+		let source = b"hello\n\tworld|";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..5, VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(7..12, VecDeque::new()));
+			content
+		}));
+
+		let value = to_json(&source[..], &nodes, JsonOptions { spans: true }).unwrap();
+		let (deserialized_source, deserialized_nodes) = from_json(&value).unwrap();
+
+		assert_eq!(dump(&deserialized_source, &deserialized_nodes), dump(&source[..], &nodes));
+	}
+
+	#[test]
+	fn cannot_deserialize_unknown_kind() {
+		let value = json!([
+			{ "kind": "complex", "name": "a", "content": [{ "kind": "unknown" }] }
+		]);
+
+		let error = from_json(&value).unwrap_err();
+
+		assert_eq!(error, JsonError {
+			path: "/0/content/0".into(),
+			message: "unknown kind of node `unknown`".into()
+		});
+	}
+
+	#[test]
+	fn cannot_serialize_invalid_utf8() {
+		let source = b"a\xFF";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..1, VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(1..2, VecDeque::new()));
+			content
+		}));
+
+		let error = to_json(&source[..], &nodes, JsonOptions::default()).unwrap_err();
+
+		assert_eq!(error.path, "/0/content/0/name");
+	}
+
+	#[test]
+	fn cannot_deserialize_misplaced_nodes() {
+		let attacher_in_content = json!([
+			{
+				"kind": "complex",
+				"name": "a",
+				"content": [{ "kind": "attacher", "label": "b", "content": "c" }]
+			}
+		]);
+		let concept_in_attachers = json!([
+			{ "kind": "simplex", "name": "a", "attachers": [{ "kind": "simplex", "name": "b" }] }
+		]);
+
+		assert_eq!(from_json(&attacher_in_content).unwrap_err(), JsonError {
+			path: "/0/content/0".into(),
+			message: "`attacher` cannot be in content".into()
+		});
+		assert_eq!(from_json(&concept_in_attachers).unwrap_err(), JsonError {
+			path: "/0/attachers/0".into(),
+			message: "`simplex` cannot be in attachers".into()
+		});
+	}
+}
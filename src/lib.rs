@@ -5,6 +5,13 @@
 //!
 //! ## Features available
 //! - `no_std`: Uses the `core` crate instead of `std` crate.
+//! - `json`: Converts parsed nodes to and from JSON.
+//! - `serde`: Maps parsed nodes to and from Rust values.
+//! - `yaml`: Converts parsed nodes to and from YAML.
+//! - `toml`: Converts parsed nodes to and from TOML.
+//! - `xml`: Converts parsed nodes to and from XML.
+//! - `cli`: Builds the `chearmyp` command-line tool.
+//! - `lsp`: Serves documents over the Language Server Protocol with the `chearmyp-lsp` binary.
 
 #[cfg(feature = "no_std")]
 extern crate alloc;
@@ -19,16 +26,24 @@ mod native {
 
 	#[cfg(feature = "no_std")]
 	pub use alloc::{
+		format,
 		vec::Vec,
-		collections::VecDeque
+		boxed::Box,
+		string::String,
+		collections::VecDeque,
+		collections::BTreeMap as Map
 	};
 
 	#[cfg(not(feature = "no_std"))]
 	pub use std::{
+		format,
 		vec::Vec,
 		ops::Range,
+		boxed::Box,
+		string::String,
 		marker::PhantomData,
-		collections::VecDeque
+		collections::VecDeque,
+		collections::HashMap as Map
 	};
 }
 
@@ -78,12 +93,14 @@ mod node_kind {
 	pub use abstract_chearmyp_node::NodeKind;
 }
 
-#[cfg(test)]
 mod token {
 	pub use chearmyp_token::Token;
 }
 
-#[cfg(test)]
+mod lexer {
+	pub use chearmyp_lexer::lex;
+}
+
 mod node {
 	pub use chearmyp_node::Node;
 }
@@ -93,5 +110,183 @@ mod scope_stack;
 /// Contains the parser.
 mod parse;
 
+/// Contains the concrete tree that the parser returns for slices of bytes.
+mod tree;
+
+/// Contains the S-expression dump of parsed trees.
+mod dump;
+
+/// Contains the builder of nodes which own their source.
+mod builder;
+
+/// Contains the writer of nodes back to source.
+mod write;
+
+/// Contains the problems that can be found in sources.
+mod diagnostic;
+
+/// Contains the interpretation of attacher contents as typed values.
+mod scalar;
+
+/// Contains the resolved view of attachers keyed by their labels.
+mod attacher_map;
+
+/// Contains the view of parsed trees whose boundaries resolve to strings.
+mod document;
+
+/// Contains the tree representation that owns its texts.
+mod owned;
+
+/// Contains the schema language and validator of documents.
+mod schema;
+
+/// Contains the lint framework and its built-in rules.
+mod lint;
+
+/// Contains the resolver of include directives.
+mod include;
+
+/// Contains the expansion of references in attacher contents.
+mod interpolate;
+
+/// Contains the expansion of templates.
+mod template;
+
+/// Contains the resolution of references between concepts.
+mod reference;
+
+/// Contains the layered merge of parsed trees.
+mod merge;
+
+/// Contains the handlers of embedded othertongue content.
+mod othertongue;
+
+/// Contains the dedenting of lines of blocks.
+mod block;
+
+/// Contains the association of documentation comments with concepts.
+mod doc_comment;
+
+/// Contains the conversion between byte offsets and line positions.
+mod line_index;
+
+/// Contains the hierarchical outline of concepts.
+mod outline;
+
+/// Contains the classification of tokens for syntax highlighting.
+mod semantic;
+
+/// Contains the enclosing boundaries for expanding selections.
+mod selection;
+
+/// Contains the JSON representation of parsed trees.
+#[cfg(feature = "json")]
+mod json;
+
+/// Contains the error of mapping nodes to and from Rust values.
+#[cfg(feature = "serde")]
+mod serde_error;
+
+/// Contains the deserializer of parsed trees.
+#[cfg(feature = "serde")]
+mod de;
+
+/// Contains the serializer of Rust values to nodes.
+#[cfg(feature = "serde")]
+mod ser;
+
+/// Contains the rules shared by the conversions to and from other data formats.
+#[cfg(any(feature = "yaml", feature = "toml"))]
+mod conversion;
+
+/// Contains the conversion of parsed nodes to and from YAML.
+#[cfg(feature = "yaml")]
+mod yaml_conversion;
+
+/// Contains the conversion of parsed nodes to and from TOML.
+#[cfg(feature = "toml")]
+mod toml_conversion;
+
+/// Contains the XML representation of parsed trees.
+#[cfg(feature = "xml")]
+mod xml;
+
+/// Contains the language server of Chearmyp documents.
+#[cfg(all(feature = "lsp", not(feature = "no_std")))]
+mod lsp;
+
 use scope_stack::ScopeStack;
-pub use parse::parse;
+pub use parse::{parse, parse_source, parse_source_with_extents};
+pub use tree::{RangeNode, RangeNodeQueue, Extents};
+pub use dump::{dump, assert_dump};
+pub use builder::TreeBuilder;
+pub use write::to_source;
+pub use diagnostic::{Diagnostic, Severity};
+pub use scalar::{ScalarValue, ValueMode, ValueError};
+pub use attacher_map::{AttacherMap, AttacherEntry};
+pub use document::{Document, DocumentNode};
+pub use owned::{OwnedNode, OwnedNodeQueue, parse_owned, to_owned_nodes, to_range_nodes};
+pub use schema::Schema;
+pub use lint::{
+	Rule,
+	LintConfig,
+	Linter,
+	Siblings,
+	lint,
+	built_in_rules,
+	for_each_siblings,
+	EmptyComplex,
+	DuplicateConcept,
+	MisplacedAttacher,
+	CommentInAttachers,
+	DeepNesting,
+	UnusedOthertongue
+};
+pub use include::{SourceLoader, Composition, resolve_includes};
+#[cfg(not(feature = "no_std"))]
+pub use include::FileLoader;
+pub use interpolate::{InterpolationOptions, Expansion, interpolate};
+pub use template::{TemplateOptions, TemplateExpansion, Origin, expand_templates};
+pub use reference::{PathIndex, ReferenceConfig, Reference, ReferenceGraph, resolve_references};
+pub use merge::{Layer, MergeStrategy, MergeOptions, Provenance, MergedTree, merge};
+pub use othertongue::{
+	OthertongueHandler,
+	OthertongueRegistry,
+	EmbeddedError,
+	EmbeddedValue,
+	EmbeddedValues
+};
+pub use block::{Newline, BlockOptions, common_indentation, dedent, dedented_lines, join_block};
+pub use doc_comment::{DocComments, attach_doc_comments};
+pub use line_index::{LineIndex, Position};
+pub use outline::{Symbol, SymbolKind, OutlineReader, outline, outline_with, outline_owned};
+pub use semantic::{
+	SEMANTIC_TOKEN_TYPES,
+	SEMANTIC_TOKEN_MODIFIERS,
+	SemanticKind,
+	SemanticModifiers,
+	SemanticToken,
+	SemanticTokensEdit,
+	semantic_tokens,
+	encode_semantic_tokens,
+	diff_semantic_tokens
+};
+pub use selection::selection_ranges;
+#[cfg(feature = "json")]
+pub use json::{to_json, from_json, JsonOptions, JsonError};
+#[cfg(feature = "serde")]
+pub use serde_error::SerdeError;
+#[cfg(feature = "serde")]
+pub use de::{from_slice, from_nodes, Deserializer};
+#[cfg(feature = "serde")]
+pub use ser::{to_nodes, to_vec, to_string};
+#[cfg(any(feature = "yaml", feature = "toml"))]
+pub use conversion::{Conversion, Loss};
+#[cfg(feature = "yaml")]
+pub use yaml_conversion::{to_yaml, from_yaml};
+#[cfg(feature = "toml")]
+pub use toml_conversion::{to_toml, from_toml};
+#[cfg(feature = "xml")]
+pub use xml::{to_xml, from_xml, XmlError};
+#[cfg(all(feature = "lsp", not(feature = "no_std")))]
+pub use lsp::{LanguageServer, read_message, write_message, serve};
@@ -0,0 +1,136 @@
+//! Positions count lines and characters from zero. Characters are counted in UTF-16 code units
+//! since editors that follow the Language Server Protocol expect them by default. Lines are
+//! separated by line feeds, and a carriage return before a line feed is not part of the line.
+
+use crate::native::Vec;
+
+/// Represents a line and a character in the line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+	/// Number of line feeds before the position.
+	pub line: usize,
+	/// Number of UTF-16 code units before the position in the line.
+	pub character: usize
+}
+
+/// Converts byte offsets in a source to positions and back.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{LineIndex, Position};
+/// let source = "a\n\t\u{1F600}b|\n".as_bytes();
+/// let index = LineIndex::new(source);
+///
+/// assert_eq!(index.position(7), Position { line: 1, character: 3 });
+/// assert_eq!(index.offset(Position { line: 1, character: 3 }), 7);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex<'s> {
+	source: &'s [u8],
+	line_starts: Vec<usize>
+}
+
+impl<'s> LineIndex<'s> {
+	/// Creates the index of the lines in the source.
+	pub fn new(source: &'s [u8]) -> Self {
+		let mut line_starts = Vec::new();
+		line_starts.push(0);
+		line_starts.extend(source.iter()
+			.enumerate()
+			.filter(|(_, &byte)| byte == b'\n')
+			.map(|(index, _)| index + 1));
+
+		Self { source, line_starts }
+	}
+
+	/// Returns the number of lines, which is one more than the number of line feeds.
+	pub fn line_count(&self) -> usize {
+		self.line_starts.len()
+	}
+
+	/// Returns the line which contains the offset. Offsets past the source are on the last line.
+	pub fn line_of(&self, offset: usize) -> usize {
+		let offset = offset.min(self.source.len());
+		match self.line_starts.binary_search(&offset) {
+			Ok(line) => line,
+			Err(line) => line - 1
+		}
+	}
+
+	/// Returns the offset where the line starts. Lines past the source start at its end.
+	pub fn line_start(&self, line: usize) -> usize {
+		self.line_starts.get(line).copied().unwrap_or(self.source.len())
+	}
+
+	/// Returns the offset where the line ends, excluding its line ending.
+	pub fn line_end(&self, line: usize) -> usize {
+		match self.line_starts.get(line + 1) {
+			Some(&next_line_start) => {
+				let end = next_line_start - 1;
+				if end > self.line_start(line) && self.source[end - 1] == b'\r' { end - 1 } else { end }
+			},
+			None => self.source.len()
+		}
+	}
+
+	/// Returns the position of the offset. Offsets past the source are at its end.
+	pub fn position(&self, offset: usize) -> Position {
+		let offset = offset.min(self.source.len());
+		let line = self.line_of(offset);
+		let character = utf16_length(&self.source[self.line_starts[line]..offset]);
+		Position { line, character }
+	}
+
+	/// Returns the offset of the position. Characters past the line are clamped to its end.
+	pub fn offset(&self, position: Position) -> usize {
+		if position.line >= self.line_count() {
+			return self.source.len();
+		}
+
+		let start = self.line_start(position.line);
+		let end = self.line_end(position.line);
+		let mut character = 0;
+
+		for (index, &byte) in self.source[start..end].iter().enumerate() {
+			if !is_continuation(byte) {
+				if character >= position.character {
+					return start + index;
+				}
+				character += utf16_width(byte);
+			}
+		}
+
+		end
+	}
+}
+
+fn utf16_length(bytes: &[u8]) -> usize {
+	bytes.iter().filter(|&&byte| !is_continuation(byte)).map(|&byte| utf16_width(byte)).sum()
+}
+
+/// Characters that need four bytes in UTF-8 need a surrogate pair in UTF-16.
+fn utf16_width(leading_byte: u8) -> usize {
+	if leading_byte >= 0xF0 { 2 } else { 1 }
+}
+
+fn is_continuation(byte: u8) -> bool {
+	byte & 0xC0 == 0x80
+}
+
+#[cfg(test)]
+mod t {
+	use super::{LineIndex, Position};
+
+	#[test]
+	fn can_convert_offsets_and_positions() {
+		let source = "ab\r\n\u{E9}c\n".as_bytes();
+		let index = LineIndex::new(source);
+
+		assert_eq!(index.line_count(), 3);
+		assert_eq!(index.line_end(0), 2);
+		assert_eq!(index.position(7), Position { line: 1, character: 2 });
+		assert_eq!(index.position(99), Position { line: 2, character: 0 });
+		assert_eq!(index.offset(Position { line: 0, character: 9 }), 2);
+		assert_eq!(index.offset(Position { line: 1, character: 1 }), 6);
+	}
+}
@@ -0,0 +1,319 @@
+//! Lints check parsed trees for problems that are not syntax errors. The built-in rules are:
+//!
+//! | Code                   | Default severity | Reports                                         |
+//! |------------------------|------------------|-------------------------------------------------|
+//! | `empty_complex`        | warning          | complexes without content                       |
+//! | `duplicate_concept`    | warning          | concepts named the same as an earlier sibling   |
+//! | `misplaced_attacher`   | warning          | attachers after content or without a concept    |
+//! | `comment_in_attachers` | information      | comments among the attachers of a concept       |
+//! | `deep_nesting`         | warning          | concepts nested deeper than a limit             |
+//! | `unused_othertongue`   | warning          | othertongues without a concept or without text  |
+//!
+//! Diagnostics can be suppressed with line comments. `# lint: allow(code, ...)` suppresses the
+//! diagnostics found in the next sibling of the comment and its descendants, while
+//! `# lint: allow_file(code, ...)` suppresses them in the whole source.
+
+mod empty_complex;
+mod duplicate_concept;
+mod misplaced_attacher;
+mod comment_in_attachers;
+mod deep_nesting;
+mod unused_othertongue;
+
+use crate::native::{Box, Range, String, Vec};
+use crate::node::Node;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::tree::{RangeNode, RangeNodeQueue, extent_of, resolve};
+
+pub use empty_complex::EmptyComplex;
+pub use duplicate_concept::DuplicateConcept;
+pub use misplaced_attacher::MisplacedAttacher;
+pub use comment_in_attachers::CommentInAttachers;
+pub use deep_nesting::DeepNesting;
+pub use unused_othertongue::UnusedOthertongue;
+
+/// Represents a check over parsed trees.
+pub trait Rule {
+	/// Returns the code of the diagnostics that the rule reports.
+	fn code(&self) -> &'static str;
+
+	/// Returns the severity of the diagnostics unless it has been configured.
+	fn default_severity(&self) -> Severity {
+		Severity::Warning
+	}
+
+	/// Reports the problems found in the nodes. The severity of the reported diagnostics is
+	/// replaced by the configured severity.
+	fn check(&self, source: &[u8], nodes: &RangeNodeQueue, diagnostics: &mut Vec<Diagnostic>);
+}
+
+/// Contains the severities of the rules that differ from their defaults.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+	severities: Vec<(String, Option<Severity>)>
+}
+
+impl LintConfig {
+	/// Creates a configuration where all rules have their default severities.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the severity of the diagnostics of a rule.
+	pub fn set_severity(&mut self, code: &str, severity: Severity) -> &mut Self {
+		self.severities.push((code.into(), Some(severity)));
+		self
+	}
+
+	/// Turns off a rule.
+	pub fn disable(&mut self, code: &str) -> &mut Self {
+		self.severities.push((code.into(), None));
+		self
+	}
+
+	fn severity_of(&self, rule: &dyn Rule) -> Option<Severity> {
+		self.severities.iter()
+			.rev()
+			.find(|(code, _)| code == rule.code())
+			.map(|(_, severity)| *severity)
+			.unwrap_or_else(|| Some(rule.default_severity()))
+	}
+}
+
+/// Runs rules over parsed trees.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, LintConfig, Linter, Severity};
+/// let source = b"server\n";
+/// let nodes = parse_source(&source[..]);
+/// let mut config = LintConfig::new();
+/// config.set_severity("empty_complex", Severity::Error);
+///
+/// let diagnostics = Linter::new(config).lint(&source[..], &nodes);
+///
+/// assert_eq!(diagnostics[0].code, "empty_complex");
+/// assert_eq!(diagnostics[0].severity, Severity::Error);
+/// ```
+pub struct Linter {
+	rules: Vec<Box<dyn Rule>>,
+	config: LintConfig
+}
+
+impl Linter {
+	/// Creates a linter with the built-in rules.
+	pub fn new(config: LintConfig) -> Self {
+		Self { rules: built_in_rules(), config }
+	}
+
+	/// Creates a linter without any rule.
+	pub fn without_rules(config: LintConfig) -> Self {
+		Self { rules: Vec::new(), config }
+	}
+
+	/// Adds the rule after the rules that the linter already has.
+	pub fn add_rule<T: Rule + 'static>(&mut self, rule: T) {
+		self.rules.push(Box::new(rule));
+	}
+
+	/// Returns the unsuppressed diagnostics of all enabled rules sorted by their positions.
+	pub fn lint(&self, source: &[u8], nodes: &RangeNodeQueue) -> Vec<Diagnostic> {
+		let suppressions = collect_suppressions(source, nodes);
+		let mut diagnostics = Vec::new();
+
+		for rule in &self.rules {
+			let severity = match self.config.severity_of(rule.as_ref()) {
+				Some(severity) => severity,
+				None => continue
+			};
+
+			let mut found_diagnostics = Vec::new();
+			rule.check(source, nodes, &mut found_diagnostics);
+
+			for mut diagnostic in found_diagnostics {
+				let is_suppressed = suppressions.iter().any(|suppression| {
+					suppression.covers(&diagnostic)
+				});
+
+				if !is_suppressed {
+					diagnostic.severity = severity;
+					diagnostics.push(diagnostic);
+				}
+			}
+		}
+
+		diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+		diagnostics
+	}
+}
+
+/// Returns the diagnostics of the built-in rules with their default severities.
+pub fn lint(source: &[u8], nodes: &RangeNodeQueue) -> Vec<Diagnostic> {
+	Linter::new(LintConfig::new()).lint(source, nodes)
+}
+
+/// Returns the built-in rules.
+pub fn built_in_rules() -> Vec<Box<dyn Rule>> {
+	let mut rules: Vec<Box<dyn Rule>> = Vec::new();
+	rules.push(Box::new(EmptyComplex));
+	rules.push(Box::new(DuplicateConcept));
+	rules.push(Box::new(MisplacedAttacher));
+	rules.push(Box::new(CommentInAttachers));
+	rules.push(Box::new(DeepNesting::default()));
+	rules.push(Box::new(UnusedOthertongue));
+	rules
+}
+
+/// Represents a list of sibling nodes.
+#[derive(Debug, Clone, Copy)]
+pub struct Siblings<'n> {
+	pub nodes: &'n RangeNodeQueue,
+	/// Concept that has the nodes. It is absent for the top-level nodes.
+	pub parent: Option<&'n RangeNode>,
+	/// Indicates that the nodes are attachers of the parent rather than its content.
+	pub is_attachers: bool,
+	/// Number of concepts above the nodes.
+	pub depth: usize
+}
+
+/// Calls the visitor for every non-empty list of sibling nodes, from the top level down.
+pub fn for_each_siblings<'n>(nodes: &'n RangeNodeQueue, visitor: &mut dyn FnMut(Siblings<'n>)) {
+	visit_siblings(Siblings { nodes, parent: None, is_attachers: false, depth: 0 }, visitor);
+}
+
+fn visit_siblings<'n>(siblings: Siblings<'n>, visitor: &mut dyn FnMut(Siblings<'n>)) {
+	if siblings.nodes.is_empty() {
+		return;
+	}
+
+	visitor(siblings);
+
+	let depth = siblings.depth + 1;
+	for node in siblings.nodes {
+		let parent = Some(node);
+		let (attachers, content) = match node {
+			Node::Complex(_, attachers, content) => (attachers, Some(content)),
+			Node::Simplex(_, attachers) => (attachers, None),
+			_ => continue
+		};
+
+		visit_siblings(Siblings { nodes: attachers, parent, is_attachers: true, depth }, visitor);
+		if let Some(nodes) = content {
+			visit_siblings(Siblings { nodes, parent, is_attachers: false, depth }, visitor);
+		}
+	}
+}
+
+struct Suppression {
+	codes: Vec<String>,
+	/// Boundary where the diagnostics are suppressed. It is absent for the whole source.
+	span: Option<Range<usize>>
+}
+
+impl Suppression {
+	fn covers(&self, diagnostic: &Diagnostic) -> bool {
+		let is_in_span = match &self.span {
+			Some(span) => span.start <= diagnostic.span.start && diagnostic.span.end <= span.end,
+			None => true
+		};
+		is_in_span && self.codes.iter().any(|code| code == diagnostic.code)
+	}
+}
+
+fn collect_suppressions(source: &[u8], nodes: &RangeNodeQueue) -> Vec<Suppression> {
+	let mut suppressions = Vec::new();
+
+	for_each_siblings(nodes, &mut |siblings| {
+		for (index, node) in siblings.nodes.iter().enumerate() {
+			let (is_file_wide, codes) = match parse_directive(source, node) {
+				Some(directive) => directive,
+				None => continue
+			};
+
+			let span = if is_file_wide {
+				None
+			} else {
+				let next_sibling = siblings.nodes.iter()
+					.skip(index + 1)
+					.find(|sibling| !matches!(sibling, Node::LineComment(_) | Node::BlockComment(_)));
+				match next_sibling {
+					Some(sibling) => Some(extent_of(sibling)),
+					None => continue
+				}
+			};
+
+			suppressions.push(Suppression { codes, span });
+		}
+	});
+
+	suppressions
+}
+
+/// Returns the file-wide flag and the codes of a suppression comment.
+pub(crate) fn parse_directive(source: &[u8], node: &RangeNode) -> Option<(bool, Vec<String>)> {
+	let line = match node {
+		Node::LineComment(line) => core::str::from_utf8(resolve(source, line)).ok()?,
+		_ => return None
+	};
+	let rest = line.trim().strip_prefix("lint:")?.trim_start();
+	let (is_file_wide, rest) = match rest.strip_prefix("allow_file") {
+		Some(rest) => (true, rest),
+		None => (false, rest.strip_prefix("allow")?)
+	};
+	let codes = rest.trim().strip_prefix('(')?.strip_suffix(')')?;
+	let codes = codes.split(',')
+		.map(|code| code.trim())
+		.filter(|code| !code.is_empty())
+		.map(String::from)
+		.collect();
+
+	Some((is_file_wide, codes))
+}
+
+fn text(source: &[u8], boundary: &Range<usize>) -> String {
+	String::from_utf8_lossy(resolve(source, boundary)).into_owned()
+}
+
+#[cfg(test)]
+mod t {
+	use crate::diagnostic::Severity;
+	use crate::parse::parse_source;
+	use crate::tree::resolve;
+	use super::{LintConfig, Linter, lint};
+
+	#[test]
+	fn can_suppress_diagnostics() {
+		let source = b"# lint: allow(empty_complex)\nfirst\nsecond\n";
+		let nodes = parse_source(&source[..]);
+
+		let diagnostics = lint(&source[..], &nodes);
+
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(resolve(&source[..], &diagnostics[0].span), b"second");
+	}
+
+	#[test]
+	fn can_suppress_diagnostics_in_whole_source() {
+		let source = b"first\n# lint: allow_file(empty_complex, deep_nesting)\nsecond\n";
+		let nodes = parse_source(&source[..]);
+
+		let diagnostics = lint(&source[..], &nodes);
+
+		assert!(diagnostics.is_empty());
+	}
+
+	#[test]
+	fn can_configure_severities() {
+		let source = b"first\n";
+		let nodes = parse_source(&source[..]);
+		let mut config = LintConfig::new();
+		config.set_severity("empty_complex", Severity::Hint);
+
+		let hints = Linter::new(config.clone()).lint(&source[..], &nodes);
+		config.disable("empty_complex");
+		let diagnostics = Linter::new(config).lint(&source[..], &nodes);
+
+		assert_eq!(hints[0].severity, Severity::Hint);
+		assert!(diagnostics.is_empty());
+	}
+}
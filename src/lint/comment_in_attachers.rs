@@ -0,0 +1,77 @@
+use crate::native::{format, Vec};
+use crate::node::Node;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::tree::{RangeNodeQueue, span_of};
+use super::{Rule, for_each_siblings, parse_directive, text};
+
+/// Reports comments among attachers since they move together with the attachers instead of staying
+/// near the content. Suppression comments are not reported.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommentInAttachers;
+
+impl Rule for CommentInAttachers {
+	fn code(&self) -> &'static str {
+		"comment_in_attachers"
+	}
+
+	fn default_severity(&self) -> Severity {
+		Severity::Information
+	}
+
+	fn check(&self, source: &[u8], nodes: &RangeNodeQueue, diagnostics: &mut Vec<Diagnostic>) {
+		for_each_siblings(nodes, &mut |siblings| {
+			let concept = match siblings.parent {
+				Some(Node::Complex(concept, _, _) | Node::Simplex(concept, _)) => concept,
+				_ => return
+			};
+
+			if !siblings.is_attachers {
+				return;
+			}
+
+			for node in siblings.nodes {
+				let is_comment = matches!(node, Node::LineComment(_) | Node::BlockComment(_));
+				if is_comment && parse_directive(source, node).is_none() {
+					let message = format!(
+						"comment is placed among the attachers of `{}`",
+						text(source, concept)
+					);
+					diagnostics.push(Diagnostic {
+						severity: self.default_severity(),
+						span: span_of(node),
+						code: self.code(),
+						message
+					});
+				}
+			}
+		});
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use crate::diagnostic::Severity;
+	use super::{CommentInAttachers, Rule};
+
+	#[test]
+	fn can_report_comments_among_attachers() {
+		let source = b"hellonameworld note lint: allow(x)";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Simplex(0..5, {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(Node::Attacher(5..9, 9..14, Vec::new()));
+			attachers.push_back(Node::LineComment(14..19));
+			attachers.push_back(Node::LineComment(19..34));
+			attachers
+		}));
+		let mut diagnostics = Vec::new();
+
+		CommentInAttachers.check(&source[..], &nodes, &mut diagnostics);
+
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].span, 14..19);
+		assert_eq!(diagnostics[0].severity, Severity::Information);
+	}
+}
@@ -0,0 +1,82 @@
+use crate::native::{format, Vec};
+use crate::node::Node;
+use crate::diagnostic::Diagnostic;
+use crate::tree::RangeNodeQueue;
+use super::{Rule, text};
+
+/// Reports concepts that have more concepts above them than the limit. Only the outermost concept
+/// that exceeds the limit is reported in each branch.
+#[derive(Debug, Clone, Copy)]
+pub struct DeepNesting {
+	pub max_depth: usize
+}
+
+impl Default for DeepNesting {
+	fn default() -> Self {
+		Self { max_depth: 8 }
+	}
+}
+
+impl DeepNesting {
+	fn check_nodes(
+		&self,
+		source: &[u8],
+		nodes: &RangeNodeQueue,
+		depth: usize,
+		diagnostics: &mut Vec<Diagnostic>
+	) {
+		for node in nodes {
+			let (concept, content) = match node {
+				Node::Complex(concept, _, content) => (concept, Some(content)),
+				Node::Simplex(concept, _) => (concept, None),
+				_ => continue
+			};
+
+			if depth > self.max_depth {
+				let message = format!(
+					"concept `{}` is nested {} levels deep which is more than {}",
+					text(source, concept),
+					depth,
+					self.max_depth
+				);
+				diagnostics.push(Diagnostic::warning(concept.clone(), self.code(), message));
+			} else if let Some(content) = content {
+				self.check_nodes(source, content, depth + 1, diagnostics);
+			}
+		}
+	}
+}
+
+impl Rule for DeepNesting {
+	fn code(&self) -> &'static str {
+		"deep_nesting"
+	}
+
+	fn check(&self, source: &[u8], nodes: &RangeNodeQueue, diagnostics: &mut Vec<Diagnostic>) {
+		self.check_nodes(source, nodes, 0, diagnostics);
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::Vec;
+	use crate::parse::parse_source;
+	use crate::tree::resolve;
+	use super::{DeepNesting, Rule};
+
+	#[test]
+	fn can_report_outermost_deep_concept() {
+		let source = b"a\n\tb\n\t\tc\n\t\t\td|\n";
+		let nodes = parse_source(&source[..]);
+		let mut diagnostics = Vec::new();
+
+		DeepNesting { max_depth: 1 }.check(&source[..], &nodes, &mut diagnostics);
+
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(resolve(&source[..], &diagnostics[0].span), b"c");
+		assert_eq!(
+			diagnostics[0].message,
+			"concept `c` is nested 2 levels deep which is more than 1"
+		);
+	}
+}
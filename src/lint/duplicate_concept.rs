@@ -0,0 +1,69 @@
+use crate::native::{format, Range, Vec};
+use crate::node::Node;
+use crate::diagnostic::Diagnostic;
+use crate::tree::{RangeNodeQueue, resolve};
+use super::{Rule, for_each_siblings, text};
+
+/// Reports concepts whose names are the same as an earlier concept in the same content.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DuplicateConcept;
+
+impl Rule for DuplicateConcept {
+	fn code(&self) -> &'static str {
+		"duplicate_concept"
+	}
+
+	fn check(&self, source: &[u8], nodes: &RangeNodeQueue, diagnostics: &mut Vec<Diagnostic>) {
+		for_each_siblings(nodes, &mut |siblings| {
+			if siblings.is_attachers {
+				return;
+			}
+
+			let mut earlier_concepts: Vec<&Range<usize>> = Vec::new();
+
+			for node in siblings.nodes {
+				let concept = match node {
+					Node::Complex(concept, _, _) | Node::Simplex(concept, _) => concept,
+					_ => continue
+				};
+				let name = resolve(source, concept);
+				let earlier_concept = earlier_concepts.iter()
+					.find(|earlier_concept| resolve(source, earlier_concept) == name);
+
+				match earlier_concept {
+					Some(earlier_concept) => {
+						let message = format!(
+							"concept `{}` is repeated; it first appears at bytes {}..{}",
+							text(source, concept),
+							earlier_concept.start,
+							earlier_concept.end
+						);
+						diagnostics.push(Diagnostic::warning(concept.clone(), self.code(), message));
+					},
+					None => earlier_concepts.push(concept)
+				}
+			}
+		});
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::Vec;
+	use crate::parse::parse_source;
+	use crate::tree::resolve;
+	use super::{DuplicateConcept, Rule};
+
+	#[test]
+	fn can_report_repeated_siblings() {
+		let source = b"a|\nb|\na\n\tx|\n\tx|\n";
+		let nodes = parse_source(&source[..]);
+		let mut diagnostics = Vec::new();
+
+		DuplicateConcept.check(&source[..], &nodes, &mut diagnostics);
+
+		let spans = diagnostics.iter().map(|diagnostic| diagnostic.span.clone()).collect::<Vec<_>>();
+		assert_eq!(spans, [6..7, 13..14]);
+		assert_eq!(resolve(&source[..], &spans[1]), b"x");
+	}
+}
@@ -0,0 +1,51 @@
+use crate::native::{format, Vec};
+use crate::node::Node;
+use crate::diagnostic::Diagnostic;
+use crate::tree::RangeNodeQueue;
+use super::{Rule, for_each_siblings, text};
+
+/// Reports complexes without content since they can be written as simplexes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EmptyComplex;
+
+impl Rule for EmptyComplex {
+	fn code(&self) -> &'static str {
+		"empty_complex"
+	}
+
+	fn check(&self, source: &[u8], nodes: &RangeNodeQueue, diagnostics: &mut Vec<Diagnostic>) {
+		for_each_siblings(nodes, &mut |siblings| {
+			for node in siblings.nodes {
+				if let Node::Complex(concept, _, content) = node {
+					if content.is_empty() {
+						let message = format!(
+							"complex `{}` has no content so it may be written as a simplex",
+							text(source, concept)
+						);
+						diagnostics.push(Diagnostic::warning(concept.clone(), self.code(), message));
+					}
+				}
+			}
+		});
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::Vec;
+	use crate::parse::parse_source;
+	use crate::tree::resolve;
+	use super::{EmptyComplex, Rule};
+
+	#[test]
+	fn can_report_empty_complexes() {
+		let source = b"a\nb|\nc\n\td|\n";
+		let nodes = parse_source(&source[..]);
+		let mut diagnostics = Vec::new();
+
+		EmptyComplex.check(&source[..], &nodes, &mut diagnostics);
+
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(resolve(&source[..], &diagnostics[0].span), b"a");
+	}
+}
@@ -0,0 +1,72 @@
+use crate::native::{format, Vec};
+use crate::node::Node;
+use crate::diagnostic::Diagnostic;
+use crate::tree::RangeNodeQueue;
+use super::{Rule, for_each_siblings, text};
+
+/// Reports attachers that are among content, which happens when they are placed after the content
+/// of a concept or when there is no concept above them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MisplacedAttacher;
+
+impl Rule for MisplacedAttacher {
+	fn code(&self) -> &'static str {
+		"misplaced_attacher"
+	}
+
+	fn check(&self, source: &[u8], nodes: &RangeNodeQueue, diagnostics: &mut Vec<Diagnostic>) {
+		for_each_siblings(nodes, &mut |siblings| {
+			if siblings.is_attachers {
+				return;
+			}
+
+			for node in siblings.nodes {
+				if let Node::Attacher(label, content, _) = node {
+					let label_text = text(source, label);
+					let message = match siblings.parent {
+						Some(Node::Complex(concept, _, _)) => format!(
+							"attacher `{}` is placed after the content of `{}`",
+							label_text,
+							text(source, concept)
+						),
+						_ => format!("attacher `{}` does not belong to any concept", label_text)
+					};
+					let span = label.start..content.end;
+					diagnostics.push(Diagnostic::warning(span, self.code(), message));
+				}
+			}
+		});
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use super::{MisplacedAttacher, Rule};
+
+	#[test]
+	fn can_report_attachers_among_content() {
+		let source = b"serverchildport80";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..6, VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(6..11, VecDeque::new()));
+			content.push_back(Node::Attacher(11..15, 15..17, Vec::new()));
+			content
+		}));
+		nodes.push_back(Node::Attacher(11..15, 15..17, Vec::new()));
+		let mut diagnostics = Vec::new();
+
+		MisplacedAttacher.check(&source[..], &nodes, &mut diagnostics);
+
+		let messages = diagnostics.iter()
+			.map(|diagnostic| diagnostic.message.as_str())
+			.collect::<Vec<_>>();
+		assert_eq!(messages, [
+			"attacher `port` does not belong to any concept",
+			"attacher `port` is placed after the content of `server`"
+		]);
+		assert_eq!(diagnostics[0].span, 11..17);
+	}
+}
@@ -0,0 +1,73 @@
+use crate::native::Vec;
+use crate::node::Node;
+use crate::diagnostic::Diagnostic;
+use crate::tree::{RangeNodeQueue, resolve, span_of};
+use super::{Rule, for_each_siblings};
+
+/// Reports othertongues that no concept can use, either because there is no concept above them or
+/// because they have no text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnusedOthertongue;
+
+impl Rule for UnusedOthertongue {
+	fn code(&self) -> &'static str {
+		"unused_othertongue"
+	}
+
+	fn check(&self, source: &[u8], nodes: &RangeNodeQueue, diagnostics: &mut Vec<Diagnostic>) {
+		for_each_siblings(nodes, &mut |siblings| {
+			for node in siblings.nodes {
+				let lines = match node {
+					Node::LineOthertongue(line) => core::slice::from_ref(line),
+					Node::BlockOthertongue(lines) => lines.as_slice(),
+					_ => continue
+				};
+
+				let message = if siblings.parent.is_none() {
+					"othertongue does not belong to any concept"
+				} else if lines.iter().all(|line| resolve(source, line).trim_ascii().is_empty()) {
+					"othertongue has no text"
+				} else {
+					continue;
+				};
+
+				diagnostics.push(Diagnostic::warning(span_of(node), self.code(), message.into()));
+			}
+		});
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use super::{UnusedOthertongue, Rule};
+
+	#[test]
+	fn can_report_unused_othertongues() {
+		let source = b"free textsnippet\t\t code";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::LineOthertongue(0..9));
+		nodes.push_back(Node::Complex(9..16, VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(Node::BlockOthertongue({
+				let mut lines = Vec::new();
+				lines.push(16..19);
+				lines
+			}));
+			content.push_back(Node::LineOthertongue(19..23));
+			content
+		}));
+		let mut diagnostics = Vec::new();
+
+		UnusedOthertongue.check(&source[..], &nodes, &mut diagnostics);
+
+		let messages = diagnostics.iter()
+			.map(|diagnostic| diagnostic.message.as_str())
+			.collect::<Vec<_>>();
+		assert_eq!(messages, [
+			"othertongue does not belong to any concept",
+			"othertongue has no text"
+		]);
+	}
+}
@@ -0,0 +1,396 @@
+//! The language server exchanges JSON-RPC messages framed by `Content-Length` headers as the
+//! Language Server Protocol specifies. Documents are synchronized in full on every change and are
+//! parsed again each time.
+//!
+//! | Method                            | Provides                                               |
+//! |-----------------------------------|--------------------------------------------------------|
+//! | `textDocument/publishDiagnostics` | lint diagnostics whenever a document opens or changes  |
+//! | `textDocument/documentSymbol`     | the outline of the concepts and their attachers        |
+//! | `textDocument/foldingRange`       | complexes, block comments, and block othertongues      |
+//! | `textDocument/formatting`         | the written source of the whole document               |
+//! | `textDocument/rangeFormatting`    | the written source of the top-level nodes in the range |
+//! | `textDocument/hover`              | the attachers of the concept under the cursor          |
+//! | `textDocument/semanticTokens`     | the classified tokens in full or as edits              |
+//! | `textDocument/selectionRange`     | the enclosing boundaries of each position              |
+
+mod transport;
+mod symbols;
+mod folding;
+mod formatting;
+mod hover;
+mod semantic_tokens;
+mod selection_ranges;
+
+use serde_json::{json, Value};
+use crate::native::{format, Map, Range, String, Vec};
+use crate::node::Node;
+use crate::diagnostic::Severity;
+use crate::line_index::{LineIndex, Position};
+use crate::lint::lint;
+use crate::parse::parse_source_with_extents;
+use crate::semantic::{SEMANTIC_TOKEN_MODIFIERS, SEMANTIC_TOKEN_TYPES};
+use crate::tree::{Extents, RangeNode, RangeNodeQueue, extent_of, span_of};
+
+pub use transport::{read_message, write_message, serve};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Represents the error code and message of a failed request.
+type RequestError = (i64, String);
+
+struct OpenDocument {
+	source: Vec<u8>,
+	nodes: RangeNodeQueue,
+	/// Extents of the concepts that the parser recorded along with the nodes.
+	extents: Extents,
+	/// Result ID and data of the last semantic tokens sent for the document.
+	semantic_tokens: Option<(String, Vec<u32>)>
+}
+
+/// Contains the open documents and the lifecycle of a language server.
+///
+/// ## Examples
+/// ```
+/// use serde_json::json;
+/// use chearmyp_parser::LanguageServer;
+/// let mut server = LanguageServer::new();
+///
+/// let replies = server.handle(&json!({
+/// 	"jsonrpc": "2.0",
+/// 	"method": "textDocument/didOpen",
+/// 	"params": {
+/// 		"textDocument": { "uri": "file:///a.chr", "text": "a\n" }
+/// 	}
+/// }));
+///
+/// assert_eq!(replies[0]["params"]["diagnostics"][0]["code"], "empty_complex");
+/// ```
+#[derive(Default)]
+pub struct LanguageServer {
+	documents: Map<String, OpenDocument>,
+	is_shut_down: bool,
+	has_exited: bool,
+	last_result_id: u64
+}
+
+impl LanguageServer {
+	/// Creates a server without open documents.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Checks if the client has requested a shutdown.
+	pub fn is_shut_down(&self) -> bool {
+		self.is_shut_down
+	}
+
+	/// Checks if the client has sent the exit notification.
+	pub fn has_exited(&self) -> bool {
+		self.has_exited
+	}
+
+	/// Handles a request or notification and returns the messages to send to the client.
+	///
+	/// Responses from the client are ignored since the server does not send requests.
+	pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+		let method = match message["method"].as_str() {
+			Some(method) => method,
+			None => return Vec::new()
+		};
+		let params = &message["params"];
+
+		match message.get("id") {
+			Some(id) => {
+				let response = match self.respond(method, params) {
+					Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+					Err((code, message)) => json!({
+						"jsonrpc": "2.0",
+						"id": id,
+						"error": { "code": code, "message": message }
+					})
+				};
+				let mut replies = Vec::new();
+				replies.push(response);
+				replies
+			},
+			None => self.notify(method, params)
+		}
+	}
+
+	fn respond(&mut self, method: &str, params: &Value) -> Result<Value, RequestError> {
+		if self.is_shut_down {
+			return Err((INVALID_REQUEST, "the server has been shut down".into()));
+		}
+
+		match method {
+			"initialize" => Ok(json!({
+				"capabilities": {
+					"textDocumentSync": { "openClose": true, "change": 1 },
+					"documentSymbolProvider": true,
+					"foldingRangeProvider": true,
+					"documentFormattingProvider": true,
+					"documentRangeFormattingProvider": true,
+					"hoverProvider": true,
+					"selectionRangeProvider": true,
+					"semanticTokensProvider": {
+						"legend": {
+							"tokenTypes": SEMANTIC_TOKEN_TYPES,
+							"tokenModifiers": SEMANTIC_TOKEN_MODIFIERS
+						},
+						"full": { "delta": true }
+					}
+				},
+				"serverInfo": { "name": "chearmyp-lsp", "version": env!("CARGO_PKG_VERSION") }
+			})),
+			"shutdown" => {
+				self.is_shut_down = true;
+				Ok(Value::Null)
+			},
+			"textDocument/documentSymbol" => {
+				let document = self.document(params)?;
+				Ok(symbols::document_symbols(&document.source, &document.nodes))
+			},
+			"textDocument/foldingRange" => {
+				let document = self.document(params)?;
+				Ok(folding::folding_ranges(&document.source, &document.nodes))
+			},
+			"textDocument/formatting" => {
+				let document = self.document(params)?;
+				Ok(formatting::format_document(&document.source, &document.nodes))
+			},
+			"textDocument/rangeFormatting" => {
+				let document = self.document(params)?;
+				let index = LineIndex::new(&document.source);
+				let range = to_offset(&index, &params["range"]["start"])?
+					..to_offset(&index, &params["range"]["end"])?;
+				Ok(formatting::format_range(&document.source, &document.nodes, range))
+			},
+			"textDocument/hover" => {
+				let document = self.document(params)?;
+				let offset = to_offset(&LineIndex::new(&document.source), &params["position"])?;
+				Ok(hover::hover(&document.source, &document.nodes, offset))
+			},
+			"textDocument/selectionRange" => {
+				let document = self.document(params)?;
+				let index = LineIndex::new(&document.source);
+				let positions = params["positions"].as_array()
+					.ok_or((INVALID_PARAMS, String::from("expected `positions`")))?;
+				let offsets = positions.iter()
+					.map(|position| to_offset(&index, position))
+					.collect::<Result<Vec<_>, _>>()?;
+				Ok(selection_ranges::selection_ranges(
+					&document.source,
+					&document.nodes,
+					&document.extents,
+					&offsets
+				))
+			},
+			"textDocument/semanticTokens/full" => {
+				let result_id = self.next_result_id();
+				Ok(semantic_tokens::full(self.document_mut(params)?, result_id))
+			},
+			"textDocument/semanticTokens/full/delta" => {
+				let result_id = self.next_result_id();
+				let previous_result_id = params["previousResultId"].as_str().unwrap_or("");
+				let document = self.document_mut(params)?;
+				Ok(semantic_tokens::delta(document, previous_result_id, result_id))
+			},
+			_ => Err((METHOD_NOT_FOUND, format!("unknown method `{}`", method)))
+		}
+	}
+
+	fn notify(&mut self, method: &str, params: &Value) -> Vec<Value> {
+		let uri = params["textDocument"]["uri"].as_str();
+		let mut replies = Vec::new();
+
+		match (method, uri) {
+			("exit", _) => self.has_exited = true,
+			("textDocument/didOpen", Some(uri)) => {
+				if let Some(text) = params["textDocument"]["text"].as_str() {
+					replies.push(self.open(uri, text));
+				}
+			},
+			("textDocument/didChange", Some(uri)) => {
+				let text = params["contentChanges"].as_array()
+					.and_then(|changes| changes.last())
+					.and_then(|change| change["text"].as_str());
+				if let Some(text) = text {
+					replies.push(self.open(uri, text));
+				}
+			},
+			("textDocument/didClose", Some(uri)) => {
+				self.documents.remove(uri);
+				replies.push(publish_diagnostics(uri, Vec::new()));
+			},
+			_ => {}
+		}
+
+		replies
+	}
+
+	/// Stores the parsed text of the document and returns the notification of its diagnostics.
+	fn open(&mut self, uri: &str, text: &str) -> Value {
+		let source = Vec::from(text.as_bytes());
+		let (nodes, extents) = parse_source_with_extents(&source);
+		let index = LineIndex::new(&source);
+		let diagnostics = lint(&source, &nodes).into_iter()
+			.map(|diagnostic| json!({
+				"range": to_lsp_range(&index, &diagnostic.span),
+				"severity": match diagnostic.severity {
+					Severity::Error => 1,
+					Severity::Warning => 2,
+					Severity::Information => 3,
+					Severity::Hint => 4
+				},
+				"code": diagnostic.code,
+				"source": "chearmyp",
+				"message": diagnostic.message
+			}))
+			.collect();
+		let notification = publish_diagnostics(uri, diagnostics);
+
+		self.documents.insert(uri.into(), OpenDocument {
+			source,
+			nodes,
+			extents,
+			semantic_tokens: None
+		});
+		notification
+	}
+
+	fn document(&self, params: &Value) -> Result<&OpenDocument, RequestError> {
+		let uri = params["textDocument"]["uri"].as_str()
+			.ok_or((INVALID_PARAMS, String::from("expected `textDocument.uri`")))?;
+		self.documents.get(uri).ok_or((INVALID_PARAMS, format!("`{}` has not been opened", uri)))
+	}
+
+	fn document_mut(&mut self, params: &Value) -> Result<&mut OpenDocument, RequestError> {
+		let uri = params["textDocument"]["uri"].as_str()
+			.ok_or((INVALID_PARAMS, String::from("expected `textDocument.uri`")))?;
+		self.documents.get_mut(uri)
+			.ok_or((INVALID_PARAMS, format!("`{}` has not been opened", uri)))
+	}
+
+	fn next_result_id(&mut self) -> String {
+		self.last_result_id += 1;
+		format!("{}", self.last_result_id)
+	}
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+	json!({
+		"jsonrpc": "2.0",
+		"method": "textDocument/publishDiagnostics",
+		"params": { "uri": uri, "diagnostics": diagnostics }
+	})
+}
+
+fn to_offset(index: &LineIndex, position: &Value) -> Result<usize, RequestError> {
+	let line = position["line"].as_u64();
+	let character = position["character"].as_u64();
+
+	match (line, character) {
+		(Some(line), Some(character)) => Ok(index.offset(Position {
+			line: line as usize,
+			character: character as usize
+		})),
+		_ => Err((INVALID_PARAMS, "expected a position with `line` and `character`".into()))
+	}
+}
+
+fn to_lsp_range(index: &LineIndex, span: &Range<usize>) -> Value {
+	let start = index.position(span.start);
+	let end = index.position(span.end);
+	json!({
+		"start": { "line": start.line, "character": start.character },
+		"end": { "line": end.line, "character": end.character }
+	})
+}
+
+/// Returns the line where the node starts. Blocks start at their opening fence.
+fn first_line(index: &LineIndex, node: &RangeNode) -> usize {
+	match node {
+		Node::BlockComment(_) | Node::BlockOthertongue(_) => {
+			index.line_of(span_of(node).start).saturating_sub(1)
+		},
+		_ => index.line_of(span_of(node).start)
+	}
+}
+
+/// Returns the line where the node and its descendants end. Blocks end at their closing fence.
+fn last_line(index: &LineIndex, node: &RangeNode) -> usize {
+	let line = index.line_of(extent_of(node).end);
+
+	match last_descendant(node) {
+		Node::BlockComment(_) | Node::BlockOthertongue(_) => line + 1,
+		_ => line
+	}
+}
+
+/// Returns the node that is written last among the node and its descendants.
+fn last_descendant(node: &RangeNode) -> &RangeNode {
+	let last_child = match node {
+		Node::Complex(_, attachers, content) => content.back().or_else(|| attachers.back()),
+		Node::Simplex(_, attachers) => attachers.back(),
+		_ => None
+	};
+	last_child.map_or(node, last_descendant)
+}
+
+#[cfg(test)]
+mod t {
+	use std::io::Cursor;
+	use serde_json::{json, Value};
+	use crate::native::Vec;
+	use super::{read_message, serve, write_message};
+
+	#[test]
+	fn can_serve_scripted_session() {
+		let mut input = Vec::new();
+		let uri = "file:///server.chr";
+		let script = [
+			json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+			json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+			json!({
+				"jsonrpc": "2.0",
+				"method": "textDocument/didOpen",
+				"params": { "textDocument": { "uri": uri, "text": "server\n\tport: 80\n\troute|\n" } }
+			}),
+			json!({
+				"jsonrpc": "2.0",
+				"id": 2,
+				"method": "textDocument/hover",
+				"params": {
+					"textDocument": { "uri": uri },
+					"position": { "line": 0, "character": 2 }
+				}
+			}),
+			json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }),
+			json!({ "jsonrpc": "2.0", "method": "exit" })
+		];
+		for message in &script {
+			write_message(&mut input, message).unwrap();
+		}
+		let mut output = Vec::new();
+
+		let is_shut_down = serve(Cursor::new(input), &mut output).unwrap();
+
+		let mut output = Cursor::new(output);
+		let mut replies = Vec::new();
+		while let Some(body) = read_message(&mut output).unwrap() {
+			replies.push(serde_json::from_slice::<Value>(&body).unwrap());
+		}
+		assert!(is_shut_down);
+		assert_eq!(replies.len(), 4);
+		assert_eq!(replies[0]["result"]["capabilities"]["hoverProvider"], true);
+		assert_eq!(replies[1]["params"]["diagnostics"], json!([]));
+		assert_eq!(
+			replies[2]["result"]["contents"]["value"],
+			"**server** (complex)\n\n- `port`: 80"
+		);
+		assert_eq!(replies[3]["id"], 3);
+	}
+}
@@ -0,0 +1,56 @@
+use serde_json::{json, Value};
+use crate::native::Vec;
+use crate::node::Node;
+use crate::line_index::LineIndex;
+use crate::tree::RangeNodeQueue;
+use super::{first_line, last_line};
+
+/// Returns the folding ranges of the complexes and blocks that take more than one line.
+pub fn folding_ranges(source: &[u8], nodes: &RangeNodeQueue) -> Value {
+	let mut ranges = Vec::new();
+	collect(&LineIndex::new(source), nodes, &mut ranges);
+	Value::Array(ranges)
+}
+
+fn collect(index: &LineIndex, nodes: &RangeNodeQueue, ranges: &mut Vec<Value>) {
+	for node in nodes {
+		let kind = match node {
+			Node::Complex(_, _, _) => "region",
+			Node::BlockComment(lines) if !lines.is_empty() => "comment",
+			Node::BlockOthertongue(lines) if !lines.is_empty() => "region",
+			_ => continue
+		};
+		let start_line = first_line(index, node);
+		let end_line = last_line(index, node);
+
+		if end_line > start_line {
+			ranges.push(json!({ "startLine": start_line, "endLine": end_line, "kind": kind }));
+		}
+
+		if let Node::Complex(_, attachers, content) = node {
+			collect(index, attachers, ranges);
+			collect(index, content, ranges);
+		}
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use serde_json::json;
+	use crate::parse::parse_source;
+	use super::folding_ranges;
+
+	#[test]
+	fn can_fold_complexes_and_blocks() {
+		let source = b"a\n\tb\n\t\t###\n\t\tnote\n\t\t###\nc|\n";
+		let nodes = parse_source(&source[..]);
+
+		let ranges = folding_ranges(&source[..], &nodes);
+
+		assert_eq!(ranges, json!([
+			{ "startLine": 0, "endLine": 4, "kind": "region" },
+			{ "startLine": 1, "endLine": 4, "kind": "region" },
+			{ "startLine": 2, "endLine": 4, "kind": "comment" }
+		]));
+	}
+}
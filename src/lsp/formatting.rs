@@ -0,0 +1,80 @@
+use serde_json::{json, Value};
+use crate::native::{Range, String, Vec};
+use crate::line_index::LineIndex;
+use crate::tree::{RangeNodeQueue, resolve};
+use crate::write::to_source;
+use super::{first_line, to_lsp_range};
+
+/// Returns the edit that replaces the document with its written source.
+pub fn format_document(source: &[u8], nodes: &RangeNodeQueue) -> Value {
+	let index = LineIndex::new(source);
+	edits(source, &index, 0..source.len(), to_source(source, nodes))
+}
+
+/// Returns the edit that rewrites the top-level nodes which intersect the range.
+///
+/// Each top-level node owns the lines from where it starts until the next top-level node starts so
+/// blank lines after the rewritten nodes are removed as in the whole document.
+pub fn format_range(source: &[u8], nodes: &RangeNodeQueue, range: Range<usize>) -> Value {
+	let index = LineIndex::new(source);
+	let region_starts = nodes.iter()
+		.enumerate()
+		.map(|(position, node)| {
+			if position == 0 { 0 } else { index.line_start(first_line(&index, node)) }
+		})
+		.collect::<Vec<_>>();
+	let region_end = |position: usize| {
+		region_starts.get(position + 1).copied().unwrap_or(source.len())
+	};
+	let range_end = range.end.max(range.start + 1);
+	let mut selected_positions = (0..nodes.len()).filter(|&position| {
+		region_starts[position] < range_end && range.start < region_end(position)
+	});
+
+	match (selected_positions.next(), selected_positions.last()) {
+		(Some(first), last) => {
+			let last = last.unwrap_or(first);
+			let selected_nodes = nodes.range(first..=last).cloned().collect::<RangeNodeQueue>();
+			let region = region_starts[first]..region_end(last);
+			edits(source, &index, region, to_source(source, &selected_nodes))
+		},
+		(None, _) => Value::Array(Vec::new())
+	}
+}
+
+fn edits(source: &[u8], index: &LineIndex, region: Range<usize>, written_source: Vec<u8>) -> Value {
+	if resolve(source, &region) == written_source {
+		return Value::Array(Vec::new());
+	}
+
+	json!([{
+		"range": to_lsp_range(index, &region),
+		"newText": String::from_utf8_lossy(&written_source)
+	}])
+}
+
+#[cfg(test)]
+mod t {
+	use serde_json::json;
+	use crate::parse::parse_source;
+	use super::{format_document, format_range};
+
+	#[test]
+	fn can_format_document_and_range() {
+		let source = b"a|\n\n\nb|\n\n\nc|\n";
+		let nodes = parse_source(&source[..]);
+
+		let document_edits = format_document(&source[..], &nodes);
+		let range_edits = format_range(&source[..], &nodes, 5..5);
+
+		assert_eq!(document_edits[0]["newText"], "a|\nb|\nc|\n");
+		assert_eq!(range_edits, json!([{
+			"range": {
+				"start": { "line": 3, "character": 0 },
+				"end": { "line": 6, "character": 0 }
+			},
+			"newText": "b|\n"
+		}]));
+		assert_eq!(format_document(b"a|\n", &parse_source(b"a|\n")), json!([]));
+	}
+}
@@ -0,0 +1,91 @@
+use serde_json::{json, Value};
+use crate::native::{format, String};
+use crate::node::Node;
+use crate::attacher_map::AttacherMap;
+use crate::line_index::LineIndex;
+use crate::tree::{RangeNode, RangeNodeQueue, resolve};
+use super::to_lsp_range;
+
+/// Returns the attachers of the concept whose name contains the offset, or null if there is none.
+pub fn hover(source: &[u8], nodes: &RangeNodeQueue, offset: usize) -> Value {
+	let (concept, attachers, kind) = match find_concept(nodes, offset) {
+		Some(Node::Complex(concept, attachers, _)) => (concept, attachers, "complex"),
+		Some(Node::Simplex(concept, attachers)) => (concept, attachers, "simplex"),
+		_ => return Value::Null
+	};
+	let attachers = AttacherMap::new(source, attachers);
+	let name = String::from_utf8_lossy(resolve(source, concept));
+	let mut contents = format!("**{}** ({})", escape(&name), kind);
+
+	if !attachers.is_empty() {
+		contents.push('\n');
+	}
+
+	for entry in attachers.iter() {
+		contents.push_str(&format!(
+			"\n- {}: {}",
+			code_span(&String::from_utf8_lossy(entry.label)),
+			escape(&String::from_utf8_lossy(entry.content))
+		));
+	}
+
+	json!({
+		"contents": { "kind": "markdown", "value": contents },
+		"range": to_lsp_range(&LineIndex::new(source), concept)
+	})
+}
+
+fn find_concept(nodes: &RangeNodeQueue, offset: usize) -> Option<&RangeNode> {
+	for node in nodes {
+		match node {
+			Node::Complex(concept, _, content) => {
+				if concept.contains(&offset) || concept.end == offset {
+					return Some(node);
+				}
+				if let Some(found_node) = find_concept(content, offset) {
+					return Some(found_node);
+				}
+			},
+			Node::Simplex(concept, _) if concept.contains(&offset) || concept.end == offset => {
+				return Some(node);
+			},
+			_ => {}
+		}
+	}
+
+	None
+}
+
+/// Returns the text with backslashes before the characters that markdown may treat as markup.
+fn escape(text: &str) -> String {
+	let mut escaped_text = String::with_capacity(text.len());
+
+	for character in text.chars() {
+		if character.is_ascii_punctuation() {
+			escaped_text.push('\\');
+		}
+		escaped_text.push(character);
+	}
+
+	escaped_text
+}
+
+/// Returns the text as a code span whose fence is longer than the backticks in the text.
+fn code_span(text: &str) -> String {
+	let backtick_count = text.split(|character| character != '`').map(str::len).max().unwrap_or(0);
+	let fence = "`".repeat(backtick_count + 1);
+	let padding = if text.starts_with('`') || text.ends_with('`') { " " } else { "" };
+	format!("{}{}{}{}{}", fence, padding, text, padding, fence)
+}
+
+#[cfg(test)]
+mod t {
+	use super::{code_span, escape};
+
+	#[test]
+	fn can_escape_markdown() {
+		assert_eq!(escape("*a* [b](c)"), "\\*a\\* \\[b\\]\\(c\\)");
+		assert_eq!(code_span("port"), "`port`");
+		assert_eq!(code_span("`a``b"), "``` `a``b ```");
+	}
+}
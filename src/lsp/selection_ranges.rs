@@ -0,0 +1,53 @@
+use serde_json::{json, Value};
+use crate::line_index::LineIndex;
+use crate::selection;
+use crate::tree::{Extents, RangeNodeQueue};
+use super::to_lsp_range;
+
+/// Returns the selection range of each offset with its enclosing ranges as parents.
+pub fn selection_ranges(source: &[u8], nodes: &RangeNodeQueue, extents: &Extents, offsets: &[usize])
+-> Value {
+	let index = LineIndex::new(source);
+
+	offsets.iter()
+		.map(|&offset| {
+			selection::selection_ranges(source, nodes, extents, offset).iter()
+				.rev()
+				.fold(Value::Null, |parent, range| {
+					let mut selection_range = json!({ "range": to_lsp_range(&index, range) });
+					if !parent.is_null() {
+						selection_range["parent"] = parent;
+					}
+					selection_range
+				})
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod t {
+	use serde_json::json;
+	use crate::parse::parse_source_with_extents;
+	use super::selection_ranges;
+
+	#[test]
+	fn can_nest_selection_ranges() {
+		let source = b"a|\n";
+		let (nodes, extents) = parse_source_with_extents(&source[..]);
+
+		let ranges = selection_ranges(&source[..], &nodes, &extents, &[1]);
+
+		assert_eq!(ranges, json!([{
+			"range": {
+				"start": { "line": 0, "character": 0 },
+				"end": { "line": 0, "character": 1 }
+			},
+			"parent": {
+				"range": {
+					"start": { "line": 0, "character": 0 },
+					"end": { "line": 1, "character": 0 }
+				}
+			}
+		}]));
+	}
+}
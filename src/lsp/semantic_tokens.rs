@@ -0,0 +1,36 @@
+use serde_json::{json, Value};
+use crate::native::{String, Vec};
+use crate::semantic::{diff_semantic_tokens, encode_semantic_tokens, semantic_tokens};
+use super::OpenDocument;
+
+/// Returns all tokens of the document and remembers them under the result ID.
+pub(super) fn full(document: &mut OpenDocument, result_id: String) -> Value {
+	let data = encode(document);
+	let result = json!({ "resultId": result_id, "data": data });
+	document.semantic_tokens = Some((result_id, data));
+	result
+}
+
+/// Returns the edits from the tokens of the previous result, or all tokens if the document does
+/// not remember the previous result.
+pub(super) fn delta(document: &mut OpenDocument, previous_result_id: &str, result_id: String)
+-> Value {
+	let previous_data = match document.semantic_tokens.take() {
+		Some((remembered_id, data)) if remembered_id == previous_result_id => data,
+		_ => return full(document, result_id)
+	};
+	let data = encode(document);
+	let edits = diff_semantic_tokens(&previous_data, &data).into_iter()
+		.map(|edit| json!({
+			"start": edit.start,
+			"deleteCount": edit.delete_count,
+			"data": edit.data
+		}))
+		.collect::<Value>();
+	document.semantic_tokens = Some((result_id.clone(), data));
+	json!({ "resultId": result_id, "edits": edits })
+}
+
+fn encode(document: &OpenDocument) -> Vec<u32> {
+	encode_semantic_tokens(&document.source, &semantic_tokens(&document.source, &document.nodes))
+}
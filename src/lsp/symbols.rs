@@ -0,0 +1,37 @@
+use serde_json::{json, Value};
+use crate::native::Vec;
+use crate::line_index::LineIndex;
+use crate::outline::{Symbol, SymbolKind, outline};
+use crate::tree::RangeNodeQueue;
+use super::to_lsp_range;
+
+const PROPERTY: u8 = 7;
+const FIELD: u8 = 8;
+const OBJECT: u8 = 19;
+
+/// Returns the outline as document symbols. Containers are objects, leaves are fields, and
+/// properties are properties.
+pub fn document_symbols(source: &[u8], nodes: &RangeNodeQueue) -> Value {
+	let index = LineIndex::new(source);
+	let symbols = outline(source, nodes);
+	Value::Array(symbols.iter().map(|symbol| to_document_symbol(&index, symbol)).collect())
+}
+
+fn to_document_symbol(index: &LineIndex, symbol: &Symbol) -> Value {
+	let kind = match symbol.kind {
+		SymbolKind::Container => OBJECT,
+		SymbolKind::Leaf => FIELD,
+		SymbolKind::Property => PROPERTY
+	};
+	let children = symbol.children.iter()
+		.map(|child| to_document_symbol(index, child))
+		.collect::<Vec<_>>();
+
+	json!({
+		"name": symbol.name,
+		"kind": kind,
+		"range": to_lsp_range(index, &symbol.full_span),
+		"selectionRange": to_lsp_range(index, &symbol.name_span),
+		"children": children
+	})
+}
@@ -0,0 +1,96 @@
+use std::io::{BufRead, Error, ErrorKind, Result, Write};
+use serde_json::{json, Value};
+use crate::native::{String, Vec};
+use super::{LanguageServer, PARSE_ERROR};
+
+/// Reads the body of the next message. Returns `None` at the end of the input.
+pub fn read_message<R: BufRead>(reader: &mut R) -> Result<Option<Vec<u8>>> {
+	let mut content_length = None;
+	let mut has_headers = false;
+	let mut header = String::new();
+
+	loop {
+		header.clear();
+		if reader.read_line(&mut header)? == 0 {
+			return if has_headers {
+				Err(Error::new(ErrorKind::UnexpectedEof, "the input ended within the headers"))
+			} else {
+				Ok(None)
+			};
+		}
+
+		let header = header.trim_end();
+		if header.is_empty() {
+			break;
+		}
+
+		has_headers = true;
+		if let Some((name, value)) = header.split_once(':') {
+			if name.trim().eq_ignore_ascii_case("content-length") {
+				let length = value.trim().parse::<usize>()
+					.map_err(|_| Error::new(ErrorKind::InvalidData, "invalid `Content-Length`"))?;
+				content_length = Some(length);
+			}
+		}
+	}
+
+	let content_length = content_length
+		.ok_or_else(|| Error::new(ErrorKind::InvalidData, "missing `Content-Length`"))?;
+	let mut body = vec![0; content_length];
+	reader.read_exact(&mut body)?;
+	Ok(Some(body))
+}
+
+/// Writes the message with its `Content-Length` header.
+pub fn write_message<W: Write>(writer: &mut W, message: &Value) -> Result<()> {
+	let body = serde_json::to_vec(message)?;
+	write!(writer, "Content-Length: {}\r\n\r\n", body.len())?;
+	writer.write_all(&body)?;
+	writer.flush()
+}
+
+/// Serves the client until it sends the exit notification or closes the input.
+///
+/// Returns true if the client has requested a shutdown before, in which case the server should
+/// exit successfully.
+pub fn serve<R: BufRead, W: Write>(mut reader: R, mut writer: W) -> Result<bool> {
+	let mut server = LanguageServer::new();
+
+	while !server.has_exited() {
+		let body = match read_message(&mut reader)? {
+			Some(body) => body,
+			None => break
+		};
+		let replies = match serde_json::from_slice::<Value>(&body) {
+			Ok(message) => server.handle(&message),
+			Err(error) => vec![json!({
+				"jsonrpc": "2.0",
+				"id": null,
+				"error": { "code": PARSE_ERROR, "message": error.to_string() }
+			})]
+		};
+
+		for reply in &replies {
+			write_message(&mut writer, reply)?;
+		}
+	}
+
+	Ok(server.is_shut_down())
+}
+
+#[cfg(test)]
+mod t {
+	use std::io::Cursor;
+	use super::read_message;
+
+	#[test]
+	fn can_read_framed_messages() {
+		let input = b"Content-Length: 2\r\nContent-Type: x\r\n\r\n{}content-length: 1\r\n\r\n1";
+		let mut reader = Cursor::new(&input[..]);
+
+		assert_eq!(read_message(&mut reader).unwrap().unwrap(), b"{}");
+		assert_eq!(read_message(&mut reader).unwrap().unwrap(), b"1");
+		assert!(read_message(&mut reader).unwrap().is_none());
+		assert!(read_message(&mut Cursor::new(&b"Content-Length: 2\r\n"[..])).is_err());
+	}
+}
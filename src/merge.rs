@@ -0,0 +1,398 @@
+//! Layers are merged from the first to the last so later layers take precedence:
+//!
+//! - Concepts match by their name paths. The merged concept takes its name and kind from the latest
+//!   layer, except that a simplex does not remove the content of an earlier complex.
+//! - Attachers of a later layer replace all earlier attachers with the same label. Replaced labels
+//!   keep their earlier positions while new labels are appended.
+//! - Content lists are combined according to the [`MergeStrategy`] of their concept.
+//!
+//! Merging only depends on the order of the layers and nodes so the result is deterministic.
+
+use crate::native::{format, Range, String, Vec, VecDeque};
+use crate::node::Node;
+use crate::builder::TreeBuilder;
+use crate::tree::{RangeNode, RangeNodeQueue, path_of, resolve, span_of};
+
+/// Represents a parsed document to be merged.
+#[derive(Debug, Clone, Copy)]
+pub struct Layer<'a> {
+	/// Source that the boundaries of the nodes refer to.
+	pub source: &'a [u8],
+	/// Parsed nodes of the document.
+	pub nodes: &'a RangeNodeQueue
+}
+
+/// Represents how the content lists of a concept are combined.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+	/// Keeps the earlier content and adds the later content after it.
+	Append,
+	/// Uses the content of the latest complex.
+	Replace,
+	/// Merges concepts with the same name and appends the rest.
+	#[default]
+	Merge,
+	/// Merges concepts with the same content in the attacher with the label and appends the rest.
+	MergeByKey(String)
+}
+
+/// Contains the merge strategies of concepts keyed by their name paths.
+///
+/// The top-level list has an empty path. Lists without a strategy are merged by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeOptions {
+	strategies: Vec<(String, MergeStrategy)>
+}
+
+impl MergeOptions {
+	/// Creates options that merge every list by name.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the strategy for the content of the concepts with the path, such as `server/routes`.
+	pub fn set_strategy(&mut self, path: &str, strategy: MergeStrategy) -> &mut Self {
+		self.strategies.push((path.into(), strategy));
+		self
+	}
+
+	fn strategy_of(&self, path: &str) -> &MergeStrategy {
+		static DEFAULT_STRATEGY: MergeStrategy = MergeStrategy::Merge;
+
+		self.strategies.iter()
+			.rev()
+			.find(|(strategy_path, _)| strategy_path == path)
+			.map(|(_, strategy)| strategy)
+			.unwrap_or(&DEFAULT_STRATEGY)
+	}
+}
+
+/// Represents where a merged node came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+	/// Boundary of the node in the merged source.
+	pub span: Range<usize>,
+	/// Indices that lead to the node in the merged nodes. Each index counts the attachers of the
+	/// parent before its content.
+	pub path: Vec<usize>,
+	/// Index of the layer that the node came from.
+	pub layer: usize,
+	/// Boundary of the node in the source of the layer.
+	pub original_span: Range<usize>
+}
+
+/// Contains the merged nodes.
+#[derive(Debug, Clone, Default)]
+pub struct MergedTree {
+	/// Source that the boundaries of the nodes refer to.
+	pub source: Vec<u8>,
+	/// Merged nodes of all layers.
+	pub nodes: RangeNodeQueue,
+	/// Provenance of every merged node.
+	pub provenance: Vec<Provenance>
+}
+
+impl MergedTree {
+	/// Returns the provenance of the merged node.
+	///
+	/// The node is found by its address so it must be borrowed from the merged nodes.
+	pub fn provenance_of(&self, node: &RangeNode) -> Option<&Provenance> {
+		let path = path_of(&self.nodes, node)?;
+		self.provenance.iter().find(|provenance| provenance.path == path)
+	}
+}
+
+/// Merges the layers in order.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, merge, assert_dump, Layer, MergeOptions};
+/// let base = b"server\n\thost: localhost\n\tport: 80\n";
+/// let local = b"server\n\tport: 8080\n\tdebug|\n";
+/// let base_nodes = parse_source(&base[..]);
+/// let local_nodes = parse_source(&local[..]);
+///
+/// let merged = merge(&[
+/// 	Layer { source: &base[..], nodes: &base_nodes },
+/// 	Layer { source: &local[..], nodes: &local_nodes }
+/// ], &MergeOptions::new());
+///
+/// assert_dump(&merged.source, &merged.nodes, "
+/// 	(complex \"server\"
+/// 		(attachers (attacher \"host\" \"localhost\") (attacher \"port\" \"8080\"))
+/// 		(content (simplex \"debug\")))
+/// ");
+/// assert_eq!(merged.provenance_of(&merged.nodes[0]).unwrap().layer, 1);
+/// ```
+pub fn merge(layers: &[Layer], options: &MergeOptions) -> MergedTree {
+	let mut items = Vec::new();
+
+	for (layer_index, layer) in layers.iter().enumerate() {
+		let later_items = layer.nodes.iter().map(|node| Item::new(layer_index, node)).collect();
+		merge_lists(layers, options, &mut items, later_items, "");
+	}
+
+	let mut emitter = Emitter {
+		layers,
+		builder: TreeBuilder::new(),
+		path: Vec::new(),
+		provenance: Vec::new()
+	};
+	let nodes = emitter.emit_all(&items, 0);
+
+	MergedTree {
+		source: emitter.builder.finish(),
+		nodes,
+		provenance: emitter.provenance
+	}
+}
+
+/// Represents a node being merged. Only concepts have attachers and content.
+struct Item<'a> {
+	layer: usize,
+	node: &'a RangeNode,
+	attachers: Vec<Item<'a>>,
+	content: Option<Vec<Item<'a>>>
+}
+
+impl<'a> Item<'a> {
+	fn new(layer: usize, node: &'a RangeNode) -> Self {
+		let items = |nodes: &'a RangeNodeQueue| {
+			nodes.iter().map(|node| Self::new(layer, node)).collect::<Vec<_>>()
+		};
+		let (attachers, content) = match node {
+			Node::Complex(_, attachers, content) => (items(attachers), Some(items(content))),
+			Node::Simplex(_, attachers) => (items(attachers), None),
+			_ => (Vec::new(), None)
+		};
+
+		Self { layer, node, attachers, content }
+	}
+
+	fn name(&self, layers: &[Layer<'a>]) -> Option<&'a [u8]> {
+		match self.node {
+			Node::Complex(concept, _, _) | Node::Simplex(concept, _) => {
+				Some(resolve(layers[self.layer].source, concept))
+			},
+			_ => None
+		}
+	}
+
+	fn label(&self, layers: &[Layer<'a>]) -> Option<&'a [u8]> {
+		match self.node {
+			Node::Attacher(label, _, _) => Some(resolve(layers[self.layer].source, label)),
+			_ => None
+		}
+	}
+
+	fn key(&self, layers: &[Layer<'a>], strategy: &MergeStrategy) -> Option<&'a [u8]> {
+		let name = self.name(layers)?;
+
+		match strategy {
+			MergeStrategy::MergeByKey(key_label) => self.attachers.iter()
+				.find(|attacher| attacher.label(layers) == Some(key_label.as_bytes()))
+				.and_then(|attacher| match attacher.node {
+					Node::Attacher(_, content, _) => {
+						Some(resolve(layers[attacher.layer].source, content))
+					},
+					_ => None
+				}),
+			_ => Some(name)
+		}
+	}
+}
+
+fn merge_lists<'a>(
+	layers: &[Layer<'a>],
+	options: &MergeOptions,
+	items: &mut Vec<Item<'a>>,
+	later_items: Vec<Item<'a>>,
+	path: &str
+) {
+	let strategy = options.strategy_of(path);
+
+	match strategy {
+		MergeStrategy::Append => items.extend(later_items),
+		MergeStrategy::Replace => *items = later_items,
+		MergeStrategy::Merge | MergeStrategy::MergeByKey(_) => {
+			let earlier_count = items.len();
+
+			for later_item in later_items {
+				let matched_index = later_item.key(layers, strategy).and_then(|key| {
+					items[..earlier_count].iter()
+						.position(|item| item.key(layers, strategy) == Some(key))
+				});
+
+				match matched_index {
+					Some(index) => merge_items(layers, options, &mut items[index], later_item, path),
+					None => items.push(later_item)
+				}
+			}
+		}
+	}
+}
+
+fn merge_items<'a>(
+	layers: &[Layer<'a>],
+	options: &MergeOptions,
+	item: &mut Item<'a>,
+	later_item: Item<'a>,
+	parent_path: &str
+) {
+	let name = String::from_utf8_lossy(later_item.name(layers).unwrap_or_default());
+	let path = if parent_path.is_empty() {
+		name.into_owned()
+	} else {
+		format!("{}/{}", parent_path, name)
+	};
+
+	item.layer = later_item.layer;
+	item.node = later_item.node;
+	let attachers = core::mem::take(&mut item.attachers);
+	item.attachers = merge_attachers(layers, attachers, later_item.attachers);
+	item.content = match (item.content.take(), later_item.content) {
+		(Some(mut content), Some(later_content)) => {
+			merge_lists(layers, options, &mut content, later_content, &path);
+			Some(content)
+		},
+		(content, later_content) => content.or(later_content)
+	};
+}
+
+fn merge_attachers<'a>(
+	layers: &[Layer<'a>],
+	attachers: Vec<Item<'a>>,
+	later_attachers: Vec<Item<'a>>
+) -> Vec<Item<'a>> {
+	let later_labels = later_attachers.iter()
+		.filter_map(|attacher| attacher.label(layers))
+		.collect::<Vec<_>>();
+	let mut later_attachers = later_attachers.into_iter().map(Some).collect::<Vec<_>>();
+	let mut merged_attachers = Vec::new();
+
+	for attacher in attachers {
+		match attacher.label(layers) {
+			Some(label) if later_labels.contains(&label) => {
+				for later_attacher in &mut later_attachers {
+					let has_label = later_attacher.as_ref()
+						.map(|later_attacher| later_attacher.label(layers) == Some(label))
+						.unwrap_or(false);
+					if has_label {
+						merged_attachers.extend(later_attacher.take());
+					}
+				}
+			},
+			_ => merged_attachers.push(attacher)
+		}
+	}
+
+	merged_attachers.extend(later_attachers.into_iter().flatten());
+	merged_attachers
+}
+
+struct Emitter<'l, 'a> {
+	layers: &'l [Layer<'a>],
+	builder: TreeBuilder,
+	/// Indices that lead to the node being emitted.
+	path: Vec<usize>,
+	provenance: Vec<Provenance>
+}
+
+impl<'l, 'a> Emitter<'l, 'a> {
+	/// Emits the items whose indices among their siblings start from the given index.
+	fn emit_all(&mut self, items: &[Item<'a>], start: usize) -> RangeNodeQueue {
+		let mut nodes = VecDeque::with_capacity(items.len());
+
+		for (index, item) in items.iter().enumerate() {
+			self.path.push(start + index);
+			nodes.push_back(self.emit(item));
+			self.path.pop();
+		}
+
+		nodes
+	}
+
+	fn emit(&mut self, item: &Item<'a>) -> RangeNode {
+		let source = self.layers[item.layer].source;
+		let node = match item.name(self.layers) {
+			Some(name) => {
+				let concept = self.builder.push_text(name);
+				let attachers = self.emit_all(&item.attachers, 0);
+				match &item.content {
+					Some(content) => {
+						let content = self.emit_all(content, attachers.len());
+						Node::Complex(concept, attachers, content)
+					},
+					None => Node::Simplex(concept, attachers)
+				}
+			},
+			None => self.builder.copy(source, item.node)
+		};
+
+		self.provenance.push(Provenance {
+			span: span_of(&node),
+			path: self.path.clone(),
+			layer: item.layer,
+			original_span: span_of(item.node)
+		});
+
+		node
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::node::Node;
+	use crate::dump::assert_dump;
+	use crate::parse::parse_source;
+	use super::{Layer, MergeOptions, MergeStrategy, merge};
+
+	#[test]
+	fn can_merge_with_strategies() {
+		let base = b"a\n\tx|\nb\n\tx|\nc\n\troute\n\t\tid: 1\n\t\tto: old\n\troute\n\t\tid: 2\n";
+		let local = b"a\n\ty|\nb\n\ty|\nc\n\troute\n\t\tid: 2\n\t\tto: new\n";
+		let nodes = [parse_source(&base[..]), parse_source(&local[..])];
+		let layers = [
+			Layer { source: &base[..], nodes: &nodes[0] },
+			Layer { source: &local[..], nodes: &nodes[1] }
+		];
+		let mut options = MergeOptions::new();
+		options.set_strategy("a", MergeStrategy::Append)
+			.set_strategy("b", MergeStrategy::Replace)
+			.set_strategy("c", MergeStrategy::MergeByKey("id".into()));
+
+		let merged = merge(&layers, &options);
+
+		assert_dump(&merged.source, &merged.nodes, "
+			(complex \"a\" (content (simplex \"x\") (simplex \"y\")))
+			(complex \"b\" (content (simplex \"y\")))
+			(complex \"c\" (content
+				(complex \"route\" (attachers (attacher \"id\" \"1\") (attacher \"to\" \"old\")))
+				(complex \"route\" (attachers (attacher \"id\" \"2\") (attacher \"to\" \"new\")))))
+		");
+		let route = match &merged.nodes[2] {
+			Node::Complex(_, _, content) => &content[1],
+			_ => unreachable!()
+		};
+		let provenance = merged.provenance_of(route).unwrap();
+		assert_eq!(provenance.layer, 1);
+		assert_eq!(provenance.path, [2, 1]);
+		assert_eq!(&local[provenance.original_span.clone()], b"route");
+	}
+
+	#[test]
+	fn can_keep_content_of_earlier_complex() {
+		let base = b"a\n\tb|\n";
+		let local = b"a|\n\tk: v\n";
+		let nodes = [parse_source(&base[..]), parse_source(&local[..])];
+
+		let merged = merge(&[
+			Layer { source: &base[..], nodes: &nodes[0] },
+			Layer { source: &local[..], nodes: &nodes[1] }
+		], &MergeOptions::new());
+
+		assert_dump(&merged.source, &merged.nodes, "
+			(complex \"a\" (attachers (attacher \"k\" \"v\")) (content (simplex \"b\")))
+		");
+	}
+}
@@ -0,0 +1,277 @@
+//! The language of an othertongue is tagged in one of two ways:
+//!
+//! - The first line is `#!` followed by the language, such as `#!json`. The tag line is not part of
+//!   the embedded text.
+//! - The nearest concept before the othertongue among its siblings has a `language` attacher.
+//!
+//! The tag on the first line takes precedence. Othertongues without a tag or without a registered
+//! handler are skipped. The lines of the embedded text are joined by line feeds as they are,
+//! including the indentation that the source has.
+
+use core::fmt::{Display, Formatter, Result as FormatResult};
+use crate::native::{Box, Range, String, Vec};
+use crate::node::Node;
+use crate::attacher_map::AttacherMap;
+use crate::diagnostic::Diagnostic;
+use crate::lint::for_each_siblings;
+use crate::tree::{RangeNodeQueue, resolve, span_of};
+
+/// Represents an interpreter of embedded text in a language.
+pub trait OthertongueHandler<T> {
+	/// Interprets the embedded text. Spans of errors are relative to the text.
+	fn handle(&self, text: &str) -> Result<T, EmbeddedError>;
+}
+
+impl<T, F> OthertongueHandler<T> for F
+where F: Fn(&str) -> Result<T, EmbeddedError> {
+	fn handle(&self, text: &str) -> Result<T, EmbeddedError> {
+		self(text)
+	}
+}
+
+/// Represents the error found by a handler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedError {
+	/// Boundary in the embedded text where the problem has been found.
+	pub span: Range<usize>,
+	pub message: String
+}
+
+impl Display for EmbeddedError {
+	fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+		write!(formatter, "{} at bytes {}..{}", self.message, self.span.start, self.span.end)
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for EmbeddedError {}
+
+/// Represents an othertongue that has been interpreted by a handler.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedValue<T> {
+	/// Boundary of the othertongue in the source.
+	pub span: Range<usize>,
+	pub language: String,
+	pub value: T
+}
+
+/// Contains the interpreted othertongues and the problems found while interpreting them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedValues<T> {
+	pub values: Vec<EmbeddedValue<T>>,
+	/// Problems found by the handlers. Their spans refer to the source.
+	pub diagnostics: Vec<Diagnostic>
+}
+
+/// Contains the handlers of othertongues keyed by their languages.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, EmbeddedError, OthertongueRegistry};
+/// let source = b"sum|\n\tlanguage: numbers\n===\n1 2 3\n===\n";
+/// let nodes = parse_source(&source[..]);
+/// let mut registry = OthertongueRegistry::new();
+/// registry.register("numbers", |text: &str| -> Result<i64, EmbeddedError> {
+/// 	Ok(text.split_whitespace().map(|number| number.parse::<i64>().unwrap()).sum())
+/// });
+///
+/// let embedded = registry.handle(&source[..], &nodes);
+///
+/// assert_eq!(embedded.values[0].value, 6);
+/// ```
+pub struct OthertongueRegistry<T> {
+	handlers: Vec<(String, Box<dyn OthertongueHandler<T>>)>
+}
+
+impl<T> Default for OthertongueRegistry<T> {
+	fn default() -> Self {
+		Self { handlers: Vec::new() }
+	}
+}
+
+impl<T> OthertongueRegistry<T> {
+	/// Creates a registry without any handler.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Registers the handler of the language. It replaces the earlier handler of the language.
+	pub fn register<H>(&mut self, language: &str, handler: H) -> &mut Self
+	where H: OthertongueHandler<T> + 'static {
+		self.handlers.retain(|(registered_language, _)| registered_language != language);
+		self.handlers.push((language.into(), Box::new(handler)));
+		self
+	}
+
+	/// Interprets the tagged othertongues in the nodes with the registered handlers.
+	pub fn handle(&self, source: &[u8], nodes: &RangeNodeQueue) -> EmbeddedValues<T> {
+		let mut embedded = EmbeddedValues { values: Vec::new(), diagnostics: Vec::new() };
+
+		for_each_siblings(nodes, &mut |siblings| {
+			let mut preceding_language = None;
+
+			for node in siblings.nodes {
+				let lines = match node {
+					Node::LineOthertongue(line) => core::slice::from_ref(line),
+					Node::BlockOthertongue(lines) => lines.as_slice(),
+					Node::Complex(..) | Node::Simplex(..) => {
+						preceding_language = AttacherMap::of(source, node)
+							.get(b"language")
+							.map(|entry| entry.content);
+						continue;
+					},
+					_ => continue
+				};
+				let tagged_language = lines.first()
+					.and_then(|line| resolve(source, line).trim_ascii().strip_prefix(b"#!"));
+				let (language, lines) = match (tagged_language, preceding_language) {
+					(Some(language), _) => (language, &lines[1..]),
+					(None, Some(language)) => (language, lines),
+					(None, None) => continue
+				};
+				let handler = self.handlers.iter()
+					.find(|(registered_language, _)| registered_language.as_bytes() == language)
+					.map(|(_, handler)| handler);
+
+				if let Some(handler) = handler {
+					let language = String::from_utf8_lossy(language).into_owned();
+					let span = span_of(node);
+					match interpret(source, lines, handler.as_ref()) {
+						Ok(value) => embedded.values.push(EmbeddedValue { span, language, value }),
+						Err(diagnostic) => embedded.diagnostics.push(diagnostic)
+					}
+				}
+			}
+		});
+
+		embedded
+	}
+}
+
+fn interpret<T>(source: &[u8], lines: &[Range<usize>], handler: &dyn OthertongueHandler<T>)
+-> Result<T, Diagnostic> {
+	let mut text = Vec::new();
+
+	for (index, line) in lines.iter().enumerate() {
+		if index > 0 {
+			text.push(b'\n');
+		}
+		text.extend_from_slice(resolve(source, line));
+	}
+
+	let text = core::str::from_utf8(&text).map_err(|error| {
+		let offset = map_offset(lines, error.valid_up_to());
+		let message = "embedded text is not valid UTF-8".into();
+		Diagnostic::error(offset..offset, "invalid_embedded", message)
+	})?;
+
+	handler.handle(text).map_err(|error| {
+		let span = map_offset(lines, error.span.start)..map_offset(lines, error.span.end);
+		Diagnostic::error(span, "invalid_embedded", error.message)
+	})
+}
+
+/// Returns the offset in the source that corresponds to the offset in the joined lines.
+fn map_offset(lines: &[Range<usize>], offset: usize) -> usize {
+	let mut line_start = 0;
+
+	for line in lines {
+		let line_end = line_start + line.len();
+		if offset <= line_end {
+			return line.start + (offset - line_start);
+		}
+		line_start = line_end + 1;
+	}
+
+	lines.last().map(|line| line.end).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use crate::tree::resolve;
+	use super::{EmbeddedError, EmbeddedValue, OthertongueRegistry, map_offset};
+
+	fn parse_numbers(text: &str) -> Result<Vec<u32>, EmbeddedError> {
+		let mut numbers = Vec::new();
+		let mut offset = 0;
+
+		for word in text.split(|character: char| character.is_whitespace()) {
+			if !word.is_empty() {
+				let number = word.parse().map_err(|_| EmbeddedError {
+					span: offset..offset + word.len(),
+					message: "expected a number".into()
+				})?;
+				numbers.push(number);
+			}
+			offset += word.len() + 1;
+		}
+
+		Ok(numbers)
+	}
+
+	#[test]
+	fn can_interpret_tagged_othertongues() {
+		let source = b"listlanguagenumbers1 2#!numbers3";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Simplex(0..4, {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(Node::Attacher(4..12, 12..19, Vec::new()));
+			attachers
+		}));
+		nodes.push_back(Node::LineOthertongue(19..22));
+		nodes.push_back(Node::BlockOthertongue({
+			let mut lines = Vec::new();
+			lines.push(22..31);
+			lines.push(31..32);
+			lines
+		}));
+		let mut registry = OthertongueRegistry::new();
+		registry.register("numbers", parse_numbers);
+
+		let embedded = registry.handle(&source[..], &nodes);
+
+		assert_eq!(embedded.values, [
+			EmbeddedValue { span: 19..22, language: "numbers".into(), value: Vec::from([1, 2]) },
+			EmbeddedValue { span: 22..32, language: "numbers".into(), value: Vec::from([3]) }
+		]);
+		assert_eq!(embedded.diagnostics.len(), 0);
+	}
+
+	#[test]
+	fn cannot_interpret_invalid_othertongues() {
+		let source = b"numbers#!numbers1 2\n3 4x5";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..7, VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(Node::BlockOthertongue({
+				let mut lines = Vec::new();
+				lines.push(7..16);
+				lines.push(16..19);
+				lines.push(20..24);
+				lines
+			}));
+			content.push_back(Node::LineOthertongue(24..25));
+			content
+		}));
+		let mut registry = OthertongueRegistry::new();
+		registry.register("numbers", parse_numbers);
+
+		let embedded = registry.handle(&source[..], &nodes);
+
+		assert_eq!(embedded.values.len(), 0);
+		assert_eq!(embedded.diagnostics.len(), 1);
+		assert_eq!(resolve(&source[..], &embedded.diagnostics[0].span), b"4x");
+	}
+
+	#[test]
+	fn can_map_offsets_across_lines() {
+		let lines = [3..6, 10..12];
+
+		assert_eq!(map_offset(&lines, 0), 3);
+		assert_eq!(map_offset(&lines, 3), 6);
+		assert_eq!(map_offset(&lines, 4), 10);
+		assert_eq!(map_offset(&lines, 6), 12);
+	}
+}
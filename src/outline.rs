@@ -0,0 +1,223 @@
+//! An outline has a symbol for every concept and attacher. Comments and othertongues are left out.
+//!
+//! | Node     | Kind        | Name          | Full span                                      |
+//! |----------|-------------|---------------|------------------------------------------------|
+//! | Complex  | `Container` | concept       | from the concept to the end of its descendants |
+//! | Simplex  | `Leaf`      | concept       | from the concept to the end of its attachers   |
+//! | Attacher | `Property`  | label         | from the label to the end of the content       |
+//!
+//! Nodes of any `AbstractNode` implementation are outlined by [`outline_with()`]. Their kinds are
+//! read through the trait while their names, spans, and children are read through an
+//! [`OutlineReader`], in the same way that the parser reads spans while recording extents. Owned
+//! nodes have no boundaries, so [`outline_owned()`] converts them to ranged nodes first.
+
+use crate::abstracts::{
+	AbstractBoundary,
+	AbstractBoundaryCollection,
+	AbstractNode,
+	AbstractNodeQueue,
+	AbstractAttacherCollection,
+	AbstractAttacherNode
+};
+use crate::native::{Range, String, Vec};
+use crate::node::Node;
+use crate::node_kind::NodeKind;
+use crate::owned::{OwnedNodeQueue, to_range_nodes};
+use crate::tree::{RangeNode, RangeNodeQueue, extent_of, resolve, span_of};
+
+/// Represents the role of a symbol in an outline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+	Container,
+	Leaf,
+	Property
+}
+
+impl SymbolKind {
+	/// Returns the name of the kind in lowercase.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Container => "container",
+			Self::Leaf => "leaf",
+			Self::Property => "property"
+		}
+	}
+}
+
+/// Represents a concept or attacher in an outline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+	/// Name of the concept or label of the attacher. Invalid UTF-8 is replaced with the replacement
+	/// character.
+	pub name: String,
+	pub kind: SymbolKind,
+	/// Boundary of the node and its descendants.
+	pub full_span: Range<usize>,
+	/// Boundary of the name of the concept or the label of the attacher.
+	pub name_span: Range<usize>,
+	/// Symbols of the attachers followed by the symbols of the content.
+	pub children: Vec<Symbol>
+}
+
+/// Reads the parts of nodes that [`outline_with()`] cannot read through `AbstractNode`.
+pub struct OutlineReader<'a, X> {
+	/// Returns the name of a concept or the label of an attacher, with its boundary.
+	pub name_of: &'a dyn Fn(&X) -> (String, Range<usize>),
+	/// Returns the boundary of the node and its descendants.
+	pub full_span_of: &'a dyn Fn(&X) -> Range<usize>,
+	/// Returns the attachers of the node followed by its content.
+	pub children_of: &'a dyn Fn(&X) -> Vec<&X>
+}
+
+/// Returns the symbols of the concepts in the nodes as a hierarchy.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, outline, SymbolKind};
+/// let source = b"server\n\tport: 80\n\troute|\n";
+/// let nodes = parse_source(&source[..]);
+///
+/// let symbols = outline(&source[..], &nodes);
+///
+/// assert_eq!(symbols[0].name, "server");
+/// assert_eq!(symbols[0].kind, SymbolKind::Container);
+/// assert_eq!(symbols[0].children[0].kind, SymbolKind::Property);
+/// assert_eq!(symbols[0].children[1].name, "route");
+/// ```
+pub fn outline(source: &[u8], nodes: &RangeNodeQueue) -> Vec<Symbol> {
+	outline_with::<
+		Range<usize>,
+		Vec<Range<usize>>,
+		RangeNode,
+		RangeNodeQueue,
+		RangeNode,
+		RangeNodeQueue,
+		_
+	>(nodes, &OutlineReader {
+		name_of: &|node| {
+			let name_span = match node {
+				Node::Complex(concept, _, _) | Node::Simplex(concept, _) => concept.clone(),
+				Node::Attacher(label, _, _) => label.clone(),
+				_ => span_of(node)
+			};
+			(String::from_utf8_lossy(resolve(source, &name_span)).into_owned(), name_span)
+		},
+		full_span_of: &|node| match node {
+			Node::Attacher(_, _, _) => span_of(node),
+			_ => extent_of(node)
+		},
+		children_of: &|node| match node {
+			Node::Complex(_, attachers, content) => attachers.iter().chain(content.iter()).collect(),
+			Node::Simplex(_, attachers) => attachers.iter().collect(),
+			_ => Vec::new()
+		}
+	})
+}
+
+/// Returns the symbols of the concepts in nodes of any `AbstractNode` implementation.
+///
+/// Complexes, simplexes, and attachers are told apart by their kinds. Other nodes are left out
+/// without being read.
+pub fn outline_with<'n, T, U, V, W, X, Y, I>(nodes: I, reader: &OutlineReader<X>) -> Vec<Symbol>
+where
+	T: AbstractBoundary<usize>,
+	U: AbstractBoundaryCollection<usize, T>,
+	V: AbstractAttacherNode,
+	W: AbstractAttacherCollection<V>,
+	X: AbstractNode<usize, T, usize, T, U, V, W, X, Y> + 'n,
+	Y: AbstractNodeQueue<X>,
+	I: IntoIterator<Item = &'n X> {
+	nodes.into_iter()
+		.filter_map(|node| {
+			let kind = match node.kind() {
+				NodeKind::Complex => SymbolKind::Container,
+				NodeKind::Simplex => SymbolKind::Leaf,
+				NodeKind::Attacher => SymbolKind::Property,
+				_ => return None
+			};
+			let (name, name_span) = (reader.name_of)(node);
+
+			Some(Symbol {
+				name,
+				kind,
+				full_span: (reader.full_span_of)(node),
+				name_span,
+				children: outline_with::<T, U, V, W, X, Y, _>((reader.children_of)(node), reader)
+			})
+		})
+		.collect()
+}
+
+/// Returns the symbols of the concepts in the owned nodes and the source that their spans refer to.
+///
+/// Owned nodes have no boundaries, so the source is the one that
+/// [`to_range_nodes()`](crate::to_range_nodes) creates for them.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_owned, outline_owned, OwnedNodeQueue};
+/// let nodes: OwnedNodeQueue = parse_owned(b"server\n\tport: 80\n").unwrap();
+///
+/// let (source, symbols) = outline_owned(&nodes);
+///
+/// assert_eq!(symbols[0].children[0].name, "port");
+/// assert_eq!(&source[symbols[0].name_span.clone()], b"server");
+/// ```
+pub fn outline_owned<S: AsRef<str>>(nodes: &OwnedNodeQueue<S>) -> (Vec<u8>, Vec<Symbol>) {
+	let (source, nodes) = to_range_nodes(nodes);
+	let symbols = outline(&source, &nodes);
+	(source, symbols)
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Range, String, Vec};
+	use crate::node::Node;
+	use crate::parse::parse_source;
+	use crate::tree::{RangeNode, RangeNodeQueue};
+	use super::{OutlineReader, SymbolKind, outline, outline_with};
+
+	#[test]
+	fn can_outline_concepts_and_attachers() {
+		let source = b"a\n\tk: v\n\t# c\n\tb|\nc|\n";
+		let nodes = parse_source(&source[..]);
+
+		let symbols = outline(&source[..], &nodes);
+
+		assert_eq!(symbols.len(), 2);
+		assert_eq!(symbols[0].full_span, 0..15);
+		assert_eq!(symbols[0].children.len(), 2);
+		assert_eq!(symbols[0].children[0].name, "k");
+		assert_eq!(symbols[0].children[0].name_span, 3..4);
+		assert_eq!(symbols[0].children[0].full_span, 3..7);
+		assert_eq!(symbols[0].children[1].kind, SymbolKind::Leaf);
+		assert_eq!(symbols[1].name_span, 17..18);
+	}
+
+	#[test]
+	fn can_outline_through_readers() {
+		let nodes = parse_source(b"a\n\tb|\n\t# c\n");
+
+		let symbols = outline_with::<
+			Range<usize>,
+			Vec<Range<usize>>,
+			RangeNode,
+			RangeNodeQueue,
+			RangeNode,
+			RangeNodeQueue,
+			_
+		>(&nodes, &OutlineReader {
+			name_of: &|_| (String::from("node"), 0..0),
+			full_span_of: &|_| 0..0,
+			children_of: &|node| match node {
+				Node::Complex(_, _, content) => content.iter().collect(),
+				_ => Vec::new()
+			}
+		});
+
+		assert_eq!(symbols.len(), 1);
+		assert_eq!(symbols[0].name, "node");
+		assert_eq!(symbols[0].children.len(), 1);
+		assert_eq!(symbols[0].children[0].kind, SymbolKind::Leaf);
+	}
+}
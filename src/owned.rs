@@ -0,0 +1,165 @@
+//! Owned nodes store their texts instead of boundaries so they do not borrow the source. The text
+//! type may be `Box<str>`, `Arc<str>`, `String`, or any type that can be created from `&str`.
+//!
+//! Owned nodes do not implement `AbstractNode`. The lexer creates boundaries from offsets alone, so
+//! the tokens that the generic [`parse()`](crate::parse) receives never carry the source and the
+//! texts cannot be created from them. Parse with [`parse_owned()`] instead, or convert ranged nodes
+//! with [`to_owned_nodes()`].
+//!
+//! The third field of an attacher keeps the texts of the boundaries in the third field of the
+//! ranged attacher.
+
+use crate::native::{Box, Range, Vec, VecDeque};
+use crate::node::Node;
+use crate::builder::TreeBuilder;
+use crate::diagnostic::Diagnostic;
+use crate::document::Document;
+use crate::tree::{RangeNode, RangeNodeQueue};
+
+/// Represents a node that owns its texts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OwnedNode<S = Box<str>> {
+	Complex(S, OwnedNodeQueue<S>, OwnedNodeQueue<S>),
+	Simplex(S, OwnedNodeQueue<S>),
+	Attacher(S, S, Vec<S>),
+	LineComment(S),
+	BlockComment(Vec<S>),
+	LineOthertongue(S),
+	BlockOthertongue(Vec<S>)
+}
+
+/// Represents the collection of owned nodes.
+pub type OwnedNodeQueue<S = Box<str>> = VecDeque<OwnedNode<S>>;
+
+/// Parses the source and converts its nodes into owned nodes.
+///
+/// Returns an error that spans the first invalid bytes if the source is not valid UTF-8.
+///
+/// ## Examples
+/// ```
+/// use std::sync::Arc;
+/// use chearmyp_parser::{parse_owned, OwnedNode, OwnedNodeQueue};
+/// let nodes: OwnedNodeQueue<Arc<str>> = {
+/// 	let source = String::from("hello|\n");
+/// 	parse_owned(source.as_bytes()).unwrap()
+/// };
+///
+/// assert_eq!(nodes[0], OwnedNode::Simplex("hello".into(), Default::default()));
+/// ```
+pub fn parse_owned<S>(source: &[u8]) -> Result<OwnedNodeQueue<S>, Diagnostic>
+where S: for<'a> From<&'a str> {
+	let document = Document::parse(source)?;
+	Ok(to_owned_nodes(&document))
+}
+
+/// Converts the nodes of the document into owned nodes.
+pub fn to_owned_nodes<S>(document: &Document) -> OwnedNodeQueue<S>
+where S: for<'a> From<&'a str> {
+	convert_nodes(document, document.nodes())
+}
+
+fn convert_nodes<S>(document: &Document, nodes: &RangeNodeQueue) -> OwnedNodeQueue<S>
+where S: for<'a> From<&'a str> {
+	nodes.iter().map(|node| convert_node(document, node)).collect()
+}
+
+fn convert_node<S>(document: &Document, node: &RangeNode) -> OwnedNode<S>
+where S: for<'a> From<&'a str> {
+	let text = |boundary: &Range<usize>| S::from(document.resolve(boundary));
+	let lines = |lines: &Vec<Range<usize>>| lines.iter().map(text).collect();
+
+	match node {
+		Node::Complex(concept, attachers, content) => OwnedNode::Complex(
+			text(concept),
+			convert_nodes(document, attachers),
+			convert_nodes(document, content)
+		),
+		Node::Simplex(concept, attachers) => {
+			OwnedNode::Simplex(text(concept), convert_nodes(document, attachers))
+		},
+		Node::Attacher(label, content, comments) => {
+			OwnedNode::Attacher(text(label), text(content), lines(comments))
+		},
+		Node::LineComment(line) => OwnedNode::LineComment(text(line)),
+		Node::BlockComment(block) => OwnedNode::BlockComment(lines(block)),
+		Node::LineOthertongue(line) => OwnedNode::LineOthertongue(text(line)),
+		Node::BlockOthertongue(block) => OwnedNode::BlockOthertongue(lines(block))
+	}
+}
+
+/// Converts owned nodes back into ranged nodes and the source that their boundaries refer to.
+pub fn to_range_nodes<S: AsRef<str>>(nodes: &OwnedNodeQueue<S>) -> (Vec<u8>, RangeNodeQueue) {
+	let mut builder = TreeBuilder::new();
+	let nodes = build_nodes(&mut builder, nodes);
+	(builder.finish(), nodes)
+}
+
+fn build_nodes<S: AsRef<str>>(builder: &mut TreeBuilder, nodes: &OwnedNodeQueue<S>)
+-> RangeNodeQueue {
+	nodes.iter().map(|node| build_node(builder, node)).collect()
+}
+
+fn build_node<S: AsRef<str>>(builder: &mut TreeBuilder, node: &OwnedNode<S>) -> RangeNode {
+	match node {
+		OwnedNode::Complex(concept, attachers, content) => {
+			let attachers = build_nodes(builder, attachers);
+			let content = build_nodes(builder, content);
+			builder.complex(bytes(concept), attachers, content)
+		},
+		OwnedNode::Simplex(concept, attachers) => {
+			let attachers = build_nodes(builder, attachers);
+			builder.simplex(bytes(concept), attachers)
+		},
+		OwnedNode::Attacher(label, content, comments) => {
+			let label = builder.push_text(bytes(label));
+			let content = builder.push_text(bytes(content));
+			let comments = comments.iter().map(|comment| builder.push_text(bytes(comment))).collect();
+			Node::Attacher(label, content, comments)
+		},
+		OwnedNode::LineComment(line) => builder.line_comment(bytes(line)),
+		OwnedNode::BlockComment(lines) => builder.block_comment(lines.iter().map(bytes)),
+		OwnedNode::LineOthertongue(line) => builder.line_othertongue(bytes(line)),
+		OwnedNode::BlockOthertongue(lines) => builder.block_othertongue(lines.iter().map(bytes))
+	}
+}
+
+fn bytes<S: AsRef<str>>(text: &S) -> &[u8] {
+	text.as_ref().as_bytes()
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Box, String, Vec, VecDeque};
+	use crate::dump::assert_dump;
+	use super::{OwnedNode, OwnedNodeQueue, parse_owned, to_range_nodes};
+
+	#[test]
+	fn can_outlive_source() {
+		let nodes: OwnedNodeQueue<Box<str>> = {
+			let source = String::from("hello\n\tname: world\n");
+			parse_owned(source.as_bytes()).unwrap()
+		};
+
+		let mut attachers = VecDeque::new();
+		let mut comments = Vec::new();
+		comments.push("".into());
+		attachers.push_back(OwnedNode::Attacher("name".into(), "world".into(), comments));
+		assert_eq!(nodes[0], OwnedNode::Complex("hello".into(), attachers, VecDeque::new()));
+	}
+
+	#[test]
+	fn can_convert_back_to_range_nodes() {
+		let mut nodes: OwnedNodeQueue<String> = VecDeque::new();
+		nodes.push_back(OwnedNode::Simplex("hello".into(), {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(OwnedNode::LineComment(" greeting".into()));
+			attachers
+		}));
+
+		let (source, nodes) = to_range_nodes(&nodes);
+
+		assert_dump(&source, &nodes, "
+			(simplex \"hello\" (attachers (line_comment \" greeting\")))
+		");
+	}
+}
@@ -20,6 +20,10 @@ use crate::abstracts::{
 };
 use crate::token_kind::TokenKind;
 use crate::ScopeStack;
+use crate::native::{Range, Vec, VecDeque};
+use crate::token::Token;
+use crate::tree::{Extents, RangeNodeQueue, span_of};
+use crate::lexer::lex;
 
 /// Returns a collection of nodes based from the source.
 ///
@@ -87,7 +91,7 @@ use crate::ScopeStack;
 /// 	Node::Simplex(47..58, VecDeque::new())
 /// ]))
 /// ```
-pub fn parse<T, U, V, W, X, Y, Z, A, B, C, D, E, F, G, H, I>(mut tokens: W) -> A
+pub fn parse<T, U, V, W, X, Y, Z, A, B, C, D, E, F, G, H, I>(tokens: W) -> A
 where
 	T: AbstractBoundary<usize>,
 	U: AbstractBoundaryCollection<usize, T>,
@@ -106,7 +110,32 @@ where
 	H: AbstractLineOthertongueToken<Line = T> + From<V>,
 	I: AbstractBlockOthertongueToken<Block = U> + From<V> {
 	let mut scope_stack = ScopeStack::<T, U, X, Y, Z, A>::new();
+	append_tokens::<T, U, V, W, X, Y, Z, A, B, C, D, E, F, G, H, I>(tokens, &mut scope_stack);
+	scope_stack.finalize()
+}
 
+/// Appends every token of the stream to the scope stack.
+fn append_tokens<T, U, V, W, X, Y, Z, A, B, C, D, E, F, G, H, I>(
+	mut tokens: W,
+	scope_stack: &mut ScopeStack<T, U, X, Y, Z, A>
+)
+where
+	T: AbstractBoundary<usize>,
+	U: AbstractBoundaryCollection<usize, T>,
+	V: AbstractToken<usize, T, usize, T, U>,
+	W: AbstractTokenQueue<usize, T, usize, T, U, V>,
+	X: AbstractAttacherNode + From<Z>,
+	Y: AbstractAttacherCollection<X>,
+	Z: AbstractNode<usize, T, usize, T, U, X, Y, Z, A>,
+	A: AbstractNodeQueue<Z>,
+	B: AbstractAttacherToken<Label = T, Content = T> + From<V>,
+	C: AbstractScopeLevelToken + From<V>,
+	D: AbstractComplexToken<Complex = T> + From<V>,
+	E: AbstractSimplexToken<Simplex = T> + From<V>,
+	F: AbstractLineCommentToken<Line = T> + From<V>,
+	G: AbstractBlockCommentToken<Block = U> + From<V>,
+	H: AbstractLineOthertongueToken<Line = T> + From<V>,
+	I: AbstractBlockOthertongueToken<Block = U> + From<V> {
 	loop {
 		let token = tokens.shift_token();
 
@@ -148,8 +177,76 @@ where
 			None => break
 		}
 	}
+}
 
-	scope_stack.finalize()
+/// Returns the nodes of a source after lexing it.
+///
+/// It is a shorthand of [`parse()`] for the common case of using ranges as boundaries.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, assert_dump};
+/// let source = b"hello\n\tworld|\n";
+///
+/// let nodes = parse_source(&source[..]);
+///
+/// assert_dump(&source[..], &nodes, "(complex \"hello\" (content (simplex \"world\")))");
+/// ```
+pub fn parse_source(source: &[u8]) -> RangeNodeQueue {
+	type DefaultToken = Token<Range<usize>, Vec<Range<usize>>>;
+
+	let tokens: VecDeque<DefaultToken> = lex(&source, VecDeque::new());
+
+	parse::<
+		_, _, _, _, _, _, _,
+		RangeNodeQueue,
+		DefaultToken,
+		DefaultToken,
+		DefaultToken,
+		DefaultToken,
+		DefaultToken,
+		DefaultToken,
+		DefaultToken,
+		DefaultToken
+	>(tokens)
+}
+
+/// Returns the nodes of a source with the extents of its concepts.
+///
+/// The extents are recorded while the parser promotes concepts so that finding them does not need
+/// another walk through the tree.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::parse_source_with_extents;
+/// let source = b"server\n\tport: 80\n\troute|\n";
+///
+/// let (nodes, extents) = parse_source_with_extents(&source[..]);
+///
+/// assert_eq!(extents.extent_of(&nodes[0]), 0..23);
+/// ```
+pub fn parse_source_with_extents(source: &[u8]) -> (RangeNodeQueue, Extents) {
+	type DefaultToken = Token<Range<usize>, Vec<Range<usize>>>;
+
+	let tokens: VecDeque<DefaultToken> = lex(&source, VecDeque::new());
+	let mut scope_stack = ScopeStack::new();
+	scope_stack.record_extents(span_of);
+
+	append_tokens::<
+		_, _, _, _, _, _, _,
+		RangeNodeQueue,
+		DefaultToken,
+		DefaultToken,
+		DefaultToken,
+		DefaultToken,
+		DefaultToken,
+		DefaultToken,
+		DefaultToken,
+		DefaultToken
+	>(tokens, &mut scope_stack);
+
+	let (nodes, extents) = scope_stack.finalize_with_extents();
+	(nodes, Extents::new(extents))
 }
 
 
@@ -158,7 +255,8 @@ mod t {
 	use crate::native::{Range, Vec, VecDeque};
 	use crate::token::Token;
 	use crate::node::Node;
-	use super::parse;
+	use crate::dump::assert_dump;
+	use super::{parse, parse_source, parse_source_with_extents};
 
 	type DefaultToken = Token<Range<usize>, Vec<Range<usize>>>;
 	type AttacherToken = DefaultToken;
@@ -230,4 +328,57 @@ mod t {
 
 		assert_eq!(nodes, expected_nodes)
 	}
+
+	#[test]
+	fn can_parse_stream_into_readable_dump() {
+		let source = b"hello\n\tworld|\nhi";
+		let mut sample_queue = VecDeque::new();
+		sample_queue.push_back(Token::Complex(0..5));
+		sample_queue.push_back(Token::ScopeLevel(1));
+		sample_queue.push_back(Token::Simplex(7..12));
+		sample_queue.push_back(Token::ScopeLevel(0));
+		sample_queue.push_back(Token::Complex(14..16));
+		let nodes = parse::<
+			_, _, _, _, _, _, _,
+			VecDeque<Node<
+				Range<usize>,
+				Vec<Range<usize>>
+			>>,
+			AttacherToken,
+			ScopeLevelToken,
+			ComplexToken,
+			SimplexToken,
+			LineCommentToken,
+			BlockCommentToken,
+			LineOthertongueToken,
+			BlockOthertongueToken
+		>(sample_queue);
+
+		assert_dump(&source[..], &nodes, "
+			(complex \"hello\"
+				(content
+					(simplex \"world\")))
+			(complex \"hi\")
+		");
+	}
+
+	#[test]
+	fn can_parse_source() {
+		let source = b"hello\n\tworld|\n";
+
+		let nodes = parse_source(&source[..]);
+
+		assert_dump(&source[..], &nodes, "(complex \"hello\" (content (simplex \"world\")))");
+	}
+
+	#[test]
+	fn can_parse_source_with_extents() {
+		let source = b"a\n\tb\n\t\tk: v\n\tc|\nd|\n";
+
+		let (nodes, extents) = parse_source_with_extents(&source[..]);
+
+		assert_eq!(nodes, parse_source(&source[..]));
+		assert_eq!(extents.extent_of(&nodes[0]), 0..14);
+		assert_eq!(extents.extent_of(&nodes[1]), 16..17);
+	}
 }
@@ -0,0 +1,275 @@
+//! A path names the concepts from the top level down to the target, separated by `/`. For example,
+//! `services/db` refers to the `db` concept inside the top-level `services` concept. A leading
+//! `/` is allowed and ignored.
+
+use crate::native::{format, Map, String, Vec};
+use crate::node::Node;
+use crate::diagnostic::Diagnostic;
+use crate::schema::Schema;
+use crate::tree::{RangeNode, RangeNodeQueue, resolve};
+
+/// Contains the concepts of a tree keyed by their paths.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, PathIndex};
+/// let source = b"services\n\tdb|\n\tweb|\n";
+/// let nodes = parse_source(&source[..]);
+///
+/// let index = PathIndex::new(&source[..], &nodes);
+///
+/// assert_eq!(index.get("services/db").len(), 1);
+/// assert!(index.get("db").is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PathIndex<'n> {
+	paths: Map<String, Vec<&'n RangeNode>>
+}
+
+impl<'n> PathIndex<'n> {
+	/// Indexes all concepts of the tree.
+	pub fn new(source: &[u8], nodes: &'n RangeNodeQueue) -> Self {
+		let mut index = Self::default();
+		index.add_nodes(source, nodes, "");
+		index
+	}
+
+	/// Returns the concepts with the path. Paths shared by more than one concept are ambiguous.
+	pub fn get(&self, path: &str) -> &[&'n RangeNode] {
+		let path = path.strip_prefix('/').unwrap_or(path);
+		self.paths.get(path).map(Vec::as_slice).unwrap_or(&[])
+	}
+
+	/// Returns the paths and their concepts in no particular order.
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &[&'n RangeNode])> {
+		self.paths.iter().map(|(path, nodes)| (path.as_str(), nodes.as_slice()))
+	}
+
+	/// Returns the number of distinct paths.
+	pub fn len(&self) -> usize {
+		self.paths.len()
+	}
+
+	/// Checks if there are no paths.
+	pub fn is_empty(&self) -> bool {
+		self.paths.is_empty()
+	}
+
+	fn add_nodes(&mut self, source: &[u8], nodes: &'n RangeNodeQueue, parent_path: &str) {
+		for node in nodes {
+			let (concept, content) = match node {
+				Node::Complex(concept, _, content) => (concept, Some(content)),
+				Node::Simplex(concept, _) => (concept, None),
+				_ => continue
+			};
+			let name = String::from_utf8_lossy(resolve(source, concept));
+			let path = if parent_path.is_empty() {
+				name.into_owned()
+			} else {
+				format!("{}/{}", parent_path, name)
+			};
+
+			if let Some(content) = content {
+				self.add_nodes(source, content, &path);
+			}
+
+			self.paths.entry(path).or_default().push(node);
+		}
+	}
+}
+
+/// Contains the attachers whose contents are paths.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReferenceConfig {
+	/// Labels of the reference attachers, paired with the concepts where they apply. Labels
+	/// without a concept apply to all concepts.
+	attachers: Vec<(Option<String>, String)>
+}
+
+impl ReferenceConfig {
+	/// Creates a configuration without any reference attacher.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Uses the attachers whose type is `reference` in the schema.
+	pub fn from_schema(schema: &Schema) -> Self {
+		let attachers = schema.reference_attachers()
+			.into_iter()
+			.map(|(concept, label)| (Some(concept), label))
+			.collect();
+		Self { attachers }
+	}
+
+	/// Treats the attachers with the label as references in all concepts.
+	pub fn add_label(&mut self, label: &str) -> &mut Self {
+		self.attachers.push((None, label.into()));
+		self
+	}
+
+	/// Treats the attachers with the label as references in the concept.
+	pub fn add_concept_label(&mut self, concept: &str, label: &str) -> &mut Self {
+		self.attachers.push((Some(concept.into()), label.into()));
+		self
+	}
+
+	fn is_reference(&self, concept: &[u8], label: &[u8]) -> bool {
+		self.attachers.iter().any(|(reference_concept, reference_label)| {
+			let is_in_concept = reference_concept.as_ref()
+				.map(|reference_concept| reference_concept.as_bytes() == concept)
+				.unwrap_or(true);
+			is_in_concept && reference_label.as_bytes() == label
+		})
+	}
+}
+
+/// Represents a resolved reference.
+#[derive(Debug, Clone, Copy)]
+pub struct Reference<'n> {
+	/// Concept that has the attacher.
+	pub from: &'n RangeNode,
+	/// Attacher whose content is the path.
+	pub attacher: &'n RangeNode,
+	/// Concept that the path names.
+	pub target: &'n RangeNode
+}
+
+/// Contains the resolved references of a tree and the problems found while resolving them.
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceGraph<'n> {
+	/// References that resolved to exactly one concept.
+	pub references: Vec<Reference<'n>>,
+	/// Problems with references that match no concept or more than one concept.
+	pub diagnostics: Vec<Diagnostic>
+}
+
+impl<'n> ReferenceGraph<'n> {
+	/// Returns the references whose attachers belong to the concept.
+	pub fn references_from<'g>(&'g self, node: &'g RangeNode)
+	-> impl Iterator<Item = &'g Reference<'n>> {
+		self.references.iter().filter(move |reference| core::ptr::eq(reference.from, node))
+	}
+
+	/// Returns the references that target the concept.
+	pub fn references_to<'g>(&'g self, node: &'g RangeNode)
+	-> impl Iterator<Item = &'g Reference<'n>> {
+		self.references.iter().filter(move |reference| core::ptr::eq(reference.target, node))
+	}
+}
+
+/// Resolves the reference attachers of the tree to the concepts that their paths name.
+///
+/// References that match no concept or more than one concept are reported.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, resolve_references, ReferenceConfig};
+/// use chearmyp_node::Node;
+/// let source = b"services\n\tdb|\n\tweb|\n\t\tdepends_on: services/db\n";
+/// let nodes = parse_source(&source[..]);
+/// let mut config = ReferenceConfig::new();
+/// config.add_label("depends_on");
+///
+/// let graph = resolve_references(&source[..], &nodes, &config);
+///
+/// assert!(graph.diagnostics.is_empty());
+/// let target = graph.references[0].target;
+/// assert!(matches!(target, Node::Simplex(concept, _) if &source[concept.clone()] == b"db"));
+/// ```
+pub fn resolve_references<'n>(source: &[u8], nodes: &'n RangeNodeQueue, config: &ReferenceConfig)
+-> ReferenceGraph<'n> {
+	let index = PathIndex::new(source, nodes);
+	let mut graph = ReferenceGraph::default();
+	resolve_nodes(source, nodes, config, &index, &mut graph);
+	graph
+}
+
+fn resolve_nodes<'n>(
+	source: &[u8],
+	nodes: &'n RangeNodeQueue,
+	config: &ReferenceConfig,
+	index: &PathIndex<'n>,
+	graph: &mut ReferenceGraph<'n>
+) {
+	for node in nodes {
+		let (concept, attachers, content) = match node {
+			Node::Complex(concept, attachers, content) => (concept, attachers, Some(content)),
+			Node::Simplex(concept, attachers) => (concept, attachers, None),
+			_ => continue
+		};
+		let concept = resolve(source, concept);
+
+		for attacher in attachers {
+			let (label, path) = match attacher {
+				Node::Attacher(label, path, _) => (label, path),
+				_ => continue
+			};
+
+			if !config.is_reference(concept, resolve(source, label)) {
+				continue;
+			}
+
+			let path_text = String::from_utf8_lossy(resolve(source, path));
+			match index.get(path_text.trim()) {
+				[target] => graph.references.push(Reference { from: node, attacher, target: *target }),
+				[] => {
+					let message = format!("reference `{}` does not match any concept", path_text);
+					let diagnostic = Diagnostic::error(path.clone(), "dangling_reference", message);
+					graph.diagnostics.push(diagnostic);
+				},
+				targets => {
+					let message = format!(
+						"reference `{}` matches {} concepts",
+						path_text,
+						targets.len()
+					);
+					let diagnostic = Diagnostic::error(path.clone(), "ambiguous_reference", message);
+					graph.diagnostics.push(diagnostic);
+				}
+			}
+		}
+
+		if let Some(content) = content {
+			resolve_nodes(source, content, config, index, graph);
+		}
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::Vec;
+	use crate::parse::parse_source;
+	use crate::schema::Schema;
+	use crate::tree::resolve;
+	use super::{ReferenceConfig, resolve_references};
+
+	#[test]
+	fn can_build_reference_graph() {
+		let source = b"a\n\tb|\nc\n\tuses: a/b\n\tuses: /a\n\tnote: a/b\n";
+		let nodes = parse_source(&source[..]);
+		let mut config = ReferenceConfig::new();
+		config.add_concept_label("c", "uses");
+
+		let graph = resolve_references(&source[..], &nodes, &config);
+
+		assert!(graph.diagnostics.is_empty());
+		assert_eq!(graph.references.len(), 2);
+		assert_eq!(graph.references_from(&nodes[1]).count(), 2);
+		assert_eq!(graph.references_to(&nodes[0]).count(), 1);
+	}
+
+	#[test]
+	fn can_report_unresolved_references() {
+		let schema = Schema::from_source(b"concept\n\tname: x\n\tattacher\n\t\tlabel: to\n\t\t\
+			type: reference\n").unwrap();
+		let source = b"y|\ny|\nx\n\tto: y\n\tto: z\n";
+		let nodes = parse_source(&source[..]);
+
+		let graph = resolve_references(&source[..], &nodes, &ReferenceConfig::from_schema(&schema));
+
+		let codes = graph.diagnostics.iter()
+			.map(|diagnostic| (diagnostic.code, resolve(&source[..], &diagnostic.span)))
+			.collect::<Vec<_>>();
+		assert_eq!(codes, [("ambiguous_reference", &b"y"[..]), ("dangling_reference", &b"z"[..])]);
+	}
+}
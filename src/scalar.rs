@@ -0,0 +1,328 @@
+//! Contents of attachers are interpreted the same way in either of the two modes:
+//!
+//! | Value    | Strict mode                 | Lenient mode also accepts                        |
+//! |----------|-----------------------------|--------------------------------------------------|
+//! | Integer  | `-12`, `340`                | spaces around, `+12`, `1_000`                    |
+//! | Float    | `-1.5`, `2`, `6.02e23`      | spaces around, `+.5`, `1_000.5`, `inf`           |
+//! | Boolean  | `true`, `false`             | `yes`, `no`, `on`, `off`, `1`, `0` in any case   |
+//! | List     | items that are not empty    | empty items which are skipped                    |
+//! | Duration | `1500ms`, `30s`, `1h30m`    | spaces, fractions like `1.5h`, and bare seconds  |
+//!
+//! Items of lists are trimmed of surrounding spaces in both modes and keep their own spans.
+
+use core::fmt::{Display, Formatter, Result as FormatResult};
+use core::time::Duration;
+use crate::native::{format, Range, String, Vec};
+use crate::node::Node;
+use crate::diagnostic::Diagnostic;
+use crate::tree::{RangeNode, resolve};
+
+/// Represents how strictly the values are interpreted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValueMode {
+	#[default]
+	Strict,
+	Lenient
+}
+
+/// Refers to the content of an attacher so that it can be interpreted as a typed value.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, ScalarValue};
+/// use chearmyp_node::Node;
+/// let source = b"server\n\tport: 80\n";
+/// let nodes = parse_source(&source[..]);
+///
+/// let port = match &nodes[0] {
+/// 	Node::Complex(_, attachers, _) => ScalarValue::of(&source[..], &attachers[0]).unwrap(),
+/// 	_ => unreachable!()
+/// };
+///
+/// assert_eq!(port.as_i64(), Ok(80));
+/// assert!(port.as_bool().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScalarValue<'s> {
+	pub text: &'s [u8],
+	pub span: Range<usize>,
+	pub mode: ValueMode
+}
+
+/// Represents the error found when interpreting a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueError {
+	pub span: Range<usize>,
+	pub message: String
+}
+
+impl Display for ValueError {
+	fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+		write!(formatter, "{} at bytes {}..{}", self.message, self.span.start, self.span.end)
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for ValueError {}
+
+impl From<ValueError> for Diagnostic {
+	fn from(error: ValueError) -> Self {
+		Diagnostic::error(error.span, "invalid_value", error.message)
+	}
+}
+
+impl<'s> ScalarValue<'s> {
+	/// Creates a value in strict mode.
+	pub fn new(text: &'s [u8], span: Range<usize>) -> Self {
+		Self { text, span, mode: ValueMode::Strict }
+	}
+
+	/// Refers to the content of an attacher. Other kinds of nodes have no values.
+	pub fn of(source: &'s [u8], node: &RangeNode) -> Option<Self> {
+		match node {
+			Node::Attacher(_, content, _) => {
+				Some(Self::new(resolve(source, content), content.clone()))
+			},
+			_ => None
+		}
+	}
+
+	/// Returns the same value in lenient mode.
+	pub fn lenient(self) -> Self {
+		Self { mode: ValueMode::Lenient, ..self }
+	}
+
+	/// Returns the value as a string.
+	pub fn as_str(&self) -> Result<&'s str, ValueError> {
+		core::str::from_utf8(self.text).map_err(|_| self.error("expected valid UTF-8".into()))
+	}
+
+	/// Returns the value as a decimal integer.
+	pub fn as_i64(&self) -> Result<i64, ValueError> {
+		let text = self.normalized_number()?;
+		let digits = text.strip_prefix('-').unwrap_or(&text);
+
+		if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+			return Err(self.expected("an integer"));
+		}
+
+		text.parse().map_err(|_| self.error(format!("integer `{}` is out of range", text)))
+	}
+
+	/// Returns the value as a float.
+	pub fn as_f64(&self) -> Result<f64, ValueError> {
+		let text = self.normalized_number()?;
+		let is_strictly_formed = {
+			let digits = text.strip_prefix('-').unwrap_or(&text);
+			digits.starts_with(|character: char| character.is_ascii_digit())
+				&& digits.bytes().all(|byte| byte.is_ascii_digit() || b".eE+-".contains(&byte))
+		};
+
+		match text.parse::<f64>() {
+			Ok(value) if self.mode == ValueMode::Lenient || is_strictly_formed => Ok(value),
+			_ => Err(self.expected("a float"))
+		}
+	}
+
+	/// Returns the value as a boolean.
+	pub fn as_bool(&self) -> Result<bool, ValueError> {
+		let text = self.trimmed()?;
+
+		match (self.mode, text) {
+			(_, "true") => Ok(true),
+			(_, "false") => Ok(false),
+			(ValueMode::Lenient, _) => {
+				let lowercase_text = text.to_ascii_lowercase();
+				match lowercase_text.as_str() {
+					"true" | "yes" | "on" | "1" => Ok(true),
+					"false" | "no" | "off" | "0" => Ok(false),
+					_ => Err(self.expected("a boolean"))
+				}
+			},
+			_ => Err(self.expected("a boolean"))
+		}
+	}
+
+	/// Splits the value into trimmed items whose mode is the same as the value.
+	pub fn as_list(&self, separator: &str) -> Result<Vec<ScalarValue<'s>>, ValueError> {
+		let text = self.as_str()?;
+		let mut items = Vec::new();
+
+		if text.trim().is_empty() {
+			return Ok(items);
+		}
+
+		let mut offset = 0;
+		for item in text.split(separator) {
+			let start = offset + (item.len() - item.trim_start().len());
+			let end = offset + item.trim_end().len();
+			offset += item.len() + separator.len();
+
+			if start >= end {
+				match self.mode {
+					ValueMode::Strict => {
+						let span = self.span.start + start..self.span.start + start;
+						return Err(ValueError { span, message: "expected an item".into() });
+					},
+					ValueMode::Lenient => continue
+				}
+			}
+
+			let span = self.span.start + start..self.span.start + end;
+			items.push(Self { text: &self.text[start..end], span, mode: self.mode });
+		}
+
+		Ok(items)
+	}
+
+	/// Returns the value as a duration which is made of numbers with units: `ms`, `s`, `m`, `h`,
+	/// or `d`.
+	pub fn as_duration(&self) -> Result<Duration, ValueError> {
+		let text = self.trimmed()?;
+		let is_lenient = self.mode == ValueMode::Lenient;
+
+		if is_lenient {
+			if let Ok(seconds) = text.parse::<f64>() {
+				return Duration::try_from_secs_f64(seconds).map_err(|_| self.expected("a duration"));
+			}
+		}
+
+		let mut rest = text;
+		let mut duration = Duration::ZERO;
+
+		if rest.is_empty() {
+			return Err(self.expected("a duration"));
+		}
+
+		while !rest.is_empty() {
+			let number_length = rest.find(|character: char| {
+				!(character.is_ascii_digit() || (is_lenient && character == '.'))
+			}).unwrap_or(rest.len());
+			let (number, unit_and_rest) = rest.split_at(number_length);
+			let unit_and_rest = if is_lenient { unit_and_rest.trim_start() } else { unit_and_rest };
+			let unit_length = unit_and_rest.find(|character: char| !character.is_ascii_alphabetic())
+				.unwrap_or(unit_and_rest.len());
+			let (unit, next_rest) = unit_and_rest.split_at(unit_length);
+
+			let component = self.duration_component(number, unit)?;
+			duration = duration.checked_add(component)
+				.ok_or_else(|| self.error(format!("duration `{}` is out of range", text)))?;
+			rest = if is_lenient { next_rest.trim_start() } else { next_rest };
+		}
+
+		Ok(duration)
+	}
+
+	fn duration_component(&self, number: &str, unit: &str) -> Result<Duration, ValueError> {
+		let milliseconds_per_unit: u64 = match unit {
+			"ms" => 1,
+			"s" => 1_000,
+			"m" => 60_000,
+			"h" => 3_600_000,
+			"d" => 86_400_000,
+			_ => return Err(self.expected("a duration"))
+		};
+
+		if number.contains('.') {
+			let value = number.parse::<f64>().map_err(|_| self.expected("a duration"))?;
+			let seconds = value * milliseconds_per_unit as f64 / 1_000.0;
+			Duration::try_from_secs_f64(seconds).map_err(|_| self.expected("a duration"))
+		} else {
+			number.parse::<u64>()
+				.ok()
+				.and_then(|value| value.checked_mul(milliseconds_per_unit))
+				.map(Duration::from_millis)
+				.ok_or_else(|| self.expected("a duration"))
+		}
+	}
+
+	fn trimmed(&self) -> Result<&'s str, ValueError> {
+		let text = self.as_str()?;
+		Ok(match self.mode {
+			ValueMode::Strict => text,
+			ValueMode::Lenient => text.trim()
+		})
+	}
+
+	fn normalized_number(&self) -> Result<String, ValueError> {
+		let text = self.trimmed()?;
+		Ok(match self.mode {
+			ValueMode::Strict => text.into(),
+			ValueMode::Lenient => {
+				let text = text.strip_prefix('+').unwrap_or(text);
+				text.chars().filter(|&character| character != '_').collect()
+			}
+		})
+	}
+
+	fn expected(&self, description: &str) -> ValueError {
+		let text = String::from_utf8_lossy(self.text);
+		self.error(format!("expected {} but found `{}`", description, text))
+	}
+
+	fn error(&self, message: String) -> ValueError {
+		ValueError { span: self.span.clone(), message }
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use core::time::Duration;
+	use crate::native::Vec;
+	use super::ScalarValue;
+
+	#[test]
+	fn can_interpret_numbers_and_booleans() {
+		let strict = |text: &'static str| ScalarValue::new(text.as_bytes(), 10..10 + text.len());
+		let lenient = |text: &'static str| strict(text).lenient();
+
+		assert_eq!(strict("-12").as_i64(), Ok(-12));
+		assert_eq!(lenient(" +1_000 ").as_i64(), Ok(1000));
+		assert_eq!(strict("6.02e23").as_f64(), Ok(6.02e23));
+		assert_eq!(lenient(".5").as_f64(), Ok(0.5));
+		assert_eq!(strict("true").as_bool(), Ok(true));
+		assert_eq!(lenient("Off").as_bool(), Ok(false));
+
+		let error = strict("+12").as_i64().unwrap_err();
+		assert_eq!(error.span, 10..13);
+		assert_eq!(error.message, "expected an integer but found `+12`");
+		assert!(strict("inf").as_f64().is_err());
+		assert!(strict("yes").as_bool().is_err());
+		assert_eq!(
+			strict("99999999999999999999").as_i64().unwrap_err().message,
+			"integer `99999999999999999999` is out of range"
+		);
+	}
+
+	#[test]
+	fn can_split_lists() {
+		let value = ScalarValue::new(b"a, b,c ,", 5..13);
+
+		let error = value.as_list(",").unwrap_err();
+		let items = value.clone().lenient().as_list(",").unwrap();
+
+		assert_eq!(error.span, 13..13);
+		assert_eq!(
+			items.iter().map(|item| item.text).collect::<Vec<_>>(),
+			[&b"a"[..], &b"b"[..], &b"c"[..]]
+		);
+		assert_eq!(
+			items.iter().map(|item| item.span.clone()).collect::<Vec<_>>(),
+			[5..6, 8..9, 10..11]
+		);
+	}
+
+	#[test]
+	fn can_interpret_durations() {
+		let strict = |text: &'static str| ScalarValue::new(text.as_bytes(), 0..text.len());
+
+		assert_eq!(strict("1h30m").as_duration(), Ok(Duration::from_secs(5_400)));
+		assert_eq!(strict("1500ms").as_duration(), Ok(Duration::from_millis(1_500)));
+		assert_eq!(strict("1.5h").lenient().as_duration(), Ok(Duration::from_secs(5_400)));
+		assert_eq!(strict("2d 1s").lenient().as_duration(), Ok(Duration::from_secs(172_801)));
+		assert_eq!(strict("90").lenient().as_duration(), Ok(Duration::from_secs(90)));
+		assert!(strict("1h 30m").as_duration().is_err());
+		assert!(strict("30").as_duration().is_err());
+		assert!(strict("5y").as_duration().is_err());
+	}
+}
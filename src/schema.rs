@@ -0,0 +1,565 @@
+//! A schema is written in Chearmyp as a list of `concept` declarations:
+//!
+//! ```text
+//! concept
+//! 	name: server
+//! 	kind: complex
+//! 	root|
+//! 	attacher
+//! 		label: port
+//! 		type: integer
+//! 		required|
+//! 	attacher
+//! 		label: mode
+//! 		choice: fast
+//! 		choice: safe
+//! concept
+//! 	name: debug
+//! 	kind: simplex
+//! 	parent: server
+//! ```
+//!
+//! A concept declaration has the following attachers and content:
+//!
+//! | Part              | Meaning                                                            |
+//! |-------------------|--------------------------------------------------------------------|
+//! | `name`            | Name of the concept. It is required.                               |
+//! | `kind`            | `simplex`, `complex`, or `any` which is the default.               |
+//! | `parent`          | Concept that the declared concept may be under. It may repeat.     |
+//! | `root|`           | Allows the concept at the top level.                               |
+//! | `attacher`        | Declaration of an allowed attacher.                                |
+//!
+//! If a concept has neither `parent` nor `root|`, it may appear anywhere. An attacher declaration
+//! has the following attachers and content:
+//!
+//! | Part              | Meaning                                                            |
+//! |-------------------|--------------------------------------------------------------------|
+//! | `label`           | Label of the attacher. It is required.                             |
+//! | `type`            | `text` by default, `integer`, `number`, `boolean`, or `reference`. |
+//! | `pattern`         | Glob that the content should match. `*` and `?` are wildcards.     |
+//! | `choice`          | Allowed content. It may repeat.                                    |
+//! | `required|`       | Requires the attacher in every instance of the concept.            |
+//!
+//! Contents of `reference` attachers are not checked by the validator. They are paths of concepts
+//! that are resolved by [`resolve_references()`](crate::resolve_references).
+
+use crate::native::{format, Range, String, Vec};
+use crate::node::Node;
+use crate::diagnostic::Diagnostic;
+use crate::attacher_map::AttacherMap;
+use crate::parse::parse_source;
+use crate::tree::{RangeNode, RangeNodeQueue, resolve, span_of};
+
+/// Contains the rules that the concepts and attachers of a document should follow.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, Schema};
+/// let schema = Schema::from_source(b"concept\n\tname: server\n\troot|\n").unwrap();
+/// let document = b"server|\nclient|\n";
+///
+/// let diagnostics = schema.validate(&document[..], &parse_source(&document[..]));
+///
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].code, "unknown_concept");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+	concepts: Vec<ConceptRule>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ConceptRule {
+	name: String,
+	kind: Kind,
+	is_root: bool,
+	parents: Vec<String>,
+	attachers: Vec<AttacherRule>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+	Any,
+	Simplex,
+	Complex
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AttacherRule {
+	label: String,
+	is_required: bool,
+	value_type: ValueType,
+	pattern: Option<String>,
+	choices: Vec<String>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueType {
+	Text,
+	Integer,
+	Number,
+	Boolean,
+	Reference
+}
+
+impl Schema {
+	/// Parses the source and reads the schema from it.
+	pub fn from_source(source: &[u8]) -> Result<Self, Vec<Diagnostic>> {
+		Self::from_nodes(source, &parse_source(source))
+	}
+
+	/// Reads the schema from parsed nodes.
+	///
+	/// All problems of the schema are returned as diagnostics with `invalid_schema` code.
+	pub fn from_nodes(source: &[u8], nodes: &RangeNodeQueue) -> Result<Self, Vec<Diagnostic>> {
+		let mut reader = SchemaReader { source, diagnostics: Vec::new() };
+		let mut concepts: Vec<ConceptRule> = Vec::new();
+
+		for node in nodes {
+			match declaration(node) {
+				Some((concept, attachers, content)) if resolve(source, concept) == b"concept" => {
+					if let Some((rule, span)) = reader.read_concept(concept, attachers, content) {
+						if concepts.iter().any(|other| other.name == rule.name) {
+							let message = format!("concept `{}` is declared more than once", rule.name);
+							reader.report(&span, message);
+						} else {
+							concepts.push(rule);
+						}
+					}
+				},
+				_ => reader.reject(node, "expected a `concept` declaration")
+			}
+		}
+
+		if reader.diagnostics.is_empty() {
+			Ok(Self { concepts })
+		} else {
+			Err(reader.diagnostics)
+		}
+	}
+
+	/// Returns the concepts and labels of the attachers whose type is `reference`.
+	pub(crate) fn reference_attachers(&self) -> Vec<(String, String)> {
+		self.concepts.iter()
+			.flat_map(|concept| {
+				concept.attachers.iter()
+					.filter(|attacher| attacher.value_type == ValueType::Reference)
+					.map(move |attacher| (concept.name.clone(), attacher.label.clone()))
+			})
+			.collect()
+	}
+
+	/// Returns the problems of the document based from the schema.
+	///
+	/// Concepts that are not declared are reported but their content are not checked.
+	pub fn validate(&self, source: &[u8], nodes: &RangeNodeQueue) -> Vec<Diagnostic> {
+		let mut diagnostics = Vec::new();
+		self.validate_nodes(source, nodes, None, &mut diagnostics);
+		diagnostics
+	}
+
+	fn validate_nodes(
+		&self,
+		source: &[u8],
+		nodes: &RangeNodeQueue,
+		parent: Option<&str>,
+		diagnostics: &mut Vec<Diagnostic>
+	) {
+		for node in nodes {
+			if let Some((concept, attachers, content)) = declaration(node) {
+				self.validate_concept(source, concept, attachers, content, parent, diagnostics);
+			}
+		}
+	}
+
+	fn validate_concept(
+		&self,
+		source: &[u8],
+		concept: &Range<usize>,
+		attachers: &RangeNodeQueue,
+		content: Option<&RangeNodeQueue>,
+		parent: Option<&str>,
+		diagnostics: &mut Vec<Diagnostic>
+	) {
+		let name = text(source, concept);
+		let rule = match self.concepts.iter().find(|rule| rule.name == name) {
+			Some(rule) => rule,
+			None => {
+				let message = format!("unknown concept `{}`", name);
+				diagnostics.push(Diagnostic::error(concept.clone(), "unknown_concept", message));
+				return;
+			}
+		};
+
+		if rule.is_root || !rule.parents.is_empty() {
+			let (is_allowed, message) = match parent {
+				Some(parent) => (
+					rule.parents.iter().any(|allowed_parent| allowed_parent == parent),
+					format!("concept `{}` may not be under `{}`", name, parent)
+				),
+				None => (rule.is_root, format!("concept `{}` may not be at the top level", name))
+			};
+
+			if !is_allowed {
+				diagnostics.push(Diagnostic::error(concept.clone(), "unexpected_parent", message));
+			}
+		}
+
+		match (rule.kind, content.is_some()) {
+			(Kind::Simplex, true) => {
+				let message = format!("concept `{}` should be a simplex", name);
+				diagnostics.push(Diagnostic::error(concept.clone(), "expected_simplex", message));
+			},
+			(Kind::Complex, false) => {
+				let message = format!("concept `{}` should be a complex", name);
+				diagnostics.push(Diagnostic::error(concept.clone(), "expected_complex", message));
+			},
+			_ => {}
+		}
+
+		for attacher in attachers {
+			if let Node::Attacher(label, value, _) = attacher {
+				let label_text = text(source, label);
+				let attacher_rule = rule.attachers.iter()
+					.find(|attacher_rule| attacher_rule.label == label_text);
+				match attacher_rule.map(|attacher_rule| attacher_rule.check(&text(source, value))) {
+					Some(Some(message)) => {
+						diagnostics.push(Diagnostic::error(value.clone(), "invalid_value", message));
+					},
+					Some(None) => {},
+					None => {
+						let message = format!("concept `{}` has no attacher `{}`", name, label_text);
+						diagnostics.push(Diagnostic::error(label.clone(), "unknown_attacher", message));
+					}
+				}
+			}
+		}
+
+		let attacher_map = AttacherMap::new(source, attachers);
+		for attacher_rule in rule.attachers.iter().filter(|attacher_rule| attacher_rule.is_required) {
+			if !attacher_map.contains(attacher_rule.label.as_bytes()) {
+				let message = format!("concept `{}` needs attacher `{}`", name, attacher_rule.label);
+				diagnostics.push(Diagnostic::error(concept.clone(), "missing_attacher", message));
+			}
+		}
+
+		if let Some(content) = content {
+			self.validate_nodes(source, content, Some(&name), diagnostics);
+		}
+	}
+}
+
+impl AttacherRule {
+	fn check(&self, value: &str) -> Option<String> {
+		if !self.choices.is_empty() && !self.choices.iter().any(|choice| choice == value) {
+			let choices = self.choices.iter()
+				.map(|choice| format!("`{}`", choice))
+				.collect::<Vec<String>>()
+				.join(", ");
+			return Some(format!("`{}` is not one of {}", value, choices));
+		}
+
+		let (is_typed, description) = match self.value_type {
+			ValueType::Text | ValueType::Reference => (true, ""),
+			ValueType::Integer => (is_integer(value), "an integer"),
+			ValueType::Number => (value.parse::<f64>().is_ok(), "a number"),
+			ValueType::Boolean => (value == "true" || value == "false", "a boolean")
+		};
+		if !is_typed {
+			return Some(format!("`{}` is not {}", value, description));
+		}
+
+		match &self.pattern {
+			Some(pattern) if !matches_glob(pattern, value) => {
+				Some(format!("`{}` does not match `{}`", value, pattern))
+			},
+			_ => None
+		}
+	}
+}
+
+struct SchemaReader<'s> {
+	source: &'s [u8],
+	diagnostics: Vec<Diagnostic>
+}
+
+impl<'s> SchemaReader<'s> {
+	fn report(&mut self, span: &Range<usize>, message: String) {
+		self.diagnostics.push(Diagnostic::error(span.clone(), "invalid_schema", message));
+	}
+
+	fn reject(&mut self, node: &RangeNode, message: &str) {
+		match node {
+			Node::LineComment(_) | Node::BlockComment(_) => {},
+			_ => self.report(&span_of(node), message.into())
+		}
+	}
+
+	fn read_concept(
+		&mut self,
+		concept: &Range<usize>,
+		attachers: &RangeNodeQueue,
+		content: Option<&RangeNodeQueue>
+	) -> Option<(ConceptRule, Range<usize>)> {
+		let mut name = None;
+		let mut kind = Kind::Any;
+		let mut parents = Vec::new();
+
+		for (label, value) in self.properties(attachers) {
+			let value_text = text(self.source, &value);
+			match &*text(self.source, &label) {
+				"name" => name = Some((value_text, value)),
+				"kind" => kind = match &*value_text {
+					"any" => Kind::Any,
+					"simplex" => Kind::Simplex,
+					"complex" => Kind::Complex,
+					_ => {
+						self.report(&value, format!("unknown kind `{}`", value_text));
+						Kind::Any
+					}
+				},
+				"parent" => parents.push(value_text),
+				property => {
+					let message = format!("unknown property `{}` of a concept", property);
+					self.report(&label, message);
+				}
+			}
+		}
+
+		let mut is_root = false;
+		let mut attacher_rules: Vec<AttacherRule> = Vec::new();
+
+		for node in content.into_iter().flatten() {
+			match declaration(node) {
+				Some((flag, flag_attachers, None)) if resolve(self.source, flag) == b"root" => {
+					is_root = flag_attachers.is_empty();
+					if !is_root {
+						self.report(flag, "`root|` does not have attachers".into());
+					}
+				},
+				Some((declared, declared_attachers, declared_content))
+				if resolve(self.source, declared) == b"attacher" => {
+					let rule = self.read_attacher(declared, declared_attachers, declared_content);
+					if let Some(rule) = rule {
+						if attacher_rules.iter().any(|other| other.label == rule.label) {
+							let message = format!("attacher `{}` is declared more than once", rule.label);
+							self.report(declared, message);
+						} else {
+							attacher_rules.push(rule);
+						}
+					}
+				},
+				_ => self.reject(node, "expected `root|` or an `attacher` declaration")
+			}
+		}
+
+		match name {
+			Some((name, span)) => {
+				let rule = ConceptRule { name, kind, is_root, parents, attachers: attacher_rules };
+				Some((rule, span))
+			},
+			None => {
+				self.report(concept, "concept declaration needs a `name`".into());
+				None
+			}
+		}
+	}
+
+	fn read_attacher(
+		&mut self,
+		attacher: &Range<usize>,
+		attachers: &RangeNodeQueue,
+		content: Option<&RangeNodeQueue>
+	) -> Option<AttacherRule> {
+		let mut label = None;
+		let mut value_type = ValueType::Text;
+		let mut pattern = None;
+		let mut choices = Vec::new();
+
+		for (property_label, value) in self.properties(attachers) {
+			let value_text = text(self.source, &value);
+			match &*text(self.source, &property_label) {
+				"label" => label = Some(value_text),
+				"type" => value_type = match &*value_text {
+					"text" => ValueType::Text,
+					"integer" => ValueType::Integer,
+					"number" => ValueType::Number,
+					"boolean" => ValueType::Boolean,
+					"reference" => ValueType::Reference,
+					_ => {
+						self.report(&value, format!("unknown type `{}`", value_text));
+						ValueType::Text
+					}
+				},
+				"pattern" => pattern = Some(value_text),
+				"choice" => choices.push(value_text),
+				property => {
+					let message = format!("unknown property `{}` of an attacher", property);
+					self.report(&property_label, message);
+				}
+			}
+		}
+
+		let mut is_required = false;
+
+		for node in content.into_iter().flatten() {
+			match declaration(node) {
+				Some((flag, flag_attachers, None))
+				if resolve(self.source, flag) == b"required" && flag_attachers.is_empty() => {
+					is_required = true;
+				},
+				_ => self.reject(node, "expected `required|`")
+			}
+		}
+
+		match label {
+			Some(label) => Some(AttacherRule { label, is_required, value_type, pattern, choices }),
+			None => {
+				self.report(attacher, "attacher declaration needs a `label`".into());
+				None
+			}
+		}
+	}
+
+	fn properties(&self, attachers: &RangeNodeQueue) -> Vec<(Range<usize>, Range<usize>)> {
+		attachers.iter().filter_map(|attacher| match attacher {
+			Node::Attacher(label, value, _) => Some((label.clone(), value.clone())),
+			_ => None
+		}).collect()
+	}
+}
+
+fn declaration(node: &RangeNode)
+-> Option<(&Range<usize>, &RangeNodeQueue, Option<&RangeNodeQueue>)> {
+	match node {
+		Node::Complex(concept, attachers, content) => Some((concept, attachers, Some(content))),
+		Node::Simplex(concept, attachers) => Some((concept, attachers, None)),
+		_ => None
+	}
+}
+
+fn text(source: &[u8], boundary: &Range<usize>) -> String {
+	String::from_utf8_lossy(resolve(source, boundary)).into_owned()
+}
+
+fn is_integer(value: &str) -> bool {
+	let digits = value.strip_prefix(['+', '-']).unwrap_or(value);
+	!digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+fn matches_glob(pattern: &str, text: &str) -> bool {
+	let pattern = pattern.chars().collect::<Vec<char>>();
+	let text = text.chars().collect::<Vec<char>>();
+	let mut pattern_index = 0;
+	let mut text_index = 0;
+	let mut last_star = None;
+
+	while text_index < text.len() {
+		match pattern.get(pattern_index) {
+			Some('*') => {
+				last_star = Some((pattern_index, text_index));
+				pattern_index += 1;
+			},
+			Some(&character) if character == '?' || character == text[text_index] => {
+				pattern_index += 1;
+				text_index += 1;
+			},
+			_ => match last_star {
+				Some((star_index, star_text_index)) => {
+					pattern_index = star_index + 1;
+					text_index = star_text_index + 1;
+					last_star = Some((star_index, star_text_index + 1));
+				},
+				None => return false
+			}
+		}
+	}
+
+	pattern[pattern_index..].iter().all(|&character| character == '*')
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::Vec;
+	use crate::parse::parse_source;
+	use crate::tree::resolve;
+	use super::{Schema, matches_glob};
+
+	const SCHEMA: &[u8] = b"concept
+	name: server
+	kind: complex
+	root|
+	attacher
+		label: port
+		type: integer
+		required|
+	attacher
+		label: mode
+		choice: fast
+		choice: safe
+concept
+	name: debug
+	kind: simplex
+	parent: server
+";
+
+	#[test]
+	fn can_validate_document() {
+		let schema = Schema::from_source(SCHEMA).unwrap();
+		let document = b"server\n\tport: eighty\n\tmode: slow\n\tdebug|\n\tdebug\n\t\textra|\n\
+			debug|\ntypo|\n";
+
+		let diagnostics = schema.validate(&document[..], &parse_source(&document[..]));
+
+		let codes = diagnostics.iter().map(|diagnostic| diagnostic.code).collect::<Vec<_>>();
+		assert_eq!(codes, [
+			"invalid_value",
+			"invalid_value",
+			"expected_simplex",
+			"unknown_concept",
+			"unexpected_parent",
+			"unknown_concept"
+		]);
+		assert_eq!(resolve(&document[..], &diagnostics[0].span), b"eighty");
+		assert_eq!(diagnostics[1].message, "`slow` is not one of `fast`, `safe`");
+		assert_eq!(resolve(&document[..], &diagnostics[5].span), b"typo");
+	}
+
+	#[test]
+	fn can_report_missing_attachers() {
+		let schema = Schema::from_source(SCHEMA).unwrap();
+		let document = b"server\n\tmode: fast\n\tcolor: red\n";
+
+		let diagnostics = schema.validate(&document[..], &parse_source(&document[..]));
+
+		let codes = diagnostics.iter().map(|diagnostic| diagnostic.code).collect::<Vec<_>>();
+		assert_eq!(codes, ["unknown_attacher", "missing_attacher"]);
+		assert_eq!(diagnostics[1].message, "concept `server` needs attacher `port`");
+	}
+
+	#[test]
+	fn cannot_read_invalid_schema() {
+		let source = b"concept\n\tkind: weird\n\tcolor: red\nstray|\n";
+
+		let diagnostics = Schema::from_source(&source[..]).unwrap_err();
+
+		let messages = diagnostics.iter()
+			.map(|diagnostic| diagnostic.message.as_str())
+			.collect::<Vec<_>>();
+		assert_eq!(messages, [
+			"unknown kind `weird`",
+			"unknown property `color` of a concept",
+			"concept declaration needs a `name`",
+			"expected a `concept` declaration"
+		]);
+	}
+
+	#[test]
+	fn can_match_globs() {
+		assert!(matches_glob("v*.?", "v1.2"));
+		assert!(matches_glob("*", ""));
+		assert!(matches_glob("a*b*c", "aXbYbZc"));
+		assert!(!matches_glob("a?c", "ac"));
+		assert!(!matches_glob("*.toml", "config.yaml"));
+	}
+}
@@ -1,7 +1,7 @@
 mod fragment;
 mod relationship;
 
-use crate::native::{Vec, PhantomData};
+use crate::native::{Range, Vec, PhantomData};
 use fragment::Fragment;
 use relationship::Relationship;
 
@@ -10,6 +10,12 @@ pub struct ScopeStack<T, U, V, W, X, Y> {
 	last_relationship: Relationship,
 	fragments: Vec<Fragment<T, V, W>>,
 	scopes: Vec<Y>,
+	/// Reads the span of a node if extents of concepts are recorded while they are promoted.
+	span_reader: Option<fn(&X) -> Range<usize>>,
+	/// End of the latest node that has been pushed while recording extents.
+	last_end: usize,
+	/// Names of promoted concepts paired with the boundaries that cover their descendants.
+	extents: Vec<(Range<usize>, Range<usize>)>,
 	_abstract_boundary: PhantomData<T>,
 	_abstract_boundary_collection: PhantomData<U>,
 	_abstract_attacher_node: PhantomData<V>,
@@ -25,6 +31,7 @@ mod promote_last_fragment;
 mod necessarily_promote_last_fragments;
 mod minimize_scope_level_by;
 mod finalize;
+mod record_extents;
 mod appenders;
 
 use crate::abstracts::{
@@ -58,6 +65,9 @@ where
 			last_relationship,
 			fragments,
 			scopes,
+			span_reader: None,
+			last_end: 0,
+			extents: Vec::new(),
 			_abstract_boundary: PhantomData,
 			_abstract_boundary_collection: PhantomData,
 			_abstract_attacher_node: PhantomData,
@@ -6,6 +6,7 @@ use crate::abstracts::{
 	AbstractAttacherCollection,
 	AbstractAttacherNode
 };
+use crate::native::{Range, Vec};
 use super::ScopeStack;
 
 impl<T, U, V, W, X, Y> ScopeStack<T, U, V, W, X, Y>
@@ -21,6 +22,12 @@ where
 		self.minimize_scope_level_by(0);
 		self.scopes.pop().unwrap()
 	}
+
+	/// Returns the topmost scope with the extents that have been recorded.
+	pub fn finalize_with_extents(mut self) -> (Y, Vec<(Range<usize>, Range<usize>)>) {
+		self.minimize_scope_level_by(0);
+		(self.scopes.pop().unwrap(), self.extents)
+	}
 }
 
 #[cfg(test)]
@@ -32,6 +32,14 @@ where
 			}
 		}
 
+		if let Some(span_reader) = self.span_reader {
+			let span = span_reader(&node);
+			if !span.is_empty() {
+				let end = span.end.max(self.last_end);
+				self.extents.push((span.clone(), span.start..end));
+			}
+		}
+
 		self.push_to_preferred_relationship(node);
 	}
 }
@@ -111,4 +119,27 @@ mod t {
 		assert_eq!(scope_stack.fragments, Vec::new());
 		assert_eq!(scope_stack.scopes, expected_scopes);
 	}
+
+	#[test]
+	fn can_record_extents_while_promoting() {
+		let mut scope_stack = ScopeStack::<
+			Range<usize>,
+			Vec<Range<usize>>,
+			Node<Range<usize>, Vec<Range<usize>>>,
+			VecDeque<Node<Range<usize>, Vec<Range<usize>>>>,
+			Node<Range<usize>, Vec<Range<usize>>>,
+			VecDeque<Node<Range<usize>, Vec<Range<usize>>>>
+		>::new();
+		scope_stack.record_extents(crate::tree::span_of);
+		scope_stack.fragments.push(Fragment::new_complex(0..1, VecDeque::new()));
+		scope_stack.scopes.push(VecDeque::new());
+		scope_stack.fragments.push(Fragment::new_simplex(3..4, VecDeque::new()));
+		scope_stack.push_to_preferred_relationship(Node::Attacher(6..7, 9..10, Vec::new()));
+
+		scope_stack.promote_last_fragment();
+		scope_stack.push_to_preferred_relationship(Node::LineComment(12..15));
+		scope_stack.promote_last_fragment();
+
+		assert_eq!(scope_stack.extents, [(3..4, 3..10), (0..1, 0..15)]);
+	}
 }
@@ -19,6 +19,10 @@ where
 	X: AbstractNode<usize, T, usize, T, U, V, W, X, Y>,
 	Y: AbstractNodeQueue<X> {
 	pub fn push_to_preferred_relationship(&mut self, node: X) {
+		if let Some(span_reader) = self.span_reader {
+			self.last_end = self.last_end.max(span_reader(&node).end);
+		}
+
 		match node.kind() {
 			NodeKind::Complex
 			| NodeKind::Simplex
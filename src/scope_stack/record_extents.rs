@@ -0,0 +1,27 @@
+use crate::abstracts::{
+	AbstractBoundary,
+	AbstractBoundaryCollection,
+	AbstractNode,
+	AbstractNodeQueue,
+	AbstractAttacherCollection,
+	AbstractAttacherNode
+};
+use crate::native::Range;
+use super::ScopeStack;
+
+impl<T, U, V, W, X, Y> ScopeStack<T, U, V, W, X, Y>
+where
+	T: AbstractBoundary<usize>,
+	U: AbstractBoundaryCollection<usize, T>,
+	V: AbstractAttacherNode,
+	W: AbstractAttacherCollection<V>,
+	X: AbstractNode<usize, T, usize, T, U, V, W, X, Y>,
+	Y: AbstractNodeQueue<X> {
+	/// Records the extent of each concept when its fragment is promoted.
+	///
+	/// Nodes arrive in the order of the source so the extent of a concept ends where the latest
+	/// pushed node ends. The span reader is needed since abstract boundaries cannot be read.
+	pub fn record_extents(&mut self, span_reader: fn(&X) -> Range<usize>) {
+		self.span_reader = Some(span_reader);
+	}
+}
@@ -0,0 +1,132 @@
+//! A selection grows through the boundaries that enclose the offset, from the innermost to the
+//! whole source:
+//!
+//! | Node     | Boundaries in order                                                           |
+//! |----------|-------------------------------------------------------------------------------|
+//! | Attacher | label or content, then the whole attacher                                     |
+//! | Complex  | name, then the header which is the name with the attachers, then the extent   |
+//! | Simplex  | name, then the extent which is the name with the attachers                    |
+//! | Block    | line, then all lines                                                          |
+//! | Line     | line                                                                          |
+//!
+//! The extent of a concept covers its descendants. Extents are the ones that the parser recorded
+//! while promoting concepts, as [`parse_source_with_extents()`] returns them. Fences of blocks
+//! have no boundaries so only their enclosing concepts contain them. Nodes with empty spans, such
+//! as blocks without lines, are never selected.
+//!
+//! [`parse_source_with_extents()`]: crate::parse_source_with_extents
+
+use crate::native::{Range, Vec};
+use crate::node::Node;
+use crate::tree::{Extents, RangeNodeQueue, span_of};
+
+/// Returns the boundaries that contain the offset, each enclosing the ones before it.
+///
+/// An offset at the end of a boundary is contained by it so that a cursor right after a name still
+/// selects the name. The last boundary always covers the whole source.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source_with_extents, selection_ranges};
+/// let source = b"server\n\tport: 80\n\troute|\n";
+/// let (nodes, extents) = parse_source_with_extents(&source[..]);
+///
+/// let ranges = selection_ranges(&source[..], &nodes, &extents, 15);
+///
+/// assert_eq!(ranges, [14..16, 8..16, 0..16, 0..23, 0..25]);
+/// ```
+pub fn selection_ranges(source: &[u8], nodes: &RangeNodeQueue, extents: &Extents, offset: usize)
+-> Vec<Range<usize>> {
+	let mut ranges = Vec::new();
+	collect(nodes, extents, offset, &mut ranges);
+	ranges.push(0..source.len());
+	ranges.retain(|range| contains(range, offset));
+	ranges.dedup();
+	ranges
+}
+
+/// Pushes the boundaries of the innermost node first and of its ancestors after.
+fn collect(
+	nodes: &RangeNodeQueue,
+	extents: &Extents,
+	offset: usize,
+	ranges: &mut Vec<Range<usize>>
+) {
+	let node = nodes.iter()
+		.filter(|node| !span_of(node).is_empty())
+		.find(|node| contains(&extents.extent_of(node), offset));
+	let node = match node {
+		Some(node) => node,
+		None => return
+	};
+
+	match node {
+		Node::Complex(concept, attachers, content) => {
+			collect(attachers, extents, offset, ranges);
+			collect(content, extents, offset, ranges);
+			let header_end = attachers.iter()
+				.map(|attacher| span_of(attacher).end)
+				.fold(concept.end, usize::max);
+			ranges.push(concept.clone());
+			ranges.push(concept.start..header_end);
+			ranges.push(extents.extent_of(node));
+		},
+		Node::Simplex(concept, attachers) => {
+			collect(attachers, extents, offset, ranges);
+			ranges.push(concept.clone());
+			ranges.push(extents.extent_of(node));
+		},
+		Node::Attacher(label, content, _) => {
+			ranges.push(label.clone());
+			ranges.push(content.clone());
+			ranges.push(span_of(node));
+		},
+		Node::LineComment(line) | Node::LineOthertongue(line) => ranges.push(line.clone()),
+		Node::BlockComment(lines) | Node::BlockOthertongue(lines) => {
+			ranges.extend(lines.iter().find(|line| contains(line, offset)).cloned());
+			ranges.push(span_of(node));
+		}
+	}
+}
+
+fn contains(range: &Range<usize>, offset: usize) -> bool {
+	range.start <= offset && offset <= range.end
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use crate::parse::parse_source_with_extents;
+	use crate::tree::Extents;
+	use super::selection_ranges;
+
+	#[test]
+	fn can_expand_selection_through_ancestors() {
+		let source = b"a\n\tk: v\n\tb|\n";
+		let (nodes, extents) = parse_source_with_extents(&source[..]);
+		let (empty_nodes, empty_extents) = parse_source_with_extents(b"");
+
+		assert_eq!(selection_ranges(&source[..], &nodes, &extents, 6), [
+			6..7,
+			3..7,
+			0..7,
+			0..10,
+			0..12
+		]);
+		assert_eq!(selection_ranges(&source[..], &nodes, &extents, 9), [9..10, 0..10, 0..12]);
+		assert_eq!(selection_ranges(&b""[..], &empty_nodes, &empty_extents, 0), [0..0]);
+	}
+
+	#[test]
+	fn cannot_select_nodes_with_empty_spans() {
+		let source = b"a|";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::BlockComment(Vec::new()));
+		nodes.push_back(Node::Simplex(0..1, VecDeque::new()));
+
+		let ranges = selection_ranges(&source[..], &nodes, &Extents::default(), 0);
+
+		assert_eq!(ranges, [0..1, 0..2]);
+	}
+}
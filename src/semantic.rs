@@ -0,0 +1,312 @@
+//! Every concept, attacher, comment, and othertongue is classified by its boundaries. Markers and
+//! fences, such as `|`, `#`, and `===`, have no boundaries so they are not classified. Each line of
+//! a block is a separate token so that no token spans many lines.
+//!
+//! | Modifier        | Applies to                                                         |
+//! |-----------------|--------------------------------------------------------------------|
+//! | `DOCUMENTATION` | comments that document a concept as [`attach_doc_comments()`] sees |
+//! | `ORPHAN`        | attachers among content and othertongues without a concept above   |
+//!
+//! The encoder follows the Language Server Protocol. Each token is encoded as five integers whose
+//! line and character are relative to the previous token. Positions count UTF-16 code units.
+//!
+//! [`attach_doc_comments()`]: crate::attach_doc_comments
+
+use core::ops::BitOr;
+use crate::native::{Range, Vec};
+use crate::node::Node;
+use crate::doc_comment::attach_doc_comments;
+use crate::line_index::{LineIndex, Position};
+use crate::lint::for_each_siblings;
+use crate::tree::RangeNodeQueue;
+
+/// Token types of the legend in the order of the indices that the encoder uses.
+pub const SEMANTIC_TOKEN_TYPES: [&str; 6] = [
+	"namespace",
+	"enumMember",
+	"property",
+	"string",
+	"comment",
+	"macro"
+];
+
+/// Token modifiers of the legend in the order of the bits that the encoder uses.
+pub const SEMANTIC_TOKEN_MODIFIERS: [&str; 2] = ["documentation", "orphan"];
+
+/// Represents what a token is in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SemanticKind {
+	ComplexName,
+	SimplexName,
+	AttacherLabel,
+	AttacherContent,
+	LineComment,
+	BlockComment,
+	LineOthertongue,
+	BlockOthertongue
+}
+
+impl SemanticKind {
+	/// Returns the index of the token type in [`SEMANTIC_TOKEN_TYPES`].
+	pub fn token_type(&self) -> usize {
+		match self {
+			Self::ComplexName => 0,
+			Self::SimplexName => 1,
+			Self::AttacherLabel => 2,
+			Self::AttacherContent => 3,
+			Self::LineComment | Self::BlockComment => 4,
+			Self::LineOthertongue | Self::BlockOthertongue => 5
+		}
+	}
+}
+
+/// Represents a set of modifiers of a token.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SemanticModifiers(u32);
+
+impl SemanticModifiers {
+	pub const NONE: Self = Self(0);
+	pub const DOCUMENTATION: Self = Self(1);
+	pub const ORPHAN: Self = Self(1 << 1);
+
+	/// Returns the bits whose positions are the indices in [`SEMANTIC_TOKEN_MODIFIERS`].
+	pub fn bits(&self) -> u32 {
+		self.0
+	}
+
+	/// Checks if all of the modifiers are in the set.
+	pub fn contains(&self, modifiers: Self) -> bool {
+		self.0 & modifiers.0 == modifiers.0
+	}
+}
+
+impl BitOr for SemanticModifiers {
+	type Output = Self;
+
+	fn bitor(self, modifiers: Self) -> Self {
+		Self(self.0 | modifiers.0)
+	}
+}
+
+/// Represents a classified boundary in the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticToken {
+	/// Boundary of the token in the source.
+	pub span: Range<usize>,
+	/// What the token is.
+	pub kind: SemanticKind,
+	/// Additional traits of the token.
+	pub modifiers: SemanticModifiers
+}
+
+/// Represents a replacement of integers in encoded tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticTokensEdit {
+	/// Index of the first replaced integer.
+	pub start: usize,
+	/// Number of replaced integers.
+	pub delete_count: usize,
+	/// Integers that replace the deleted ones.
+	pub data: Vec<u32>
+}
+
+/// Returns the tokens of the nodes sorted by their boundaries.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, semantic_tokens, SemanticKind, SemanticModifiers};
+/// let source = b"# Serves files.\nserver\n\tport: 80\n";
+/// let nodes = parse_source(&source[..]);
+///
+/// let tokens = semantic_tokens(&source[..], &nodes);
+///
+/// assert_eq!(tokens[0].kind, SemanticKind::LineComment);
+/// assert!(tokens[0].modifiers.contains(SemanticModifiers::DOCUMENTATION));
+/// assert_eq!(tokens[1].kind, SemanticKind::ComplexName);
+/// assert_eq!(tokens[3].span, 30..32);
+/// ```
+pub fn semantic_tokens(source: &[u8], nodes: &RangeNodeQueue) -> Vec<SemanticToken> {
+	let doc_lines = attach_doc_comments(source, nodes).1
+		.iter()
+		.flat_map(|(_, lines)| lines.iter().cloned())
+		.collect::<Vec<_>>();
+	let mut tokens = Vec::new();
+
+	for_each_siblings(nodes, &mut |siblings| {
+		for node in siblings.nodes {
+			let mut push = |span: &Range<usize>, kind, modifiers| {
+				if !span.is_empty() {
+					tokens.push(SemanticToken { span: span.clone(), kind, modifiers });
+				}
+			};
+			let documentation = |line: &Range<usize>| {
+				if doc_lines.contains(line) {
+					SemanticModifiers::DOCUMENTATION
+				} else {
+					SemanticModifiers::NONE
+				}
+			};
+			let orphan = |is_orphan: bool| {
+				if is_orphan { SemanticModifiers::ORPHAN } else { SemanticModifiers::NONE }
+			};
+
+			match node {
+				Node::Complex(concept, _, _) => {
+					push(concept, SemanticKind::ComplexName, SemanticModifiers::NONE);
+				},
+				Node::Simplex(concept, _) => {
+					push(concept, SemanticKind::SimplexName, SemanticModifiers::NONE);
+				},
+				Node::Attacher(label, content, _) => {
+					let modifiers = orphan(!siblings.is_attachers);
+					push(label, SemanticKind::AttacherLabel, modifiers);
+					push(content, SemanticKind::AttacherContent, modifiers);
+				},
+				Node::LineComment(line) => push(line, SemanticKind::LineComment, documentation(line)),
+				Node::BlockComment(lines) => {
+					for line in lines {
+						push(line, SemanticKind::BlockComment, documentation(line));
+					}
+				},
+				Node::LineOthertongue(line) => {
+					let modifiers = orphan(siblings.parent.is_none());
+					push(line, SemanticKind::LineOthertongue, modifiers);
+				},
+				Node::BlockOthertongue(lines) => {
+					let modifiers = orphan(siblings.parent.is_none());
+					for line in lines {
+						push(line, SemanticKind::BlockOthertongue, modifiers);
+					}
+				}
+			}
+		}
+	});
+
+	tokens.sort_by_key(|token| token.span.start);
+	tokens
+}
+
+/// Encodes the sorted tokens as the data of a semantic tokens response.
+///
+/// Tokens are cut at the end of their first line and empty tokens are skipped.
+pub fn encode_semantic_tokens(source: &[u8], tokens: &[SemanticToken]) -> Vec<u32> {
+	let index = LineIndex::new(source);
+	let mut data = Vec::with_capacity(tokens.len() * 5);
+	let mut previous_start = Position::default();
+
+	for token in tokens {
+		let start = index.position(token.span.start);
+		let end = index.position(token.span.end.min(index.line_end(start.line)));
+		let length = end.character.saturating_sub(start.character);
+
+		if length == 0 {
+			continue;
+		}
+
+		let delta_line = start.line - previous_start.line;
+		let delta_character = if delta_line == 0 {
+			start.character - previous_start.character
+		} else {
+			start.character
+		};
+		data.extend_from_slice(&[
+			delta_line as u32,
+			delta_character as u32,
+			length as u32,
+			token.kind.token_type() as u32,
+			token.modifiers.bits()
+		]);
+		previous_start = start;
+	}
+
+	data
+}
+
+/// Returns the edits that turn the previously encoded tokens into the current ones.
+///
+/// The integers that both share at the start and at the end are kept and the rest is replaced by
+/// one edit. There are no edits if both are the same.
+pub fn diff_semantic_tokens(previous: &[u32], current: &[u32]) -> Vec<SemanticTokensEdit> {
+	let prefix_length = previous.iter().zip(current).take_while(|(old, new)| old == new).count();
+	let suffix_length = previous[prefix_length..].iter()
+		.rev()
+		.zip(current[prefix_length..].iter().rev())
+		.take_while(|(old, new)| old == new)
+		.count();
+	let mut edits = Vec::new();
+
+	if previous != current {
+		edits.push(SemanticTokensEdit {
+			start: prefix_length,
+			delete_count: previous.len() - prefix_length - suffix_length,
+			data: Vec::from(&current[prefix_length..current.len() - suffix_length])
+		});
+	}
+
+	edits
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use crate::parse::parse_source;
+	use super::{
+		SemanticKind,
+		SemanticModifiers,
+		SemanticTokensEdit,
+		diff_semantic_tokens,
+		encode_semantic_tokens,
+		semantic_tokens
+	};
+
+	#[test]
+	fn can_classify_orphans() {
+		let source = b"kvtextablc";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Attacher(0..1, 1..2, Vec::new()));
+		nodes.push_back(Node::LineOthertongue(2..6));
+		nodes.push_back(Node::Complex(6..7, VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(7..8, VecDeque::new()));
+			content.push_back(Node::Attacher(8..9, 9..10, Vec::new()));
+			content
+		}));
+
+		let tokens = semantic_tokens(&source[..], &nodes);
+
+		let kinds = tokens.iter().map(|token| token.kind).collect::<Vec<_>>();
+		assert_eq!(kinds, [
+			SemanticKind::AttacherLabel,
+			SemanticKind::AttacherContent,
+			SemanticKind::LineOthertongue,
+			SemanticKind::ComplexName,
+			SemanticKind::SimplexName,
+			SemanticKind::AttacherLabel,
+			SemanticKind::AttacherContent
+		]);
+		assert!(tokens.iter()
+			.filter(|token| token.kind != SemanticKind::ComplexName)
+			.filter(|token| token.kind != SemanticKind::SimplexName)
+			.all(|token| token.modifiers.contains(SemanticModifiers::ORPHAN)));
+	}
+
+	#[test]
+	fn can_encode_and_diff_tokens() {
+		let source = b"a\n\tb|\n\tc|\n";
+		let nodes = parse_source(&source[..]);
+		let tokens = semantic_tokens(&source[..], &nodes);
+
+		let data = encode_semantic_tokens(&source[..], &tokens);
+
+		assert_eq!(data, [0, 0, 1, 0, 0, 1, 1, 1, 1, 0, 1, 1, 1, 1, 0]);
+		let mut changed_data = data.clone();
+		changed_data[7] = 2;
+		assert_eq!(diff_semantic_tokens(&data, &changed_data), [SemanticTokensEdit {
+			start: 7,
+			delete_count: 1,
+			data: [2].into()
+		}]);
+		assert!(diff_semantic_tokens(&data, &data).is_empty());
+	}
+}
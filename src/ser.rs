@@ -0,0 +1,628 @@
+//! Rust values are written following the conventions that the deserializer reads:
+//!
+//! - Structs and maps at the top level become the document. Nested ones become complexes named
+//!   after their keys.
+//! - Scalar fields become attachers. Units become simplexes so that they can be read as flags.
+//!   Fields that are `None` are omitted.
+//! - Sequences become complexes whose content are the elements. Scalar elements become simplexes
+//!   while compound elements become complexes named `item`.
+//! - Unit variants are scalars. Other variants become complexes whose only concept is named after
+//!   the variant.
+//!
+//! Texts that would be read back differently are rejected instead of being escaped. Texts cannot
+//! span many lines, start or end with whitespace, or end with `|`. Names of concepts and labels of
+//! attachers also cannot be empty, start with `#` or `=`, or contain `:`. Contents of attachers may
+//! be empty since they are written as `label: `. Elements of sequences cannot be `None` since
+//! omitting them would shift the elements after them.
+
+use serde::ser::{
+	Error as SerializationError,
+	Serialize,
+	SerializeMap,
+	SerializeSeq,
+	SerializeStruct,
+	SerializeStructVariant,
+	SerializeTuple,
+	SerializeTupleStruct,
+	SerializeTupleVariant,
+	Serializer
+};
+use crate::native::{format, Box, String, Vec, VecDeque};
+use crate::builder::TreeBuilder;
+use crate::serde_error::SerdeError;
+use crate::tree::{ELEMENT_NAME, RangeNode, RangeNodeQueue};
+use crate::write::{self, to_source};
+
+/// Returns the nodes that represent the Rust value and their source.
+pub fn to_nodes<T>(value: &T) -> Result<(Vec<u8>, RangeNodeQueue), SerdeError>
+where
+	T: ?Sized + Serialize {
+	match value.serialize(ShapeSerializer)? {
+		Shape::Map(entries) => {
+			let mut builder = TreeBuilder::new();
+			let (mut nodes, content) = build_entries(&mut builder, entries)?;
+			nodes.extend(content);
+			Ok((builder.finish(), nodes))
+		},
+		_ => Err(SerdeError::custom("expected a struct or map at the top level"))
+	}
+}
+
+/// Returns the source that represents the Rust value.
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, SerdeError>
+where
+	T: ?Sized + Serialize {
+	let (source, nodes) = to_nodes(value)?;
+	Ok(to_source(&source, &nodes))
+}
+
+/// Returns the source that represents the Rust value as a string.
+///
+/// ## Examples
+/// ```
+/// use serde::Serialize;
+/// use chearmyp_parser::to_string;
+///
+/// #[derive(Serialize)]
+/// struct Server {
+/// 	host: String,
+/// 	port: u16,
+/// 	aliases: Vec<String>
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Config {
+/// 	server: Server
+/// }
+///
+/// let config = Config {
+/// 	server: Server {
+/// 		host: "example.com".to_string(),
+/// 		port: 8080,
+/// 		aliases: vec!["www.example.com".to_string()]
+/// 	}
+/// };
+///
+/// assert_eq!(
+/// 	to_string(&config).unwrap(),
+/// 	"server\n\thost: example.com\n\tport: 8080\n\taliases\n\t\twww.example.com|\n"
+/// );
+/// ```
+pub fn to_string<T>(value: &T) -> Result<String, SerdeError>
+where
+	T: ?Sized + Serialize {
+	let source = to_vec(value)?;
+	String::from_utf8(source).map_err(|_| SerdeError::custom("texts should be in UTF-8"))
+}
+
+/// Represents the intermediate form of a serialized value.
+///
+/// It is needed because whether a field becomes an attacher or a concept is only known after the
+/// field has been serialized.
+enum Shape {
+	Absent,
+	Unit,
+	Scalar(String),
+	Sequence(Vec<Shape>),
+	Map(Vec<(String, Shape)>),
+	Variant(String, Box<Shape>)
+}
+
+fn build_entries(builder: &mut TreeBuilder, entries: Vec<(String, Shape)>)
+-> Result<(RangeNodeQueue, RangeNodeQueue), SerdeError> {
+	let mut attachers = VecDeque::new();
+	let mut content = VecDeque::new();
+
+	for (key, shape) in entries {
+		build_entry(builder, &key, shape, &mut attachers, &mut content)
+			.map_err(|error| error.locate(&key, None))?;
+	}
+
+	Ok((attachers, content))
+}
+
+fn build_entry(
+	builder: &mut TreeBuilder,
+	key: &str,
+	shape: Shape,
+	attachers: &mut RangeNodeQueue,
+	content: &mut RangeNodeQueue
+) -> Result<(), SerdeError> {
+	if let Shape::Absent = shape {
+		return Ok(());
+	}
+
+	let key = check_name(key)?;
+
+	match shape {
+		Shape::Absent => {},
+		Shape::Unit => content.push_back(builder.simplex(key, VecDeque::new())),
+		Shape::Scalar(text) => {
+			attachers.push_back(builder.attacher(key, check_text(&text)?));
+		},
+		Shape::Sequence(elements) => {
+			let elements = build_elements(builder, elements)?;
+			content.push_back(builder.complex(key, VecDeque::new(), elements));
+		},
+		Shape::Map(entries) => {
+			let (entry_attachers, entry_content) = build_entries(builder, entries)?;
+			content.push_back(builder.complex(key, entry_attachers, entry_content));
+		},
+		Shape::Variant(name, shape) => {
+			let node = build_variant(builder, key, name, *shape)?;
+			content.push_back(node);
+		}
+	}
+
+	Ok(())
+}
+
+fn build_elements(builder: &mut TreeBuilder, elements: Vec<Shape>)
+-> Result<RangeNodeQueue, SerdeError> {
+	let mut nodes = VecDeque::with_capacity(elements.len());
+
+	for element in elements {
+		match element {
+			Shape::Absent => {
+				return Err(SerdeError::custom("`None` cannot be an element of a sequence"));
+			},
+			Shape::Unit => return Err(SerdeError::custom("units cannot be elements of a sequence")),
+			Shape::Scalar(text) => {
+				nodes.push_back(builder.simplex(check_name(&text)?, VecDeque::new()));
+			},
+			Shape::Sequence(elements) => {
+				let elements = build_elements(builder, elements)?;
+				let name = ELEMENT_NAME.as_bytes();
+				nodes.push_back(builder.complex(name, VecDeque::new(), elements));
+			},
+			Shape::Map(entries) => {
+				let (attachers, content) = build_entries(builder, entries)?;
+				nodes.push_back(builder.complex(ELEMENT_NAME.as_bytes(), attachers, content));
+			},
+			Shape::Variant(name, shape) => {
+				nodes.push_back(build_variant(builder, ELEMENT_NAME.as_bytes(), name, *shape)?);
+			}
+		}
+	}
+
+	Ok(nodes)
+}
+
+fn build_variant(builder: &mut TreeBuilder, key: &[u8], name: String, shape: Shape)
+-> Result<RangeNode, SerdeError> {
+	let mut attachers = VecDeque::new();
+	let mut content = VecDeque::new();
+	build_entry(builder, &name, shape, &mut attachers, &mut content)?;
+	Ok(builder.complex(key, attachers, content))
+}
+
+/// Returns the text if it can be a name of a concept or a label of an attacher.
+fn check_name(text: &str) -> Result<&[u8], SerdeError> {
+	write::check_name(text).map_err(SerdeError::custom)
+}
+
+/// Returns the text if it would be read back the same after being written in a line.
+fn check_text(text: &str) -> Result<&[u8], SerdeError> {
+	write::check_text(text).map_err(SerdeError::custom)
+}
+
+struct ShapeSerializer;
+
+macro_rules! serialize_displayed {
+	($($method:ident($type:ty)),*) => {
+		$(
+			fn $method(self, value: $type) -> Result<Shape, SerdeError> {
+				Ok(Shape::Scalar(format!("{}", value)))
+			}
+		)*
+	};
+}
+
+impl Serializer for ShapeSerializer {
+	type Ok = Shape;
+	type Error = SerdeError;
+	type SerializeSeq = SequenceShape;
+	type SerializeTuple = SequenceShape;
+	type SerializeTupleStruct = SequenceShape;
+	type SerializeTupleVariant = SequenceShape;
+	type SerializeMap = MapShape;
+	type SerializeStruct = MapShape;
+	type SerializeStructVariant = MapShape;
+
+	serialize_displayed! {
+		serialize_bool(bool),
+		serialize_i8(i8),
+		serialize_i16(i16),
+		serialize_i32(i32),
+		serialize_i64(i64),
+		serialize_u8(u8),
+		serialize_u16(u16),
+		serialize_u32(u32),
+		serialize_u64(u64),
+		serialize_f32(f32),
+		serialize_f64(f64),
+		serialize_char(char)
+	}
+
+	fn serialize_str(self, value: &str) -> Result<Shape, SerdeError> {
+		Ok(Shape::Scalar(value.into()))
+	}
+
+	fn serialize_bytes(self, value: &[u8]) -> Result<Shape, SerdeError> {
+		match core::str::from_utf8(value) {
+			Ok(text) => self.serialize_str(text),
+			Err(_) => Err(SerdeError::custom("bytes should be in UTF-8"))
+		}
+	}
+
+	fn serialize_none(self) -> Result<Shape, SerdeError> {
+		Ok(Shape::Absent)
+	}
+
+	fn serialize_some<T>(self, value: &T) -> Result<Shape, SerdeError>
+	where
+		T: ?Sized + Serialize {
+		value.serialize(self)
+	}
+
+	fn serialize_unit(self) -> Result<Shape, SerdeError> {
+		Ok(Shape::Unit)
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<Shape, SerdeError> {
+		Ok(Shape::Unit)
+	}
+
+	fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str)
+	-> Result<Shape, SerdeError> {
+		Ok(Shape::Scalar(variant.into()))
+	}
+
+	fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T)
+	-> Result<Shape, SerdeError>
+	where
+		T: ?Sized + Serialize {
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_variant<T>(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		value: &T
+	) -> Result<Shape, SerdeError>
+	where
+		T: ?Sized + Serialize {
+		let shape = value.serialize(self)?;
+		Ok(Shape::Variant(variant.into(), Box::new(shape)))
+	}
+
+	fn serialize_seq(self, length: Option<usize>) -> Result<SequenceShape, SerdeError> {
+		Ok(SequenceShape {
+			variant: None,
+			elements: Vec::with_capacity(length.unwrap_or(0))
+		})
+	}
+
+	fn serialize_tuple(self, length: usize) -> Result<SequenceShape, SerdeError> {
+		self.serialize_seq(Some(length))
+	}
+
+	fn serialize_tuple_struct(self, _name: &'static str, length: usize)
+	-> Result<SequenceShape, SerdeError> {
+		self.serialize_seq(Some(length))
+	}
+
+	fn serialize_tuple_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		length: usize
+	) -> Result<SequenceShape, SerdeError> {
+		Ok(SequenceShape {
+			variant: Some(variant),
+			elements: Vec::with_capacity(length)
+		})
+	}
+
+	fn serialize_map(self, length: Option<usize>) -> Result<MapShape, SerdeError> {
+		Ok(MapShape {
+			variant: None,
+			entries: Vec::with_capacity(length.unwrap_or(0)),
+			key: None
+		})
+	}
+
+	fn serialize_struct(self, _name: &'static str, length: usize)
+	-> Result<MapShape, SerdeError> {
+		self.serialize_map(Some(length))
+	}
+
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		length: usize
+	) -> Result<MapShape, SerdeError> {
+		Ok(MapShape {
+			variant: Some(variant),
+			entries: Vec::with_capacity(length),
+			key: None
+		})
+	}
+}
+
+struct SequenceShape {
+	variant: Option<&'static str>,
+	elements: Vec<Shape>
+}
+
+impl SequenceShape {
+	fn push<T>(&mut self, value: &T) -> Result<(), SerdeError>
+	where
+		T: ?Sized + Serialize {
+		self.elements.push(value.serialize(ShapeSerializer)?);
+		Ok(())
+	}
+
+	fn finish(self) -> Result<Shape, SerdeError> {
+		let shape = Shape::Sequence(self.elements);
+
+		Ok(match self.variant {
+			Some(variant) => Shape::Variant(variant.into(), Box::new(shape)),
+			None => shape
+		})
+	}
+}
+
+macro_rules! serialize_elements {
+	($($trait:ident::$method:ident),*) => {
+		$(
+			impl $trait for SequenceShape {
+				type Ok = Shape;
+				type Error = SerdeError;
+
+				fn $method<T>(&mut self, value: &T) -> Result<(), SerdeError>
+				where
+					T: ?Sized + Serialize {
+					self.push(value)
+				}
+
+				fn end(self) -> Result<Shape, SerdeError> {
+					self.finish()
+				}
+			}
+		)*
+	};
+}
+
+serialize_elements! {
+	SerializeSeq::serialize_element,
+	SerializeTuple::serialize_element,
+	SerializeTupleStruct::serialize_field,
+	SerializeTupleVariant::serialize_field
+}
+
+struct MapShape {
+	variant: Option<&'static str>,
+	entries: Vec<(String, Shape)>,
+	key: Option<String>
+}
+
+impl MapShape {
+	fn push<T>(&mut self, key: String, value: &T) -> Result<(), SerdeError>
+	where
+		T: ?Sized + Serialize {
+		let shape = value.serialize(ShapeSerializer)?;
+		self.entries.push((key, shape));
+		Ok(())
+	}
+
+	fn finish(self) -> Result<Shape, SerdeError> {
+		let shape = Shape::Map(self.entries);
+
+		Ok(match self.variant {
+			Some(variant) => Shape::Variant(variant.into(), Box::new(shape)),
+			None => shape
+		})
+	}
+}
+
+impl SerializeMap for MapShape {
+	type Ok = Shape;
+	type Error = SerdeError;
+
+	fn serialize_key<T>(&mut self, key: &T) -> Result<(), SerdeError>
+	where
+		T: ?Sized + Serialize {
+		match key.serialize(ShapeSerializer)? {
+			Shape::Scalar(key) => {
+				self.key = Some(key);
+				Ok(())
+			},
+			_ => Err(SerdeError::custom("keys should be scalars"))
+		}
+	}
+
+	fn serialize_value<T>(&mut self, value: &T) -> Result<(), SerdeError>
+	where
+		T: ?Sized + Serialize {
+		let key = self.key.take().expect("a key should be serialized first");
+		self.push(key, value)
+	}
+
+	fn end(self) -> Result<Shape, SerdeError> {
+		self.finish()
+	}
+}
+
+macro_rules! serialize_fields {
+	($($trait:ident),*) => {
+		$(
+			impl $trait for MapShape {
+				type Ok = Shape;
+				type Error = SerdeError;
+
+				fn serialize_field<T>(&mut self, key: &'static str, value: &T)
+				-> Result<(), SerdeError>
+				where
+					T: ?Sized + Serialize {
+					self.push(key.into(), value)
+				}
+
+				fn end(self) -> Result<Shape, SerdeError> {
+					self.finish()
+				}
+			}
+		)*
+	};
+}
+
+serialize_fields! {
+	SerializeStruct,
+	SerializeStructVariant
+}
+
+#[cfg(test)]
+mod t {
+	use serde::{Deserialize, Serialize};
+	use crate::native::{Map, String, Vec};
+	use crate::de::{from_nodes, from_slice};
+	use crate::dump::assert_dump;
+	use super::{to_nodes, to_string};
+
+	#[derive(Serialize, Deserialize, Debug, PartialEq)]
+	#[serde(rename_all = "lowercase")]
+	enum Shape {
+		Point,
+		Circle { radius: u8 }
+	}
+
+	#[derive(Serialize, Deserialize, Debug, PartialEq)]
+	struct Drawing {
+		title: String,
+		scale: Option<f32>,
+		visible: Option<()>,
+		shapes: Vec<Shape>,
+		tags: Vec<String>
+	}
+
+	fn sample_drawing() -> Drawing {
+		let mut shapes = Vec::new();
+		shapes.push(Shape::Point);
+		shapes.push(Shape::Circle { radius: 3 });
+
+		let mut tags = Vec::new();
+		tags.push("draft".into());
+
+		Drawing {
+			title: "sketch".into(),
+			scale: None,
+			visible: Some(()),
+			shapes,
+			tags
+		}
+	}
+
+	#[test]
+	fn can_serialize_to_nodes() {
+		let (source, nodes) = to_nodes(&sample_drawing()).unwrap();
+
+		assert_dump(&source, &nodes, "
+			(attacher \"title\" \"sketch\")
+			(simplex \"visible\")
+			(complex \"shapes\"
+				(content
+					(simplex \"point\")
+					(complex \"item\"
+						(content
+							(complex \"circle\"
+								(attachers (attacher \"radius\" \"3\")))))))
+			(complex \"tags\" (content (simplex \"draft\")))
+		");
+	}
+
+	#[test]
+	fn can_serialize_to_string() {
+		let source = to_string(&sample_drawing()).unwrap();
+
+		assert_eq!(source, concat!(
+			"title: sketch\n",
+			"visible|\n",
+			"shapes\n",
+			"\tpoint|\n",
+			"\titem\n",
+			"\t\tcircle\n",
+			"\t\t\tradius: 3\n",
+			"tags\n",
+			"\tdraft|\n"
+		));
+	}
+
+	#[test]
+	fn can_deserialize_serialized_value() {
+		let (source, nodes) = to_nodes(&sample_drawing()).unwrap();
+
+		let drawing: Drawing = from_nodes(&source, &nodes).unwrap();
+
+		assert_eq!(drawing, sample_drawing());
+	}
+
+	#[test]
+	fn can_read_back_serialized_texts() {
+		let mut texts = Map::new();
+		texts.insert("title", "#1 = best|ish");
+		texts.insert("url", "https://example.com");
+		texts.insert("note", "");
+
+		let source = to_string(&texts).unwrap();
+
+		let read_texts: Map<String, String> = from_slice(source.as_bytes()).unwrap();
+		assert_eq!(read_texts["title"], "#1 = best|ish");
+		assert_eq!(read_texts["url"], "https://example.com");
+		assert_eq!(read_texts["note"], "");
+	}
+
+	#[test]
+	fn cannot_serialize_ambiguous_texts() {
+		let cases = [
+			("", "a", "names cannot be empty"),
+			("name", "a\nb", "texts cannot span multiple lines"),
+			("name", " a", "texts cannot start or end with whitespace"),
+			("name", "a|", "texts cannot end with `|`"),
+			("=name", "a", "names cannot start with `#` or `=`"),
+			("na:me", "a", "names cannot contain `:`")
+		];
+
+		for (key, value, message) in cases {
+			let mut texts = Map::new();
+			texts.insert(key, value);
+
+			let error = to_string(&texts).unwrap_err();
+
+			assert_eq!(error.message, message);
+			assert_eq!(error.path, key);
+		}
+	}
+
+	#[test]
+	fn cannot_serialize_none_elements() {
+		let mut lists = Map::new();
+		lists.insert("ports", Vec::from([Some(80), None]));
+
+		let error = to_string(&lists).unwrap_err();
+
+		assert_eq!(error.message, "`None` cannot be an element of a sequence");
+		assert_eq!(error.path, "ports");
+	}
+
+	#[test]
+	fn cannot_serialize_scalar_at_top_level() {
+		let error = to_string(&5).unwrap_err();
+
+		assert_eq!(error.message, "expected a struct or map at the top level");
+	}
+}
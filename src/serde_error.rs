@@ -0,0 +1,78 @@
+use core::fmt::{Display, Formatter, Result as FormatResult};
+use serde::de::{Error as DeserializationError, StdError};
+use serde::ser::Error as SerializationError;
+use crate::native::{format, Range, String};
+
+/// Represents the error found when mapping nodes to Rust values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerdeError {
+	/// Names of the concepts and labels, separated by slashes, that lead to the offending node.
+	pub path: String,
+	/// Boundary of the name or content of the offending node.
+	pub span: Option<Range<usize>>,
+	/// Description of the problem.
+	pub message: String
+}
+
+impl SerdeError {
+	/// Sets where the error was found unless it has been located already.
+	pub(crate) fn locate(mut self, path: &str, span: Option<Range<usize>>) -> Self {
+		if self.path.is_empty() && self.span.is_none() {
+			self.path = path.into();
+			self.span = span;
+		}
+
+		self
+	}
+}
+
+impl Display for SerdeError {
+	fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+		write!(formatter, "{}", self.message)?;
+
+		if !self.path.is_empty() {
+			write!(formatter, " at `{}`", self.path)?;
+		}
+
+		if let Some(span) = &self.span {
+			write!(formatter, " (bytes {}..{})", span.start, span.end)?;
+		}
+
+		Ok(())
+	}
+}
+
+impl StdError for SerdeError {}
+
+impl DeserializationError for SerdeError {
+	fn custom<T: Display>(message: T) -> Self {
+		Self {
+			path: String::new(),
+			span: None,
+			message: format!("{}", message)
+		}
+	}
+}
+
+impl SerializationError for SerdeError {
+	fn custom<T: Display>(message: T) -> Self {
+		<Self as DeserializationError>::custom(message)
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{format, String};
+	use super::SerdeError;
+
+	#[test]
+	fn can_display_located_error() {
+		let error = SerdeError {
+			path: String::new(),
+			span: None,
+			message: "invalid value".into()
+		}.locate("server/port", Some(10..14));
+
+		assert_eq!(format!("{}", error), "invalid value at `server/port` (bytes 10..14)");
+	}
+}
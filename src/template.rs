@@ -0,0 +1,444 @@
+//! A template is declared by a `define` concept. Its `name` attacher names the template, its
+//! `param` attachers declare the parameters, and its content is the body:
+//!
+//! ```text
+//! define
+//! 	name: listener
+//! 	param: port
+//! 	listener_{{port}}
+//! 		port: {{port}}
+//! @listener|
+//! 	port: 80
+//! ```
+//!
+//! A concept named `@` followed by the name of an earlier template is replaced by a copy of the
+//! body. Its attachers are the arguments, and every `{{parameter}}` in the names, labels, and
+//! contents of the copy is replaced by the argument.
+//!
+//! Expansion is hygienic. Arguments are inserted as they are without being expanded again, and a
+//! body only sees the parameters of its own template. Arguments of uses in a body are substituted
+//! before the inner template is expanded so parameters can be passed explicitly.
+
+use crate::native::{format, Range, String, Vec, VecDeque};
+use crate::node::Node;
+use crate::builder::TreeBuilder;
+use crate::diagnostic::Diagnostic;
+use crate::tree::{RangeNode, RangeNodeQueue, path_of, resolve, span_of};
+
+/// Contains the options for expanding templates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemplateOptions {
+	/// Number of templates that may be expanded inside each other.
+	pub max_depth: usize
+}
+
+impl Default for TemplateOptions {
+	fn default() -> Self {
+		Self { max_depth: 16 }
+	}
+}
+
+/// Represents where a generated node came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+	/// Boundary of the generated node in the expanded source.
+	pub span: Range<usize>,
+	/// Indices that lead to the generated node in the expanded nodes. Each index counts the
+	/// attachers of the parent before its content.
+	pub path: Vec<usize>,
+	pub template: String,
+	/// Boundary of the node in the original source that the generated node copies.
+	pub definition_span: Range<usize>,
+	/// Boundary of the concept in the original source that used the template.
+	pub use_span: Range<usize>
+}
+
+/// Contains the nodes whose templates have been expanded.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateExpansion {
+	/// Source that the boundaries of the nodes refer to.
+	pub source: Vec<u8>,
+	pub nodes: RangeNodeQueue,
+	/// Origins of the generated nodes in the order they were generated.
+	pub origins: Vec<Origin>,
+	/// Problems found while expanding. Their spans refer to the original source.
+	pub diagnostics: Vec<Diagnostic>
+}
+
+impl TemplateExpansion {
+	/// Returns the origin of the node if it has been generated by a template.
+	///
+	/// The node is found by its address so it must be borrowed from the expanded nodes.
+	pub fn origin_of(&self, node: &RangeNode) -> Option<&Origin> {
+		let path = path_of(&self.nodes, node)?;
+		self.origins.iter().find(|origin| origin.path == path)
+	}
+}
+
+/// Removes the template definitions and replaces the uses of templates with their bodies.
+///
+/// Uses that cannot be expanded are removed and reported.
+///
+/// ## Examples
+/// ```
+/// use chearmyp_parser::{parse_source, expand_templates, assert_dump, TemplateOptions};
+/// let source = b"define\n\tname: pair\n\tparam: x\n\t{{x}}_a|\n\t{{x}}_b|\n@pair|\n\tx: left\n";
+/// let nodes = parse_source(&source[..]);
+///
+/// let expansion = expand_templates(&source[..], &nodes, &TemplateOptions::default());
+///
+/// assert!(expansion.diagnostics.is_empty());
+/// assert_dump(&expansion.source, &expansion.nodes, "
+/// 	(simplex \"left_a\")
+/// 	(simplex \"left_b\")
+/// ");
+/// assert_eq!(expansion.origin_of(&expansion.nodes[0]).unwrap().template, "pair");
+/// ```
+pub fn expand_templates(source: &[u8], nodes: &RangeNodeQueue, options: &TemplateOptions)
+-> TemplateExpansion {
+	let mut expander = Expander {
+		source,
+		options,
+		templates: Vec::new(),
+		builder: TreeBuilder::new(),
+		path: Vec::new(),
+		origins: Vec::new(),
+		diagnostics: Vec::new()
+	};
+
+	let nodes = expander.expand_nodes(nodes, None, 0);
+
+	TemplateExpansion {
+		source: expander.builder.finish(),
+		nodes,
+		origins: expander.origins,
+		diagnostics: expander.diagnostics
+	}
+}
+
+struct Template<'s, 'n> {
+	name: &'s [u8],
+	parameters: Vec<&'s [u8]>,
+	body: Option<&'n RangeNodeQueue>
+}
+
+/// Represents a template being expanded.
+struct Expansion<'s> {
+	template: &'s [u8],
+	arguments: Vec<(&'s [u8], Vec<u8>)>,
+	use_span: Range<usize>,
+	depth: usize
+}
+
+struct Expander<'s, 'n, 'o> {
+	source: &'s [u8],
+	options: &'o TemplateOptions,
+	templates: Vec<Template<'s, 'n>>,
+	builder: TreeBuilder,
+	/// Indices that lead to the node being expanded.
+	path: Vec<usize>,
+	origins: Vec<Origin>,
+	diagnostics: Vec<Diagnostic>
+}
+
+impl<'s, 'n, 'o> Expander<'s, 'n, 'o> {
+	/// Expands the nodes whose indices among their expanded siblings start from the given index.
+	fn expand_nodes(
+		&mut self,
+		nodes: &'n RangeNodeQueue,
+		expansion: Option<&Expansion<'s>>,
+		start: usize
+	) -> RangeNodeQueue {
+		let mut expanded_nodes = VecDeque::with_capacity(nodes.len());
+
+		for node in nodes {
+			let concept = match node {
+				Node::Complex(concept, _, _) | Node::Simplex(concept, _) => {
+					Some(resolve(self.source, concept))
+				},
+				_ => None
+			};
+
+			match concept {
+				Some(b"define") => self.define(node, expansion),
+				Some(name) if name.starts_with(b"@") => {
+					let start = start + expanded_nodes.len();
+					expanded_nodes.extend(self.use_template(node, &name[1..], expansion, start));
+				},
+				_ => {
+					self.path.push(start + expanded_nodes.len());
+					let expanded_node = self.expand_node(node, expansion);
+					if let Some(expansion) = expansion {
+						self.origins.push(Origin {
+							span: span_of(&expanded_node),
+							path: self.path.clone(),
+							template: String::from_utf8_lossy(expansion.template).into_owned(),
+							definition_span: span_of(node),
+							use_span: expansion.use_span.clone()
+						});
+					}
+					self.path.pop();
+					expanded_nodes.push_back(expanded_node);
+				}
+			}
+		}
+
+		expanded_nodes
+	}
+
+	fn expand_node(&mut self, node: &'n RangeNode, expansion: Option<&Expansion<'s>>) -> RangeNode {
+		match node {
+			Node::Complex(concept, attachers, content) => {
+				let concept = self.substitute(concept, expansion);
+				let concept = self.builder.push_text(&concept);
+				let attachers = self.expand_nodes(attachers, expansion, 0);
+				let content = self.expand_nodes(content, expansion, attachers.len());
+				Node::Complex(concept, attachers, content)
+			},
+			Node::Simplex(concept, attachers) => {
+				let concept = self.substitute(concept, expansion);
+				let concept = self.builder.push_text(&concept);
+				let attachers = self.expand_nodes(attachers, expansion, 0);
+				Node::Simplex(concept, attachers)
+			},
+			Node::Attacher(label, content, _) => {
+				let label = self.substitute(label, expansion);
+				let content = self.substitute(content, expansion);
+				self.builder.attacher(&label, &content)
+			},
+			_ => self.builder.copy(self.source, node)
+		}
+	}
+
+	fn define(&mut self, node: &'n RangeNode, expansion: Option<&Expansion<'s>>) {
+		let (concept, attachers, body) = match node {
+			Node::Complex(concept, attachers, content) => (concept, attachers, Some(content)),
+			Node::Simplex(concept, attachers) => (concept, attachers, None),
+			_ => return
+		};
+
+		if expansion.is_some() {
+			let message = "templates cannot be defined inside templates".into();
+			self.diagnostics.push(Diagnostic::error(concept.clone(), "invalid_template", message));
+			return;
+		}
+
+		let mut name = None;
+		let mut parameters = Vec::new();
+
+		for attacher in attachers {
+			if let Node::Attacher(label, content, _) = attacher {
+				match resolve(self.source, label) {
+					b"name" => name = Some(resolve(self.source, content)),
+					b"param" => parameters.push(resolve(self.source, content)),
+					_ => {}
+				}
+			}
+		}
+
+		match name {
+			Some(name) => self.templates.push(Template { name, parameters, body }),
+			None => {
+				let message = "template has no `name` attacher".into();
+				self.diagnostics.push(Diagnostic::error(concept.clone(), "invalid_template", message));
+			}
+		}
+	}
+
+	fn use_template(
+		&mut self,
+		node: &'n RangeNode,
+		name: &'s [u8],
+		expansion: Option<&Expansion<'s>>,
+		start: usize
+	) -> RangeNodeQueue {
+		let (concept, attachers, has_content) = match node {
+			Node::Complex(concept, attachers, content) => (concept, attachers, !content.is_empty()),
+			Node::Simplex(concept, attachers) => (concept, attachers, false),
+			_ => return VecDeque::new()
+		};
+		let printable_name = String::from_utf8_lossy(name);
+		let depth = expansion.map(|expansion| expansion.depth + 1).unwrap_or(1);
+
+		if has_content {
+			let message = format!("use of template `{}` cannot have content", printable_name);
+			self.diagnostics.push(Diagnostic::error(concept.clone(), "invalid_template", message));
+			return VecDeque::new();
+		}
+
+		if depth > self.options.max_depth {
+			let message = format!(
+				"template `{}` is expanded more than {} levels deep",
+				printable_name,
+				self.options.max_depth
+			);
+			self.diagnostics.push(Diagnostic::error(concept.clone(), "template_depth", message));
+			return VecDeque::new();
+		}
+
+		let template_index = match self.templates.iter().rposition(|template| template.name == name) {
+			Some(template_index) => template_index,
+			None => {
+				let message = format!("template `{}` is not defined", printable_name);
+				self.diagnostics.push(Diagnostic::error(concept.clone(), "unknown_template", message));
+				return VecDeque::new();
+			}
+		};
+
+		let mut arguments = Vec::new();
+		for attacher in attachers {
+			if let Node::Attacher(label, content, _) = attacher {
+				let parameter = resolve(self.source, label);
+				if self.templates[template_index].parameters.contains(&parameter) {
+					arguments.push((parameter, self.substitute(content, expansion)));
+				} else {
+					let message = format!(
+						"template `{}` has no parameter `{}`",
+						printable_name,
+						String::from_utf8_lossy(parameter)
+					);
+					let diagnostic = Diagnostic::warning(label.clone(), "unknown_argument", message);
+					self.diagnostics.push(diagnostic);
+				}
+			}
+		}
+
+		let template = &self.templates[template_index];
+		let missing_parameters = template.parameters.iter()
+			.filter(|parameter| arguments.iter().all(|(name, _)| name != *parameter))
+			.map(|parameter| String::from_utf8_lossy(parameter).into_owned())
+			.collect::<Vec<_>>();
+		if !missing_parameters.is_empty() {
+			let message = format!(
+				"template `{}` needs arguments for {}",
+				printable_name,
+				missing_parameters.join(", ")
+			);
+			self.diagnostics.push(Diagnostic::error(concept.clone(), "missing_argument", message));
+			return VecDeque::new();
+		}
+
+		let body = match template.body {
+			Some(body) => body,
+			None => return VecDeque::new()
+		};
+		let expansion = Expansion {
+			template: template.name,
+			arguments,
+			use_span: concept.clone(),
+			depth
+		};
+
+		self.expand_nodes(body, Some(&expansion), start)
+	}
+
+	/// Replaces the parameters in the text with the arguments of the expansion. Texts outside of
+	/// templates are kept as they are.
+	fn substitute(&mut self, boundary: &Range<usize>, expansion: Option<&Expansion<'s>>)
+	-> Vec<u8> {
+		let text = resolve(self.source, boundary);
+		let expansion = match expansion {
+			Some(expansion) => expansion,
+			None => return text.to_vec()
+		};
+		let mut substituted_text = Vec::with_capacity(text.len());
+		let mut index = 0;
+
+		while index < text.len() {
+			let rest = &text[index..];
+			let parameter_length = rest.strip_prefix(b"{{")
+				.and_then(|rest| rest.windows(2).position(|window| window == b"}}"));
+
+			match parameter_length {
+				Some(parameter_length) => {
+					let parameter = &rest[2..parameter_length + 2];
+					let argument = expansion.arguments.iter()
+						.find(|(name, _)| *name == parameter)
+						.map(|(_, value)| value);
+
+					match argument {
+						Some(value) => substituted_text.extend_from_slice(value),
+						None => {
+							let start = boundary.start + index;
+							let span = start..start + parameter_length + 4;
+							let message = format!(
+								"template `{}` has no parameter `{}`",
+								String::from_utf8_lossy(expansion.template),
+								String::from_utf8_lossy(parameter)
+							);
+							self.diagnostics.push(Diagnostic::error(span, "undefined_parameter", message));
+							substituted_text.extend_from_slice(&rest[..parameter_length + 4]);
+						}
+					}
+
+					index += parameter_length + 4;
+				},
+				None => {
+					substituted_text.push(text[index]);
+					index += 1;
+				}
+			}
+		}
+
+		substituted_text
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::Vec;
+	use crate::node::Node;
+	use crate::dump::assert_dump;
+	use crate::parse::parse_source;
+	use crate::tree::resolve;
+	use super::{TemplateOptions, expand_templates};
+
+	#[test]
+	fn can_expand_nested_templates_hygienically() {
+		let source = b"define\n\tname: inner\n\tparam: y\n\t{{y}}|\n\t\tx: {{x}}\n\
+			define\n\tname: outer\n\tparam: x\n\touter\n\t\t@inner|\n\t\t\ty: {{x}}_y\n\
+			@outer|\n\tx: {{y}}\n";
+		let nodes = parse_source(&source[..]);
+
+		let expansion = expand_templates(&source[..], &nodes, &TemplateOptions::default());
+
+		assert_dump(&expansion.source, &expansion.nodes, "
+			(complex \"outer\" (content
+				(simplex \"{{y}}_y\" (attachers (attacher \"x\" \"{{x}}\")))))
+		");
+		assert_eq!(expansion.diagnostics.len(), 1);
+		assert_eq!(expansion.diagnostics[0].code, "undefined_parameter");
+		assert_eq!(resolve(&source[..], &expansion.diagnostics[0].span), b"{{x}}");
+
+		let origin = expansion.origin_of(&expansion.nodes[0]).unwrap();
+		assert_eq!(origin.template, "outer");
+		assert_eq!(resolve(&source[..], &origin.use_span), b"@outer");
+		assert_eq!(resolve(&source[..], &origin.definition_span), b"outer");
+
+		let simplex = match &expansion.nodes[0] {
+			Node::Complex(_, _, content) => &content[0],
+			_ => unreachable!()
+		};
+		let origin = expansion.origin_of(simplex).unwrap();
+		assert_eq!(origin.template, "inner");
+		assert_eq!(origin.path, [0, 0]);
+	}
+
+	#[test]
+	fn cannot_expand_too_deeply() {
+		let source = b"define\n\tname: loop\n\t@loop|\n@loop|\n@missing|\n";
+		let nodes = parse_source(&source[..]);
+
+		let expansion = expand_templates(&source[..], &nodes, &TemplateOptions { max_depth: 3 });
+
+		let codes = expansion.diagnostics.iter()
+			.map(|diagnostic| diagnostic.code)
+			.collect::<Vec<_>>();
+		assert_eq!(codes, ["template_depth", "unknown_template"]);
+		assert_eq!(
+			expansion.diagnostics[0].message,
+			"template `loop` is expanded more than 3 levels deep"
+		);
+		assert!(expansion.nodes.is_empty());
+	}
+}
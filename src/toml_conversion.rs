@@ -0,0 +1,174 @@
+use toml::{Table, Value};
+use crate::native::{format, Vec};
+use crate::conversion::{
+	Conversion,
+	Loss,
+	Neutral,
+	Scalar,
+	document_to_neutral,
+	neutral_to_document,
+	infer_scalar,
+	join,
+	report
+};
+use crate::tree::RangeNodeQueue;
+
+/// Returns the TOML table that represents the nodes.
+///
+/// See the documentation of [`Conversion`] for the rules. TOML also loses the following which are
+/// reported as well:
+/// - Order of the entries since tables are sorted by their keys.
+/// - Nulls which become empty tables since TOML has no null.
+pub fn to_toml(source: &[u8], nodes: &RangeNodeQueue) -> Conversion<Table> {
+	let mut losses = Vec::new();
+	let value = document_to_neutral(source, nodes, &mut losses);
+	let value = match neutral_to_toml(value, "", &mut losses) {
+		Value::Table(table) => table,
+		_ => Table::new()
+	};
+	Conversion { value, losses }
+}
+
+/// Returns the nodes that represent the TOML table and their source.
+///
+/// See the documentation of [`Conversion`] for the rules. Empty tables become simplexes and
+/// datetimes become attachers whose contents are their text, so they are read back as strings.
+pub fn from_toml(table: &Table) -> Conversion<(Vec<u8>, RangeNodeQueue)> {
+	let mut losses = Vec::new();
+	let value = table_to_neutral(table, "", &mut losses);
+	let value = neutral_to_document(value, &mut losses);
+	Conversion { value, losses }
+}
+
+fn neutral_to_toml(value: Neutral, path: &str, losses: &mut Vec<Loss>) -> Value {
+	match value {
+		Neutral::Null => {
+			report(losses, path, None, "null has been replaced by an empty table");
+			Value::Table(Table::new())
+		},
+		Neutral::Scalar(text) => match infer_scalar(&text) {
+			Scalar::Boolean(boolean) => Value::Boolean(boolean),
+			Scalar::Integer(integer) => Value::Integer(integer),
+			Scalar::Float(float) => Value::Float(float),
+			Scalar::Text(_) => Value::String(text)
+		},
+		Neutral::Sequence(elements) => {
+			let elements = elements.into_iter().enumerate().map(|(index, element)| {
+				neutral_to_toml(element, &join(path, &format!("{}", index)), losses)
+			}).collect();
+			Value::Array(elements)
+		},
+		Neutral::Map(entries) => {
+			let mut table = Table::new();
+
+			if entries.windows(2).any(|pair| pair[0].0 > pair[1].0) {
+				report(losses, path, None, "entries have been sorted by their keys");
+			}
+
+			for (key, value) in entries {
+				let value = neutral_to_toml(value, &join(path, &key), losses);
+				table.insert(key, value);
+			}
+
+			Value::Table(table)
+		}
+	}
+}
+
+fn toml_to_neutral(value: &Value, path: &str, losses: &mut Vec<Loss>) -> Neutral {
+	match value {
+		Value::String(text) => Neutral::from_text(text.clone(), path, losses),
+		Value::Integer(integer) => Neutral::Scalar(format!("{}", integer)),
+		Value::Float(float) => Neutral::Scalar(format!("{}", float)),
+		Value::Boolean(boolean) => Neutral::Scalar(format!("{}", boolean)),
+		Value::Datetime(datetime) => Neutral::Scalar(format!("{}", datetime)),
+		Value::Array(elements) => {
+			let elements = elements.iter().enumerate().map(|(index, element)| {
+				toml_to_neutral(element, &join(path, &format!("{}", index)), losses)
+			}).collect();
+			Neutral::Sequence(elements)
+		},
+		Value::Table(table) => table_to_neutral(table, path, losses)
+	}
+}
+
+fn table_to_neutral(table: &Table, path: &str, losses: &mut Vec<Loss>) -> Neutral {
+	if table.is_empty() && !path.is_empty() {
+		return Neutral::Null;
+	}
+
+	let entries = table.iter().map(|(key, value)| {
+		(key.clone(), toml_to_neutral(value, &join(path, key), losses))
+	}).collect();
+
+	Neutral::Map(entries)
+}
+
+#[cfg(test)]
+mod t {
+	use toml::{from_str, Table};
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use crate::dump::assert_dump;
+	use super::{to_toml, from_toml};
+
+	#[test]
+	fn can_convert_to_toml() {
+		let source = b"server\n\tport: 80\n\tdebug|\n\t### remark ###";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..6, {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(Node::Attacher(8..12, 14..16, Vec::new()));
+			attachers
+		}, {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(18..23, VecDeque::new()));
+			content.push_back(Node::BlockComment({
+				let mut lines = Vec::new();
+				lines.push(29..37);
+				lines
+			}));
+			content
+		}));
+
+		let conversion = to_toml(&source[..], &nodes);
+
+		assert_eq!(conversion.value, from_str::<Table>(concat!(
+			"[server]\n",
+			"port = 80\n",
+			"debug = {}\n"
+		)).unwrap());
+		let messages = conversion.losses.iter()
+			.map(|loss| (loss.path.as_str(), loss.message.as_str()))
+			.collect::<Vec<_>>();
+		assert_eq!(messages, [
+			("server", "comment has been dropped"),
+			("server", "entries have been sorted by their keys"),
+			("server/debug", "null has been replaced by an empty table")
+		]);
+		assert_eq!(conversion.losses[0].span, Some(29..37));
+	}
+
+	#[test]
+	fn can_convert_from_toml() {
+		let table = from_str::<Table>(concat!(
+			"title = \"sketch\"\n",
+			"scale = 1.5\n",
+			"[layers]\n",
+			"[[shapes]]\n",
+			"kind = \"circle\"\n"
+		)).unwrap();
+
+		let conversion = from_toml(&table);
+		let (source, nodes) = conversion.value;
+
+		assert_dump(&source, &nodes, "
+			(attacher \"scale\" \"1.5\")
+			(attacher \"title\" \"sketch\")
+			(simplex \"layers\")
+			(complex \"shapes\"
+				(content (complex \"item\" (attachers (attacher \"kind\" \"circle\")))))
+		");
+		assert_eq!(conversion.losses, Vec::new());
+	}
+}
@@ -0,0 +1,151 @@
+use crate::native::{Range, Vec, VecDeque};
+use crate::node::Node;
+
+/// Represents a node whose boundaries are ranges of bytes in the source.
+pub type RangeNode = Node<Range<usize>, Vec<Range<usize>>>;
+
+/// Represents the collection of nodes that the parser returns for ranged boundaries.
+pub type RangeNodeQueue = VecDeque<RangeNode>;
+
+/// Name of the complexes that represent compound elements of a sequence in other data models.
+#[cfg(any(feature = "serde", feature = "yaml", feature = "toml"))]
+pub(crate) const ELEMENT_NAME: &str = "item";
+
+/// Represents the boundaries that cover the concepts of a parsed tree and their descendants.
+///
+/// They are recorded while the parser promotes concepts so each is keyed by the name of its
+/// concept, which no other concept of the same source shares. Concepts with empty names are left
+/// out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Extents(Vec<(Range<usize>, Range<usize>)>);
+
+impl Extents {
+	pub(crate) fn new(extents: Vec<(Range<usize>, Range<usize>)>) -> Self {
+		Self(extents)
+	}
+
+	/// Returns the boundary that covers the node and its descendants.
+	///
+	/// Nodes without recorded extents, such as attachers, comments, and othertongues, are covered
+	/// by their spans.
+	pub fn extent_of(&self, node: &RangeNode) -> Range<usize> {
+		let span = span_of(node);
+		match node {
+			Node::Complex(_, _, _) | Node::Simplex(_, _) => self.0.iter()
+				.find(|(concept, _)| *concept == span)
+				.map(|(_, extent)| extent.clone())
+				.unwrap_or(span),
+			_ => span
+		}
+	}
+}
+
+/// Returns the bytes of the source that are covered by the boundary.
+pub fn resolve<'a>(source: &'a [u8], boundary: &Range<usize>) -> &'a [u8] {
+	&source[boundary.clone()]
+}
+
+/// Returns the boundary that covers the node without its descendants.
+///
+/// Concepts are covered by their names, attachers by their labels and contents, and blocks by their
+/// lines.
+pub(crate) fn span_of(node: &RangeNode) -> Range<usize> {
+	match node {
+		Node::Complex(concept, _, _) | Node::Simplex(concept, _) => concept.clone(),
+		Node::Attacher(label, content, _) => label.start..content.end,
+		Node::LineComment(line) | Node::LineOthertongue(line) => line.clone(),
+		Node::BlockComment(lines) | Node::BlockOthertongue(lines) => {
+			match (lines.first(), lines.last()) {
+				(Some(first), Some(last)) => first.start..last.end,
+				_ => 0..0
+			}
+		}
+	}
+}
+
+/// Returns the boundary that covers the node and its descendants.
+///
+/// Fences of blocks are not covered since they have no boundaries.
+pub(crate) fn extent_of(node: &RangeNode) -> Range<usize> {
+	let span = span_of(node);
+	let end_of = |descendant: &RangeNode| extent_of(descendant).end;
+	let end = match node {
+		Node::Complex(_, attachers, content) => {
+			attachers.iter().chain(content.iter()).map(end_of).fold(span.end, usize::max)
+		},
+		Node::Simplex(_, attachers) => attachers.iter().map(end_of).fold(span.end, usize::max),
+		_ => span.end
+	};
+	span.start..end
+}
+
+/// Returns the indices that lead to the node from the top level. Each index counts the attachers of
+/// the parent first and then its content.
+///
+/// The node is found by its address so it must be borrowed from the nodes.
+pub(crate) fn path_of(nodes: &RangeNodeQueue, node: &RangeNode) -> Option<Vec<usize>> {
+	let mut path = Vec::new();
+	if find_path(nodes.iter(), node, &mut path) { Some(path) } else { None }
+}
+
+fn find_path<'n, T>(nodes: T, node: &RangeNode, path: &mut Vec<usize>) -> bool
+where
+	T: Iterator<Item = &'n RangeNode> {
+	for (index, candidate) in nodes.enumerate() {
+		path.push(index);
+
+		let is_found = core::ptr::eq(candidate, node) || match candidate {
+			Node::Complex(_, attachers, content) => {
+				find_path(attachers.iter().chain(content.iter()), node, path)
+			},
+			Node::Simplex(_, attachers) => find_path(attachers.iter(), node, path),
+			_ => false
+		};
+		if is_found {
+			return true;
+		}
+
+		path.pop();
+	}
+
+	false
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use super::{path_of, resolve};
+
+	#[test]
+	fn can_resolve() {
+		let source = b"hello world";
+
+		let text = resolve(&source[..], &(6..11));
+
+		assert_eq!(text, b"world");
+	}
+
+	#[test]
+	fn can_find_path_by_address() {
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Simplex(0..0, VecDeque::new()));
+		nodes.push_back(Node::Complex(0..0, {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(Node::Attacher(0..0, 0..0, Vec::new()));
+			attachers
+		}, {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(0..0, VecDeque::new()));
+			content
+		}));
+
+		let path = match &nodes[1] {
+			Node::Complex(_, _, content) => path_of(&nodes, &content[0]),
+			_ => None
+		};
+
+		assert_eq!(path, Some(Vec::from([1, 1])));
+		assert_eq!(path_of(&nodes, &nodes[0].clone()), None);
+	}
+}
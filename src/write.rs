@@ -0,0 +1,184 @@
+//! Nodes are written one per line and indented by tabs according to their depth. Attachers and the
+//! content of a concept are indented one level deeper than the concept.
+//!
+//! | Node              | Written as                              |
+//! |-------------------|-----------------------------------------|
+//! | Complex           | `name`                                  |
+//! | Simplex           | `name|`                                 |
+//! | Attacher          | `label: content`                        |
+//! | Line comment      | `#line`                                 |
+//! | Block comment     | lines enclosed by `###`                 |
+//! | Line othertongue  | `= line`                                |
+//! | Block othertongue | lines enclosed by `===`                 |
+//!
+//! Lines of blocks are written as is since their boundaries include their indentation.
+
+use crate::native::{Range, Vec};
+use crate::node::Node;
+use crate::tree::{RangeNode, RangeNodeQueue, resolve};
+
+/// Returns the source that represents the nodes.
+///
+/// ## Examples
+/// ```
+/// use std::collections::VecDeque;
+/// use chearmyp_node::Node;
+/// use chearmyp_parser::to_source;
+/// let source = b"helloworld";
+/// let nodes = VecDeque::from(vec![
+/// 	Node::Complex(0..5, VecDeque::new(), VecDeque::from(vec![
+/// 		Node::Simplex(5..10, VecDeque::new())
+/// 	]))
+/// ]);
+///
+/// assert_eq!(to_source(&source[..], &nodes), b"hello\n\tworld|\n");
+/// ```
+pub fn to_source(source: &[u8], nodes: &RangeNodeQueue) -> Vec<u8> {
+	let mut output = Vec::with_capacity(source.len());
+	write_nodes(&mut output, source, nodes, 0);
+	output
+}
+
+/// Checks if the text would be read back the same after being written as a name of a concept or a
+/// label of an attacher.
+#[cfg(any(feature = "serde", feature = "yaml", feature = "toml"))]
+pub(crate) fn check_name(text: &str) -> Result<&[u8], &'static str> {
+	let text = check_text(text)?;
+
+	if text.is_empty() {
+		Err("names cannot be empty")
+	} else if text.starts_with(b"#") || text.starts_with(b"=") {
+		Err("names cannot start with `#` or `=`")
+	} else if text.contains(&b':') {
+		Err("names cannot contain `:`")
+	} else {
+		Ok(text)
+	}
+}
+
+/// Checks if the text would be read back the same after being written in a line, such as the
+/// content of an attacher.
+#[cfg(any(feature = "serde", feature = "yaml", feature = "toml"))]
+pub(crate) fn check_text(text: &str) -> Result<&[u8], &'static str> {
+	if text.contains(&['\n', '\r'][..]) {
+		Err("texts cannot span multiple lines")
+	} else if text.starts_with(char::is_whitespace) || text.ends_with(char::is_whitespace) {
+		Err("texts cannot start or end with whitespace")
+	} else if text.ends_with('|') {
+		Err("texts cannot end with `|`")
+	} else {
+		Ok(text.as_bytes())
+	}
+}
+
+fn write_nodes(output: &mut Vec<u8>, source: &[u8], nodes: &RangeNodeQueue, depth: usize) {
+	for node in nodes {
+		write_node(output, source, node, depth);
+	}
+}
+
+fn write_node(output: &mut Vec<u8>, source: &[u8], node: &RangeNode, depth: usize) {
+	match node {
+		Node::Complex(concept, attachers, content) => {
+			write_line(output, depth, &[resolve(source, concept)]);
+			write_nodes(output, source, attachers, depth + 1);
+			write_nodes(output, source, content, depth + 1);
+		},
+		Node::Simplex(concept, attachers) => {
+			write_line(output, depth, &[resolve(source, concept), &b"|"[..]]);
+			write_nodes(output, source, attachers, depth + 1);
+		},
+		Node::Attacher(label, content, _) => {
+			let parts = [resolve(source, label), &b": "[..], resolve(source, content)];
+			write_line(output, depth, &parts);
+		},
+		Node::LineComment(line) => {
+			write_line(output, depth, &[&b"#"[..], resolve(source, line)]);
+		},
+		Node::BlockComment(lines) => write_block(output, source, lines, depth, b"###"),
+		Node::LineOthertongue(line) => {
+			write_line(output, depth, &[&b"= "[..], resolve(source, line)]);
+		},
+		Node::BlockOthertongue(lines) => write_block(output, source, lines, depth, b"===")
+	}
+}
+
+fn write_block(
+	output: &mut Vec<u8>,
+	source: &[u8],
+	lines: &[Range<usize>],
+	depth: usize,
+	fence: &[u8]
+) {
+	write_line(output, depth, &[fence]);
+
+	for line in lines {
+		output.extend_from_slice(resolve(source, line));
+		output.push(b'\n');
+	}
+
+	write_line(output, depth, &[fence]);
+}
+
+fn write_line(output: &mut Vec<u8>, depth: usize, parts: &[&[u8]]) {
+	for _ in 0..depth {
+		output.push(b'\t');
+	}
+
+	for part in parts {
+		output.extend_from_slice(part);
+	}
+
+	output.push(b'\n');
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use super::to_source;
+
+	#[test]
+	fn can_write_nested_nodes() {
+		let source = b"# A sample source\nhello\n\tworld\n\t\tto: everyone\nhi universe|\n";
+		let nodes = {
+			let mut nodes = VecDeque::new();
+			nodes.push_back(Node::LineComment(1..17));
+			nodes.push_back(Node::Complex(18..23, VecDeque::new(), {
+				let mut content = VecDeque::new();
+				content.push_back(Node::Complex(25..30, {
+					let mut attachers = VecDeque::new();
+					attachers.push_back(Node::Attacher(33..35, 37..45, Vec::new()));
+					attachers
+				}, VecDeque::new()));
+				content
+			}));
+			nodes.push_back(Node::Simplex(46..57, VecDeque::new()));
+			nodes
+		};
+
+		let written_source = to_source(&source[..], &nodes);
+
+		assert_eq!(written_source, &source[..]);
+	}
+
+	#[test]
+	fn can_write_blocks() {
+		let source = b"first\nsecond";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..5, VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(Node::BlockOthertongue({
+				let mut lines = Vec::new();
+				lines.push(0..5);
+				lines.push(6..12);
+				lines
+			}));
+			content
+		}));
+
+		let written_source = to_source(&source[..], &nodes);
+
+		assert_eq!(written_source, b"first\n\t===\nfirst\nsecond\n\t===\n");
+	}
+}
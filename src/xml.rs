@@ -0,0 +1,759 @@
+//! Nodes are written inside a `chearmyp:document` element whose prefix is bound to the
+//! `urn:chearmyp` namespace:
+//!
+//! | Node              | Written as                                                      |
+//! |-------------------|-----------------------------------------------------------------|
+//! | Complex           | element with attributes and children                            |
+//! | Simplex           | empty element with attributes                                   |
+//! | Attacher          | attribute of its concept                                        |
+//! | Line comment      | XML comment                                                     |
+//! | Block comment     | XML comment whose lines are enclosed by line breaks             |
+//! | Line othertongue  | CDATA section                                                   |
+//! | Block othertongue | CDATA section whose lines are enclosed by line breaks           |
+//!
+//! Attachers that cannot be attributes, such as those with repeated labels or those without a
+//! concept, are written as `chearmyp:attacher` elements with a `label` attribute. If a simplex
+//! needs children for them, it is marked with a `chearmyp:simplex` attribute instead of being
+//! empty. Comments in attachers are written before the content of their concept.
+//!
+//! Names of concepts and attributes are escaped as `_xHHHH_` (or `_xHHHHHHHH_`) for each character
+//! that is not allowed in XML names, including `:` and any `_` followed by `x`.
+//!
+//! The conversion is lossy in a few cases. Invalid UTF-8 is replaced, `--` in comments and a
+//! trailing `-` are separated by a space, and comments in attachers are moved after the attributes.
+//!
+//! When reading, a root element other than `chearmyp:document` becomes the only top-level node.
+//! Text outside of CDATA sections is trimmed and becomes othertongue. Leading comments and
+//! attacher elements of a concept are read as its attachers.
+
+use core::fmt::{Display, Formatter, Result as FormatResult};
+use crate::native::{format, Range, String, Vec, VecDeque};
+use crate::node::Node;
+use crate::builder::TreeBuilder;
+use crate::tree::{RangeNode, RangeNodeQueue, resolve};
+
+const DOCUMENT_NAME: &str = "chearmyp:document";
+const ATTACHER_NAME: &str = "chearmyp:attacher";
+const SIMPLEX_ATTRIBUTE: &str = "chearmyp:simplex";
+const NAMESPACE: &str = "urn:chearmyp";
+
+/// Represents the error found when reading nodes from XML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlError {
+	/// Byte offset in the XML where the error has been found.
+	pub offset: usize,
+	pub message: String
+}
+
+impl Display for XmlError {
+	fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+		write!(formatter, "{} at byte {}", self.message, self.offset)
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for XmlError {}
+
+/// Returns an XML document that represents the nodes.
+///
+/// ## Examples
+/// ```
+/// use std::collections::VecDeque;
+/// use chearmyp_node::Node;
+/// use chearmyp_parser::to_xml;
+/// let source = b"hello";
+/// let nodes = VecDeque::from(vec![Node::Simplex(0..5, VecDeque::new())]);
+///
+/// assert!(to_xml(&source[..], &nodes).contains("\t<hello/>\n"));
+/// ```
+pub fn to_xml(source: &[u8], nodes: &RangeNodeQueue) -> String {
+	let mut output = String::new();
+	output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+	output.push_str(&format!("<{} xmlns:chearmyp=\"{}\">\n", DOCUMENT_NAME, NAMESPACE));
+	write_nodes(&mut output, source, nodes.iter(), 1);
+	output.push_str(&format!("</{}>\n", DOCUMENT_NAME));
+	output
+}
+
+/// Returns the nodes that represent the XML document and their source.
+pub fn from_xml(xml: &str) -> Result<(Vec<u8>, RangeNodeQueue), XmlError> {
+	let mut builder = TreeBuilder::new();
+	let mut reader = Reader { xml, offset: 0 };
+	reader.eat("\u{feff}");
+	reader.skip_miscellany()?;
+	reader.expect("<")?;
+
+	let (name, attributes, is_empty) = reader.read_start_tag()?;
+	let nodes = if name == DOCUMENT_NAME {
+		if is_empty {
+			RangeNodeQueue::new()
+		} else {
+			reader.read_children(&mut builder, name)?
+		}
+	} else {
+		let mut nodes = VecDeque::new();
+		nodes.push_back(reader.read_element(&mut builder, name, attributes, is_empty)?);
+		nodes
+	};
+
+	reader.skip_miscellany()?;
+	if reader.offset < xml.len() {
+		return Err(reader.error("unexpected content after the root element".into()));
+	}
+
+	Ok((builder.finish(), nodes))
+}
+
+fn write_nodes<'n, T>(output: &mut String, source: &[u8], nodes: T, depth: usize)
+where T: IntoIterator<Item = &'n RangeNode> {
+	for node in nodes {
+		write_node(output, source, node, depth);
+	}
+}
+
+fn write_node(output: &mut String, source: &[u8], node: &RangeNode, depth: usize) {
+	match node {
+		Node::Complex(concept, attachers, content) => {
+			let name = encode_name(&text(source, concept));
+			let (attributes, extras) = split_attachers(source, attachers);
+			write_start_tag(output, depth, &name, &attributes);
+
+			if extras.is_empty() && content.is_empty() {
+				output.push_str(&format!("></{}>\n", name));
+			} else {
+				output.push_str(">\n");
+				write_nodes(output, source, extras, depth + 1);
+				write_nodes(output, source, content, depth + 1);
+				write_end_tag(output, depth, &name);
+			}
+		},
+		Node::Simplex(concept, attachers) => {
+			let name = encode_name(&text(source, concept));
+			let (attributes, extras) = split_attachers(source, attachers);
+			write_start_tag(output, depth, &name, &attributes);
+
+			if extras.is_empty() {
+				output.push_str("/>\n");
+			} else {
+				output.push_str(&format!(" {}=\"\">\n", SIMPLEX_ATTRIBUTE));
+				write_nodes(output, source, extras, depth + 1);
+				write_end_tag(output, depth, &name);
+			}
+		},
+		Node::Attacher(label, content, _) => {
+			indent(output, depth);
+			output.push_str(&format!("<{} label=\"", ATTACHER_NAME));
+			escape_attribute(output, &text(source, label));
+			output.push_str("\">");
+			escape_text(output, &text(source, content));
+			output.push_str(&format!("</{}>\n", ATTACHER_NAME));
+		},
+		Node::LineComment(line) => {
+			indent(output, depth);
+			output.push_str("<!--");
+			output.push_str(&separate_dashes(&text(source, line)));
+			output.push_str("-->\n");
+		},
+		Node::BlockComment(lines) => {
+			indent(output, depth);
+			output.push_str("<!--\n");
+			for line in lines {
+				output.push_str(&separate_dashes(&text(source, line)));
+				output.push('\n');
+			}
+			output.push_str("-->\n");
+		},
+		Node::LineOthertongue(line) => {
+			indent(output, depth);
+			write_character_data(output, &text(source, line));
+			output.push('\n');
+		},
+		Node::BlockOthertongue(lines) => {
+			let mut data = String::from("\n");
+			for line in lines {
+				data.push_str(&text(source, line));
+				data.push('\n');
+			}
+
+			indent(output, depth);
+			write_character_data(output, &data);
+			output.push('\n');
+		}
+	}
+}
+
+fn split_attachers<'n>(source: &[u8], attachers: &'n RangeNodeQueue)
+-> (Vec<(String, String)>, Vec<&'n RangeNode>) {
+	let mut attributes: Vec<(String, String)> = Vec::new();
+	let mut extras = Vec::new();
+
+	for attacher in attachers {
+		if let Node::Attacher(label, content, _) = attacher {
+			let name = encode_name(&text(source, label));
+			let is_taken = name == "xmlns" || attributes.iter().any(|(other, _)| *other == name);
+
+			if !is_taken {
+				attributes.push((name, text(source, content)));
+				continue;
+			}
+		}
+
+		extras.push(attacher);
+	}
+
+	(attributes, extras)
+}
+
+fn write_start_tag(output: &mut String, depth: usize, name: &str, attributes: &[(String, String)]) {
+	indent(output, depth);
+	output.push('<');
+	output.push_str(name);
+
+	for (attribute, value) in attributes {
+		output.push(' ');
+		output.push_str(attribute);
+		output.push_str("=\"");
+		escape_attribute(output, value);
+		output.push('"');
+	}
+}
+
+fn write_end_tag(output: &mut String, depth: usize, name: &str) {
+	indent(output, depth);
+	output.push_str(&format!("</{}>\n", name));
+}
+
+fn write_character_data(output: &mut String, data: &str) {
+	output.push_str("<![CDATA[");
+	output.push_str(&data.replace("]]>", "]]]]><![CDATA[>"));
+	output.push_str("]]>");
+}
+
+fn indent(output: &mut String, depth: usize) {
+	for _ in 0..depth {
+		output.push('\t');
+	}
+}
+
+fn text(source: &[u8], boundary: &Range<usize>) -> String {
+	String::from_utf8_lossy(resolve(source, boundary)).into_owned()
+}
+
+fn escape_text(output: &mut String, text: &str) {
+	for character in text.chars() {
+		match character {
+			'&' => output.push_str("&amp;"),
+			'<' => output.push_str("&lt;"),
+			'>' => output.push_str("&gt;"),
+			_ => output.push(character)
+		}
+	}
+}
+
+fn escape_attribute(output: &mut String, text: &str) {
+	for character in text.chars() {
+		match character {
+			'"' => output.push_str("&quot;"),
+			'\t' => output.push_str("&#9;"),
+			'\n' => output.push_str("&#10;"),
+			'\r' => output.push_str("&#13;"),
+			_ => escape_text(output, character.encode_utf8(&mut [0; 4]))
+		}
+	}
+}
+
+fn separate_dashes(text: &str) -> String {
+	let mut separated = String::with_capacity(text.len());
+
+	for character in text.chars() {
+		if character == '-' && separated.ends_with('-') {
+			separated.push(' ');
+		}
+		separated.push(character);
+	}
+
+	if separated.ends_with('-') {
+		separated.push(' ');
+	}
+
+	separated
+}
+
+fn encode_name(name: &str) -> String {
+	let mut encoded = String::with_capacity(name.len());
+
+	for (index, character) in name.char_indices() {
+		let is_allowed = if index == 0 {
+			is_name_start(character)
+		} else {
+			is_name_character(character)
+		};
+		let is_escape_like = character == '_' && name[index + 1..].starts_with('x');
+
+		if is_allowed && character != ':' && !is_escape_like {
+			encoded.push(character);
+		} else if (character as u32) <= 0xFFFF {
+			encoded.push_str(&format!("_x{:04X}_", character as u32));
+		} else {
+			encoded.push_str(&format!("_x{:08X}_", character as u32));
+		}
+	}
+
+	encoded
+}
+
+fn decode_name(name: &str) -> String {
+	let mut decoded = String::with_capacity(name.len());
+	let mut rest = name;
+
+	while let Some(character) = rest.chars().next() {
+		match decode_escape(rest) {
+			Some((escaped_character, length)) => {
+				decoded.push(escaped_character);
+				rest = &rest[length..];
+			},
+			None => {
+				decoded.push(character);
+				rest = &rest[character.len_utf8()..];
+			}
+		}
+	}
+
+	decoded
+}
+
+fn decode_escape(text: &str) -> Option<(char, usize)> {
+	let digits = text.strip_prefix("_x")?;
+
+	[4, 8].iter().find_map(|&count| {
+		let hexadecimal = digits.get(..count)?;
+		let is_hexadecimal = hexadecimal.bytes().all(|byte| byte.is_ascii_hexdigit());
+		if !is_hexadecimal || digits.as_bytes().get(count) != Some(&b'_') {
+			return None;
+		}
+
+		let character = char::from_u32(u32::from_str_radix(hexadecimal, 16).ok()?)?;
+		Some((character, count + 3))
+	})
+}
+
+fn is_name_start(character: char) -> bool {
+	matches!(character,
+		':' | 'A'..='Z' | '_' | 'a'..='z' | '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}'
+		| '\u{F8}'..='\u{2FF}' | '\u{370}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}'
+		| '\u{200C}'..='\u{200D}' | '\u{2070}'..='\u{218F}' | '\u{2C00}'..='\u{2FEF}'
+		| '\u{3001}'..='\u{D7FF}' | '\u{F900}'..='\u{FDCF}' | '\u{FDF0}'..='\u{FFFD}'
+		| '\u{10000}'..='\u{EFFFF}')
+}
+
+fn is_name_character(character: char) -> bool {
+	is_name_start(character) || matches!(character,
+		'-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}')
+}
+
+fn split_block(text: &str) -> Option<Vec<&str>> {
+	if let Some(inner) = text.strip_prefix('\n') {
+		if inner.is_empty() {
+			Some(Vec::new())
+		} else {
+			Some(inner.strip_suffix('\n').unwrap_or(inner).split('\n').collect())
+		}
+	} else if text.contains('\n') {
+		Some(text.split('\n').collect())
+	} else {
+		None
+	}
+}
+
+struct Reader<'x> {
+	xml: &'x str,
+	offset: usize
+}
+
+impl<'x> Reader<'x> {
+	fn error(&self, message: String) -> XmlError {
+		XmlError { offset: self.offset, message }
+	}
+
+	fn rest(&self) -> &'x str {
+		&self.xml[self.offset..]
+	}
+
+	fn eat(&mut self, prefix: &str) -> bool {
+		let is_found = self.rest().starts_with(prefix);
+		if is_found {
+			self.offset += prefix.len();
+		}
+		is_found
+	}
+
+	fn expect(&mut self, prefix: &str) -> Result<(), XmlError> {
+		if self.eat(prefix) {
+			Ok(())
+		} else {
+			Err(self.error(format!("expected `{}`", prefix)))
+		}
+	}
+
+	fn skip_whitespace(&mut self) {
+		let rest = self.rest();
+		self.offset += rest.len() - rest.trim_start().len();
+	}
+
+	fn read_until(&mut self, terminator: &str, construct: &str) -> Result<&'x str, XmlError> {
+		let rest = self.rest();
+		match rest.find(terminator) {
+			Some(length) => {
+				self.offset += length + terminator.len();
+				Ok(&rest[..length])
+			},
+			None => Err(self.error(format!("unterminated {}", construct)))
+		}
+	}
+
+	fn read_name(&mut self) -> Result<&'x str, XmlError> {
+		let rest = self.rest();
+		let length = rest.char_indices()
+			.find(|&(index, character)| {
+				if index == 0 { !is_name_start(character) } else { !is_name_character(character) }
+			})
+			.map(|(index, _)| index)
+			.unwrap_or(rest.len());
+
+		if length == 0 {
+			return Err(self.error("expected a name".into()));
+		}
+
+		self.offset += length;
+		Ok(&rest[..length])
+	}
+
+	fn skip_miscellany(&mut self) -> Result<(), XmlError> {
+		loop {
+			self.skip_whitespace();
+
+			if self.eat("<?") {
+				self.read_until("?>", "processing instruction")?;
+			} else if self.eat("<!--") {
+				self.read_until("-->", "comment")?;
+			} else if self.rest().starts_with("<!") {
+				return Err(self.error("document type declarations are not supported".into()));
+			} else {
+				return Ok(());
+			}
+		}
+	}
+
+	fn read_start_tag(&mut self) -> Result<(&'x str, Vec<(&'x str, String)>, bool), XmlError> {
+		let name = self.read_name()?;
+		let mut attributes = Vec::new();
+
+		loop {
+			self.skip_whitespace();
+
+			if self.eat("/>") {
+				return Ok((name, attributes, true));
+			} else if self.eat(">") {
+				return Ok((name, attributes, false));
+			}
+
+			let attribute = self.read_name()?;
+			self.skip_whitespace();
+			self.expect("=")?;
+			self.skip_whitespace();
+
+			let quote = if self.eat("\"") {
+				"\""
+			} else if self.eat("'") {
+				"'"
+			} else {
+				return Err(self.error("expected a quoted attribute value".into()));
+			};
+			let start = self.offset;
+			let value = self.read_until(quote, "attribute value")?;
+			let value = value.replace(['\t', '\n', '\r'], " ");
+			attributes.push((attribute, self.unescape(&value, start)?));
+		}
+	}
+
+	fn read_element(
+		&mut self,
+		builder: &mut TreeBuilder,
+		name: &str,
+		attributes: Vec<(&str, String)>,
+		is_empty: bool
+	) -> Result<RangeNode, XmlError> {
+		if name == ATTACHER_NAME {
+			return self.read_attacher(builder, attributes, is_empty);
+		}
+
+		let mut attachers = RangeNodeQueue::new();
+		let mut is_simplex = is_empty;
+
+		for (attribute, value) in attributes {
+			if attribute == SIMPLEX_ATTRIBUTE {
+				is_simplex = true;
+			} else if attribute != "xmlns" && !attribute.starts_with("xmlns:") {
+				let label = decode_name(attribute);
+				attachers.push_back(builder.attacher(label.as_bytes(), value.as_bytes()));
+			}
+		}
+
+		let mut content = if is_empty {
+			RangeNodeQueue::new()
+		} else {
+			self.read_children(builder, name)?
+		};
+
+		while let Some(node) = content.front() {
+			if !matches!(node, Node::Attacher(..) | Node::LineComment(_) | Node::BlockComment(_)) {
+				break;
+			}
+			attachers.push_back(content.pop_front().unwrap());
+		}
+
+		let concept = decode_name(name);
+		if !is_simplex {
+			Ok(builder.complex(concept.as_bytes(), attachers, content))
+		} else if content.is_empty() {
+			Ok(builder.simplex(concept.as_bytes(), attachers))
+		} else {
+			Err(self.error(format!("simplex `{}` may only contain attachers and comments", concept)))
+		}
+	}
+
+	fn read_attacher(
+		&mut self,
+		builder: &mut TreeBuilder,
+		attributes: Vec<(&str, String)>,
+		is_empty: bool
+	) -> Result<RangeNode, XmlError> {
+		let label = attributes.into_iter()
+			.find(|(attribute, _)| *attribute == "label")
+			.map(|(_, value)| value)
+			.ok_or_else(|| self.error("expected a `label` attribute of the attacher".into()))?;
+
+		let content = if is_empty {
+			String::new()
+		} else {
+			let start = self.offset;
+			let content = self.read_until("</", "attacher")?;
+			let content = self.unescape(content, start)?;
+			self.read_end_tag(ATTACHER_NAME)?;
+			content
+		};
+
+		Ok(builder.attacher(label.as_bytes(), content.as_bytes()))
+	}
+
+	fn read_end_tag(&mut self, name: &str) -> Result<(), XmlError> {
+		if self.read_name()? != name {
+			return Err(self.error(format!("expected `</{}>`", name)));
+		}
+		self.skip_whitespace();
+		self.expect(">")
+	}
+
+	fn read_children(&mut self, builder: &mut TreeBuilder, parent: &str)
+	-> Result<RangeNodeQueue, XmlError> {
+		let mut nodes = RangeNodeQueue::new();
+
+		loop {
+			if self.eat("</") {
+				self.read_end_tag(parent)?;
+				return Ok(nodes);
+			} else if self.eat("<!--") {
+				let comment = self.read_until("-->", "comment")?;
+				nodes.push_back(match split_block(comment) {
+					Some(lines) => builder.block_comment(lines.iter().map(|line| line.as_bytes())),
+					None => builder.line_comment(comment.as_bytes())
+				});
+			} else if self.rest().starts_with("<![CDATA[") {
+				let mut data = String::new();
+				while self.eat("<![CDATA[") {
+					data.push_str(self.read_until("]]>", "CDATA section")?);
+				}
+				nodes.push_back(othertongue(builder, &data));
+			} else if self.eat("<?") {
+				self.read_until("?>", "processing instruction")?;
+			} else if self.rest().starts_with("<!") {
+				return Err(self.error("unsupported markup declaration".into()));
+			} else if self.eat("<") {
+				let (name, attributes, is_empty) = self.read_start_tag()?;
+				nodes.push_back(self.read_element(builder, name, attributes, is_empty)?);
+			} else if self.rest().is_empty() {
+				return Err(self.error(format!("unterminated element `{}`", parent)));
+			} else {
+				let start = self.offset;
+				let rest = self.rest();
+				let length = rest.find('<').unwrap_or(rest.len());
+				self.offset += length;
+
+				let data = self.unescape(&rest[..length], start)?;
+				let data = data.trim();
+				if !data.is_empty() {
+					nodes.push_back(othertongue(builder, data));
+				}
+			}
+		}
+	}
+
+	fn unescape(&self, text: &str, start: usize) -> Result<String, XmlError> {
+		let mut unescaped = String::with_capacity(text.len());
+		let mut rest = text;
+
+		while let Some(index) = rest.find('&') {
+			unescaped.push_str(&rest[..index]);
+			rest = &rest[index..];
+
+			let length = rest.find(';').unwrap_or(rest.len());
+			let reference = &rest[1..length];
+			let character = match reference {
+				"lt" => Some('<'),
+				"gt" => Some('>'),
+				"amp" => Some('&'),
+				"quot" => Some('"'),
+				"apos" => Some('\''),
+				_ => reference.strip_prefix("#x")
+					.map(|digits| u32::from_str_radix(digits, 16))
+					.or_else(|| reference.strip_prefix('#').map(|digits| digits.parse()))
+					.and_then(|code| code.ok())
+					.and_then(char::from_u32)
+			};
+
+			match character {
+				Some(character) if length < rest.len() => unescaped.push(character),
+				_ => {
+					let offset = start + (text.len() - rest.len());
+					let message = format!("unknown reference `{}`", &rest[..length]);
+					return Err(XmlError { offset, message });
+				}
+			}
+
+			rest = &rest[length + 1..];
+		}
+
+		unescaped.push_str(rest);
+		Ok(unescaped)
+	}
+}
+
+fn othertongue(builder: &mut TreeBuilder, data: &str) -> RangeNode {
+	match split_block(data) {
+		Some(lines) => builder.block_othertongue(lines.iter().map(|line| line.as_bytes())),
+		None => builder.line_othertongue(data.as_bytes())
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use crate::dump::assert_dump;
+	use super::{to_xml, from_xml, encode_name, decode_name};
+
+	const WRITTEN_XML: &str = concat!(
+		"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+		"<chearmyp:document xmlns:chearmyp=\"urn:chearmyp\">\n",
+		"\t<greet to=\"all &amp; &lt;you&gt;\">\n",
+		"\t\t<chearmyp:attacher label=\"to\">again</chearmyp:attacher>\n",
+		"\t\t<!--note- - -->\n",
+		"\t\t<first_x0020_name/>\n",
+		"\t\t<![CDATA[a]]]]><![CDATA[>b]]>\n",
+		"\t\t<!--\n",
+		"x\n",
+		"y\n",
+		"-->\n",
+		"\t</greet>\n",
+		"</chearmyp:document>\n"
+	);
+
+	#[test]
+	fn can_write_nodes() {
+		let source = b"greettoall & <you>againnote--first namea]]>bxy";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..5, {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(Node::Attacher(5..7, 7..18, Vec::new()));
+			attachers.push_back(Node::Attacher(5..7, 18..23, Vec::new()));
+			attachers.push_back(Node::LineComment(23..29));
+			attachers
+		}, {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(29..39, VecDeque::new()));
+			content.push_back(Node::LineOthertongue(39..44));
+			content.push_back(Node::BlockComment({
+				let mut lines = Vec::new();
+				lines.push(44..45);
+				lines.push(45..46);
+				lines
+			}));
+			content
+		}));
+
+		let xml = to_xml(&source[..], &nodes);
+
+		assert_eq!(xml, WRITTEN_XML);
+	}
+
+	#[test]
+	fn can_read_written_nodes() {
+		let (source, nodes) = from_xml(WRITTEN_XML).unwrap();
+
+		assert_dump(&source, &nodes, "
+			(complex \"greet\"
+				(attachers
+					(attacher \"to\" \"all & <you>\")
+					(attacher \"to\" \"again\")
+					(line_comment \"note- - \"))
+				(content
+					(simplex \"first name\")
+					(line_othertongue \"a]]>b\")
+					(block_comment \"x\" \"y\")))
+		");
+	}
+
+	#[test]
+	fn can_read_foreign_xml() {
+		let xml = concat!(
+			"<?xml version=\"1.0\"?>\n",
+			"<!-- exported -->\n",
+			"<config version=\"2\">\n",
+			"\t<server host=\"a\" port='80'/>\n",
+			"\t<motd>Hello &amp; welcome&#33;</motd>\n",
+			"\t<empty></empty>\n",
+			"</config>\n"
+		);
+
+		let (source, nodes) = from_xml(xml).unwrap();
+
+		assert_dump(&source, &nodes, "
+			(complex \"config\"
+				(attachers (attacher \"version\" \"2\"))
+				(content
+					(simplex \"server\"
+						(attachers (attacher \"host\" \"a\") (attacher \"port\" \"80\")))
+					(complex \"motd\" (content (line_othertongue \"Hello & welcome!\")))
+					(complex \"empty\")))
+		");
+	}
+
+	#[test]
+	fn can_escape_names() {
+		let names = ["hello world", "1st", "a_xb", "ns:name", "plain"];
+
+		for name in names {
+			assert_eq!(decode_name(&encode_name(name)), name);
+		}
+		assert_eq!(encode_name("1st"), "_x0031_st");
+		assert_eq!(encode_name("a_xb"), "a_x005F_xb");
+	}
+
+	#[test]
+	fn cannot_read_mismatched_tags() {
+		let error = from_xml("<a><b></a></b>").unwrap_err();
+
+		assert_eq!(error.offset, 9);
+		assert_eq!(error.message, "expected `</b>`");
+	}
+}
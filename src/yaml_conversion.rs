@@ -0,0 +1,180 @@
+use serde_yaml::{Mapping, Number, Value};
+use crate::native::{format, String, Vec};
+use crate::conversion::{
+	Conversion,
+	Loss,
+	Neutral,
+	Scalar,
+	document_to_neutral,
+	neutral_to_document,
+	infer_scalar,
+	join,
+	report
+};
+use crate::tree::RangeNodeQueue;
+
+/// Returns the YAML value that represents the nodes.
+///
+/// See the documentation of [`Conversion`] for the rules.
+///
+/// ## Examples
+/// ```
+/// use std::collections::VecDeque;
+/// use chearmyp_node::Node;
+/// use chearmyp_parser::to_yaml;
+/// let source = b"port: 80\n# default port";
+/// let nodes = VecDeque::from(vec![
+/// 	Node::Attacher(0..4, 6..8, vec![]),
+/// 	Node::LineComment(10..23)
+/// ]);
+///
+/// let conversion = to_yaml(&source[..], &nodes);
+///
+/// assert_eq!(conversion.value, serde_yaml::from_str::<serde_yaml::Value>("port: 80").unwrap());
+/// assert_eq!(conversion.losses[0].span, Some(10..23));
+/// ```
+pub fn to_yaml(source: &[u8], nodes: &RangeNodeQueue) -> Conversion<Value> {
+	let mut losses = Vec::new();
+	let value = neutral_to_yaml(document_to_neutral(source, nodes, &mut losses));
+	Conversion { value, losses }
+}
+
+/// Returns the nodes that represent the YAML value and their source.
+pub fn from_yaml(value: &Value) -> Conversion<(Vec<u8>, RangeNodeQueue)> {
+	let mut losses = Vec::new();
+	let value = yaml_to_neutral(value, "", &mut losses);
+	let value = neutral_to_document(value, &mut losses);
+	Conversion { value, losses }
+}
+
+fn neutral_to_yaml(value: Neutral) -> Value {
+	match value {
+		Neutral::Null => Value::Null,
+		Neutral::Scalar(text) => match infer_scalar(&text) {
+			Scalar::Boolean(boolean) => Value::Bool(boolean),
+			Scalar::Integer(integer) => Value::Number(Number::from(integer)),
+			Scalar::Float(float) => Value::Number(Number::from(float)),
+			Scalar::Text(_) => Value::String(text)
+		},
+		Neutral::Sequence(elements) => {
+			Value::Sequence(elements.into_iter().map(neutral_to_yaml).collect())
+		},
+		Neutral::Map(entries) => {
+			let mut mapping = Mapping::new();
+
+			for (key, value) in entries {
+				mapping.insert(Value::String(key), neutral_to_yaml(value));
+			}
+
+			Value::Mapping(mapping)
+		}
+	}
+}
+
+fn yaml_to_neutral(value: &Value, path: &str, losses: &mut Vec<Loss>) -> Neutral {
+	match value {
+		Value::Null => Neutral::Null,
+		Value::Bool(boolean) => Neutral::Scalar(format!("{}", boolean)),
+		Value::Number(number) => Neutral::Scalar(format!("{}", number)),
+		Value::String(text) => Neutral::from_text(text.clone(), path, losses),
+		Value::Sequence(elements) => {
+			let elements = elements.iter().enumerate().map(|(index, element)| {
+				yaml_to_neutral(element, &join(path, &format!("{}", index)), losses)
+			}).collect();
+			Neutral::Sequence(elements)
+		},
+		Value::Mapping(mapping) => {
+			let mut entries = Vec::with_capacity(mapping.len());
+
+			for (key, value) in mapping {
+				match yaml_key(key) {
+					Some(key) => {
+						let value = yaml_to_neutral(value, &join(path, &key), losses);
+						entries.push((key, value));
+					},
+					None => report(losses, path, None, "entry with a compound key has been dropped")
+				}
+			}
+
+			Neutral::Map(entries)
+		},
+		Value::Tagged(tagged_value) => {
+			let message = format!("tag `{}` has been dropped", tagged_value.tag);
+			report(losses, path, None, &message);
+			yaml_to_neutral(&tagged_value.value, path, losses)
+		}
+	}
+}
+
+fn yaml_key(key: &Value) -> Option<String> {
+	match key {
+		Value::Null => Some("null".into()),
+		Value::Bool(boolean) => Some(format!("{}", boolean)),
+		Value::Number(number) => Some(format!("{}", number)),
+		Value::String(text) => Some(text.clone()),
+		_ => None
+	}
+}
+
+#[cfg(test)]
+mod t {
+	use serde_yaml::{from_str, Value};
+	use crate::native::{Vec, VecDeque};
+	use crate::node::Node;
+	use crate::dump::assert_dump;
+	use super::{to_yaml, from_yaml};
+
+	#[test]
+	fn can_convert_to_yaml() {
+		let source = b"server\n\thost: localhost\n\tport: 80\n\tdebug|\nnames\n\talpha|\n\tbeta|";
+		let mut nodes = VecDeque::new();
+		nodes.push_back(Node::Complex(0..6, {
+			let mut attachers = VecDeque::new();
+			attachers.push_back(Node::Attacher(8..12, 14..23, Vec::new()));
+			attachers.push_back(Node::Attacher(25..29, 31..33, Vec::new()));
+			attachers
+		}, {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(35..40, VecDeque::new()));
+			content
+		}));
+		nodes.push_back(Node::Complex(42..47, VecDeque::new(), {
+			let mut content = VecDeque::new();
+			content.push_back(Node::Simplex(49..54, VecDeque::new()));
+			content.push_back(Node::Simplex(57..61, VecDeque::new()));
+			content
+		}));
+
+		let conversion = to_yaml(&source[..], &nodes);
+
+		assert_eq!(conversion.value, from_str::<Value>(concat!(
+			"server:\n",
+			"  host: localhost\n",
+			"  port: 80\n",
+			"  debug: null\n",
+			"names: [alpha, beta]\n"
+		)).unwrap());
+		assert_eq!(conversion.losses, Vec::new());
+	}
+
+	#[test]
+	fn can_convert_from_yaml() {
+		let value = from_str::<Value>(concat!(
+			"title: !special sketch\n",
+			"description: |\n",
+			"  first line\n",
+			"  second line\n",
+			"tags: [draft, ~]\n"
+		)).unwrap();
+
+		let conversion = from_yaml(&value);
+		let (source, nodes) = conversion.value;
+
+		assert_dump(&source, &nodes, "
+			(attacher \"title\" \"sketch\")
+			(attacher \"description\" \"first line second line\")
+			(complex \"tags\" (content (simplex \"draft\")))
+		");
+		assert_eq!(conversion.losses.len(), 3);
+	}
+}